@@ -5,13 +5,12 @@ use super::BlackScholesInputs;
 use crate::option::{Call, FinancialOption, Put};
 use crate::result::PricerResult;
 use crate::risk_factors::RiskFactors;
+use crate::utils::date::DAYS_IN_YEAR;
 
 use chrono::{DateTime, Utc};
 
 use statrs::distribution::{Continuous, ContinuousCDF};
 
-static DAYS_IN_YEAR: u32 = 365;
-
 // Source of equations: https://www.macroption.com/black-scholes-formula/
 
 type BlackScholesGreekImplementation =
@@ -24,7 +23,8 @@ fn map_to_impl(
     implementation: BlackScholesGreekImplementation,
 ) -> PricerResult<f64> {
     risk_factors
-        .try_into()
+        .as_of(valuation_time)
+        .and_then(TryInto::try_into)
         .and_then(|risk_factors| {
             greeks.is_sensitive_to_risk_factors(&risk_factors)?;
             Ok(risk_factors)
@@ -90,8 +90,8 @@ impl BlackScholesGreeks for Call {
     }
     fn gamma_impl(&self, inputs: BlackScholesInputs) -> PricerResult<f64> {
         let (d1, _) = get_d1_and_d2(self.strike(), &inputs);
-        let one_over_price_vol_delta_t =
-            inputs.dividend_adjustment() / (inputs.price() * inputs.volatility_for_delta_t());
+        let one_over_price_vol_delta_t = inputs.dividend_adjustment()
+            / (inputs.escrowed_price() * inputs.volatility_for_delta_t());
         gaussian().map(|gaussian| gaussian.pdf(d1) * one_over_price_vol_delta_t)
     }
     fn rho_impl(&self, inputs: BlackScholesInputs) -> PricerResult<f64> {
@@ -102,12 +102,12 @@ impl BlackScholesGreeks for Call {
     }
     fn theta_impl(&self, inputs: BlackScholesInputs) -> PricerResult<f64> {
         let (d1, d2) = get_d1_and_d2(self.strike(), &inputs);
-        let lost_price_movement =
-            -(inputs.price() * inputs.volatility_for_delta_t()) / (2.0 * inputs.delta_t);
+        let lost_price_movement = -(inputs.dividend_adjusted_price()
+            * inputs.volatility_for_delta_t())
+            / (2.0 * inputs.delta_t);
         let risk_free_adjustment =
             -(inputs.discount_rate() * self.strike() * inputs.risk_free_adjustment());
-        let dividend_adjustment =
-            inputs.annualised_dividend_rate() * inputs.dividend_adjusted_price();
+        let dividend_adjustment = inputs.forward_yield() * inputs.dividend_adjusted_price();
         gaussian()
             .map(|gaussian| {
                 lost_price_movement * gaussian.pdf(d1)
@@ -131,8 +131,8 @@ impl BlackScholesGreeks for Put {
     }
     fn gamma_impl(&self, inputs: BlackScholesInputs) -> PricerResult<f64> {
         let (d1, _) = get_d1_and_d2(self.strike(), &inputs);
-        let one_over_price_vol_delta_t =
-            inputs.dividend_adjustment() / (inputs.price() * inputs.volatility_for_delta_t());
+        let one_over_price_vol_delta_t = inputs.dividend_adjustment()
+            / (inputs.escrowed_price() * inputs.volatility_for_delta_t());
         gaussian().map(|gaussian| gaussian.pdf(d1) * one_over_price_vol_delta_t)
     }
     fn rho_impl(&self, inputs: BlackScholesInputs) -> PricerResult<f64> {
@@ -147,12 +147,12 @@ impl BlackScholesGreeks for Put {
     }
     fn theta_impl(&self, inputs: BlackScholesInputs) -> PricerResult<f64> {
         let (d1, d2) = get_d1_and_d2(self.strike(), &inputs);
-        let lost_price_movement =
-            -(inputs.price() * inputs.volatility_for_delta_t()) / (2.0 * inputs.delta_t);
+        let lost_price_movement = -(inputs.dividend_adjusted_price()
+            * inputs.volatility_for_delta_t())
+            / (2.0 * inputs.delta_t);
         let risk_free_adjustment =
             inputs.discount_rate() * self.strike() * inputs.risk_free_adjustment();
-        let dividend_adjustment =
-            -inputs.annualised_dividend_rate() * inputs.dividend_adjusted_price();
+        let dividend_adjustment = -inputs.forward_yield() * inputs.dividend_adjusted_price();
         gaussian()
             .map(|gaussian| {
                 lost_price_movement * gaussian.pdf(d1)
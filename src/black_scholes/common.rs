@@ -2,17 +2,26 @@ use super::BlackScholesInputs;
 
 use crate::result::{PricerError, PricerResult};
 
-use statrs::distribution::Normal;
+use statrs::distribution::{ContinuousCDF, Normal};
 use statrs::StatsError;
 
 pub fn get_d1_and_d2(strike: f64, inputs: &BlackScholesInputs) -> (f64, f64) {
-    let ln_val_over_strike = (inputs.price() / strike).ln();
-    let rfr_minus_dividends_plus_vol_squared_over_two = inputs.discount_rate()
-        - inputs.annualised_dividend_rate()
-        + (inputs.volatility().powi(2) / 2f64);
-    let d1 = (ln_val_over_strike + rfr_minus_dividends_plus_vol_squared_over_two * inputs.delta_t)
-        / inputs.volatility_for_delta_t();
-    let d2 = d1 - inputs.volatility_for_delta_t();
+    d1_and_d2(
+        inputs.escrowed_price() * inputs.proportional_dividend_adjustment(),
+        strike,
+        inputs.delta_t,
+        inputs.discount_rate() - inputs.forward_yield(),
+        inputs.volatility(),
+    )
+}
+
+fn d1_and_d2(spot: f64, strike: f64, delta_t: f64, carry: f64, volatility: f64) -> (f64, f64) {
+    let ln_val_over_strike = (spot / strike).ln();
+    let carry_plus_vol_squared_over_two = carry + (volatility.powi(2) / 2f64);
+    let volatility_for_delta_t = volatility * delta_t.sqrt();
+    let d1 =
+        (ln_val_over_strike + carry_plus_vol_squared_over_two * delta_t) / volatility_for_delta_t;
+    let d2 = d1 - volatility_for_delta_t;
     (d1, d2)
 }
 
@@ -28,3 +37,61 @@ fn failed_to_create_gaussian_error(_: StatsError) -> PricerError {
 pub fn gaussian() -> PricerResult<Normal> {
     Normal::new(0.0, 1.0).map_err(failed_to_create_gaussian_error)
 }
+
+// Value of a European option from its forward and strike discounted to today
+fn discounted_value(
+    is_call: bool,
+    discounted_forward: f64,
+    discounted_strike: f64,
+    (d1, d2): (f64, f64),
+) -> PricerResult<f64> {
+    gaussian().map(|gaussian| {
+        if is_call {
+            discounted_forward * gaussian.cdf(d1) - discounted_strike * gaussian.cdf(d2)
+        } else {
+            discounted_strike * gaussian.cdf(-d2) - discounted_forward * gaussian.cdf(-d1)
+        }
+    })
+}
+
+pub fn black_scholes_value(
+    is_call: bool,
+    strike: f64,
+    inputs: &BlackScholesInputs,
+) -> PricerResult<f64> {
+    discounted_value(
+        is_call,
+        inputs.dividend_adjusted_price(),
+        strike * inputs.risk_free_adjustment(),
+        get_d1_and_d2(strike, inputs),
+    )
+}
+
+// Closed form price of a European option on an underlying paying the continuous yield
+// `dividend_yield`, for callers that hold plain numbers rather than risk factors
+pub fn black_scholes_price(
+    is_call: bool,
+    spot: f64,
+    strike: f64,
+    delta_t: f64,
+    rate: f64,
+    dividend_yield: f64,
+    volatility: f64,
+) -> PricerResult<f64> {
+    let discounted_forward = spot * (-dividend_yield * delta_t).exp();
+    let discounted_strike = strike * (-rate * delta_t).exp();
+    if volatility * delta_t.sqrt() <= 0.0 {
+        let intrinsic = discounted_forward - discounted_strike;
+        return Ok(if is_call {
+            intrinsic.max(0.0)
+        } else {
+            (-intrinsic).max(0.0)
+        });
+    }
+    discounted_value(
+        is_call,
+        discounted_forward,
+        discounted_strike,
+        d1_and_d2(spot, strike, delta_t, rate - dividend_yield, volatility),
+    )
+}
@@ -1 +1,392 @@
+use super::BlackScholes;
+use super::BlackScholesInputs;
 
+use crate::option::{Call, ExerciseStyle, FinancialOption, Put};
+use crate::result::{PricerError, PricerResult};
+use crate::risk_factors::RiskFactors;
+use crate::shock::{ApplyShock, Scenario};
+use crate::utils::date::DAYS_IN_YEAR;
+
+use chrono::{DateTime, Utc};
+
+// Theta-scheme on a uniform log-spot grid, stepping backwards from expiry in time to expiry:
+//   dV/dtau = 0.5 * sigma^2 * V_xx + (r - q - 0.5 * sigma^2) * V_x - r * V, where x = ln(S)
+
+pub enum FiniteDifferenceScheme {
+    Explicit,
+    Implicit,
+    // Rannacher start-up: the first `rannacher_steps` steps are each replaced by two implicit
+    // half-steps to damp the oscillations Crank-Nicolson produces around the payoff kink
+    CrankNicolson { rannacher_steps: usize },
+}
+
+impl FiniteDifferenceScheme {
+    fn implicitness(&self) -> f64 {
+        match self {
+            FiniteDifferenceScheme::Explicit => 0.0,
+            FiniteDifferenceScheme::Implicit => 1.0,
+            FiniteDifferenceScheme::CrankNicolson { .. } => 0.5,
+        }
+    }
+    fn rannacher_steps(&self) -> usize {
+        match self {
+            FiniteDifferenceScheme::CrankNicolson { rannacher_steps } => *rannacher_steps,
+            _ => 0,
+        }
+    }
+}
+
+pub enum BoundaryCondition {
+    // Asymptotic discounted values at the edges of the grid
+    Dirichlet,
+    // Zero gamma at the edges, the value is extrapolated linearly in spot
+    Linear,
+}
+
+pub struct FiniteDifferenceParams {
+    pub scheme: FiniteDifferenceScheme,
+    pub boundary_condition: BoundaryCondition,
+    pub exercise_style: ExerciseStyle,
+    pub spot_steps: usize,
+    pub time_steps: usize,
+    // Half-width of the grid in standard deviations of ln(S) at expiry
+    pub standard_deviations: f64,
+}
+
+impl Default for FiniteDifferenceParams {
+    fn default() -> Self {
+        FiniteDifferenceParams {
+            scheme: FiniteDifferenceScheme::CrankNicolson { rannacher_steps: 2 },
+            boundary_condition: BoundaryCondition::Linear,
+            exercise_style: ExerciseStyle::European,
+            spot_steps: 400,
+            time_steps: 200,
+            standard_deviations: 5.0,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct FiniteDifferenceValuation {
+    pub value: f64,
+    pub delta: f64,
+    pub gamma: f64,
+    pub theta: f64,
+}
+
+fn invalid_params_err(message: &str) -> PricerError {
+    PricerError::new(
+        format!("Invalid finite difference parameters: {}", message),
+        6,
+    )
+}
+
+struct LogSpotGrid {
+    spots: Vec<f64>,
+    dx: f64,
+    centre: usize,
+}
+
+impl LogSpotGrid {
+    fn build(
+        inputs: &BlackScholesInputs,
+        parameters: &FiniteDifferenceParams,
+    ) -> PricerResult<Self> {
+        if parameters.spot_steps < 4 {
+            return Err(invalid_params_err("at least 4 spot steps are required"));
+        }
+        if parameters.time_steps == 0 {
+            return Err(invalid_params_err("at least 1 time step is required"));
+        }
+        let width = parameters.standard_deviations * inputs.volatility_for_delta_t();
+        if width.is_nan() || width <= 0.0 || inputs.delta_t <= 0.0 {
+            return Err(invalid_params_err(
+                "the grid requires positive volatility and time to expiry",
+            ));
+        }
+        let centre = parameters.spot_steps / 2;
+        let dx = width / centre as f64;
+        // Discrete dividends follow the escrowed dividend model, so the grid is laid out around
+        // the price net of the dividends outstanding and early exercise is against that price
+        let log_spot = (inputs.escrowed_price() * inputs.proportional_dividend_adjustment()).ln();
+        let spots = (0..=2 * centre)
+            .map(|i| (log_spot + (i as f64 - centre as f64) * dx).exp())
+            .collect();
+        Ok(LogSpotGrid { spots, dx, centre })
+    }
+    fn len(&self) -> usize {
+        self.spots.len()
+    }
+}
+
+// Coefficients of the spatial operator L applied to (V_{i-1}, V_i, V_{i+1})
+struct Operator {
+    lower: f64,
+    diagonal: f64,
+    upper: f64,
+}
+
+impl Operator {
+    fn new(inputs: &BlackScholesInputs, dx: f64) -> Operator {
+        let variance = inputs.volatility().powi(2);
+        let diffusion = 0.5 * variance / dx.powi(2);
+        let drift = (inputs.discount_rate() - inputs.forward_yield() - 0.5 * variance) / (2.0 * dx);
+        Operator {
+            lower: diffusion - drift,
+            diagonal: -2.0 * diffusion - inputs.discount_rate(),
+            upper: diffusion + drift,
+        }
+    }
+    fn apply(&self, values: &[f64], i: usize) -> f64 {
+        self.lower * values[i - 1] + self.diagonal * values[i] + self.upper * values[i + 1]
+    }
+}
+
+fn solve_tridiagonal(lower: &[f64], diagonal: &[f64], upper: &[f64], rhs: &[f64]) -> Vec<f64> {
+    let n = diagonal.len();
+    let mut c_prime = vec![0.0; n];
+    let mut d_prime = vec![0.0; n];
+    c_prime[0] = upper[0] / diagonal[0];
+    d_prime[0] = rhs[0] / diagonal[0];
+    for i in 1..n {
+        let denominator = diagonal[i] - lower[i] * c_prime[i - 1];
+        c_prime[i] = upper[i] / denominator;
+        d_prime[i] = (rhs[i] - lower[i] * d_prime[i - 1]) / denominator;
+    }
+    let mut solution = vec![0.0; n];
+    solution[n - 1] = d_prime[n - 1];
+    for i in (0..n - 1).rev() {
+        solution[i] = d_prime[i] - c_prime[i] * solution[i + 1];
+    }
+    solution
+}
+
+pub trait BlackScholesFiniteDifference: BlackScholes {
+    // Values at the lowest and highest spot on the grid, `time_to_expiry` before expiry
+    fn dirichlet_boundaries(
+        &self,
+        inputs: &BlackScholesInputs,
+        spot_range: (f64, f64),
+        time_to_expiry: f64,
+    ) -> (f64, f64);
+    fn intrinsic_value(&self, spot: f64) -> f64 {
+        self.value_if_executed(spot).max(0.0)
+    }
+    fn value_finite_difference_impl(
+        &self,
+        inputs: BlackScholesInputs,
+        parameters: &FiniteDifferenceParams,
+    ) -> PricerResult<FiniteDifferenceValuation> {
+        solve_on_grid(self, &inputs, parameters).map(|valuation| FiniteDifferenceValuation {
+            value: valuation.value - self.cost(),
+            ..valuation
+        })
+    }
+    fn value_finite_difference(
+        &self,
+        valuation_time: DateTime<Utc>,
+        risk_factors: RiskFactors,
+        shock_scenarios: Scenario,
+        parameters: FiniteDifferenceParams,
+    ) -> PricerResult<FiniteDifferenceValuation> {
+        let check_sensitivity_to_risk_factors = |risk_factors| {
+            self.is_sensitive_to_risk_factors(&risk_factors)?;
+            Ok(risk_factors)
+        };
+        let gather_model_inputs =
+            |risk_factors| BlackScholesInputs::gather(self.expiry(), valuation_time, risk_factors);
+        let shock_inputs = |mut inputs| {
+            shock_scenarios.apply(&mut inputs);
+            inputs
+        };
+        risk_factors
+            .as_of(valuation_time)
+            .and_then(TryInto::try_into)
+            .and_then(check_sensitivity_to_risk_factors)
+            .map(gather_model_inputs)
+            .map(shock_inputs)
+            .and_then(|inputs| self.value_finite_difference_impl(inputs, &parameters))
+    }
+}
+
+fn apply_boundaries<O: BlackScholesFiniteDifference + ?Sized>(
+    option: &O,
+    inputs: &BlackScholesInputs,
+    parameters: &FiniteDifferenceParams,
+    grid: &LogSpotGrid,
+    time_to_expiry: f64,
+    values: &mut [f64],
+) {
+    let n = grid.len();
+    match parameters.boundary_condition {
+        BoundaryCondition::Dirichlet => {
+            let (low, high) = option.dirichlet_boundaries(
+                inputs,
+                (grid.spots[0], grid.spots[n - 1]),
+                time_to_expiry,
+            );
+            values[0] = low;
+            values[n - 1] = high;
+        }
+        BoundaryCondition::Linear => {
+            let ratio = (-grid.dx).exp();
+            values[0] = (1.0 + ratio) * values[1] - ratio * values[2];
+            let ratio = grid.dx.exp();
+            values[n - 1] = (1.0 + ratio) * values[n - 2] - ratio * values[n - 3];
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn step<O: BlackScholesFiniteDifference + ?Sized>(
+    option: &O,
+    inputs: &BlackScholesInputs,
+    parameters: &FiniteDifferenceParams,
+    grid: &LogSpotGrid,
+    operator: &Operator,
+    implicitness: f64,
+    dt: f64,
+    time_to_expiry: f64,
+    values: &[f64],
+) -> Vec<f64> {
+    let n = grid.len();
+    let interior = n - 2;
+    let explicit_weight = (1.0 - implicitness) * dt;
+    let implicit_weight = implicitness * dt;
+
+    let mut rhs: Vec<f64> = (1..n - 1)
+        .map(|i| values[i] + explicit_weight * operator.apply(values, i))
+        .collect();
+    let mut lower = vec![-implicit_weight * operator.lower; interior];
+    let mut diagonal = vec![1.0 - implicit_weight * operator.diagonal; interior];
+    let mut upper = vec![-implicit_weight * operator.upper; interior];
+
+    match parameters.boundary_condition {
+        BoundaryCondition::Dirichlet => {
+            let (low, high) = option.dirichlet_boundaries(
+                inputs,
+                (grid.spots[0], grid.spots[n - 1]),
+                time_to_expiry,
+            );
+            rhs[0] -= lower[0] * low;
+            rhs[interior - 1] -= upper[interior - 1] * high;
+        }
+        BoundaryCondition::Linear => {
+            // Substitute the extrapolated edge values so the system stays tridiagonal
+            let ratio = (-grid.dx).exp();
+            diagonal[0] += lower[0] * (1.0 + ratio);
+            upper[0] -= lower[0] * ratio;
+            let ratio = grid.dx.exp();
+            diagonal[interior - 1] += upper[interior - 1] * (1.0 + ratio);
+            lower[interior - 1] -= upper[interior - 1] * ratio;
+        }
+    }
+    lower[0] = 0.0;
+    upper[interior - 1] = 0.0;
+
+    let mut next = Vec::with_capacity(n);
+    next.push(0.0);
+    next.extend(solve_tridiagonal(&lower, &diagonal, &upper, &rhs));
+    next.push(0.0);
+    apply_boundaries(option, inputs, parameters, grid, time_to_expiry, &mut next);
+
+    if let ExerciseStyle::American = parameters.exercise_style {
+        next.iter_mut()
+            .zip(grid.spots.iter())
+            .for_each(|(value, spot)| *value = value.max(option.intrinsic_value(*spot)));
+    }
+    next
+}
+
+fn solve_on_grid<O: BlackScholesFiniteDifference + ?Sized>(
+    option: &O,
+    inputs: &BlackScholesInputs,
+    parameters: &FiniteDifferenceParams,
+) -> PricerResult<FiniteDifferenceValuation> {
+    let grid = LogSpotGrid::build(inputs, parameters)?;
+    let operator = Operator::new(inputs, grid.dx);
+    let dt = inputs.delta_t / parameters.time_steps as f64;
+
+    if let FiniteDifferenceScheme::Explicit = parameters.scheme {
+        if dt * operator.diagonal.abs() > 1.0 {
+            return Err(invalid_params_err(
+                "explicit scheme is unstable for this grid, increase the number of time steps",
+            ));
+        }
+    }
+
+    let mut values: Vec<f64> = grid
+        .spots
+        .iter()
+        .map(|spot| option.intrinsic_value(*spot))
+        .collect();
+    let mut previous_centre = values[grid.centre];
+    let mut last_dt = dt;
+    let mut time_to_expiry = 0.0;
+
+    let rannacher_steps = parameters.scheme.rannacher_steps();
+    for time_step in 0..parameters.time_steps {
+        let (implicitness, sub_steps) = if time_step < rannacher_steps {
+            (1.0, 2)
+        } else {
+            (parameters.scheme.implicitness(), 1)
+        };
+        let sub_dt = dt / sub_steps as f64;
+        for _ in 0..sub_steps {
+            previous_centre = values[grid.centre];
+            time_to_expiry += sub_dt;
+            values = step(
+                option,
+                inputs,
+                parameters,
+                &grid,
+                &operator,
+                implicitness,
+                sub_dt,
+                time_to_expiry,
+                &values,
+            );
+        }
+        last_dt = sub_dt;
+    }
+
+    let c = grid.centre;
+    let first_derivative = (values[c + 1] - values[c - 1]) / (2.0 * grid.dx);
+    let second_derivative = (values[c + 1] - 2.0 * values[c] + values[c - 1]) / grid.dx.powi(2);
+    let spot = grid.spots[c];
+    let dividend_scaling = inputs.proportional_dividend_adjustment();
+    Ok(FiniteDifferenceValuation {
+        value: values[c],
+        delta: dividend_scaling * first_derivative / spot,
+        gamma: dividend_scaling.powi(2) * (second_derivative - first_derivative) / spot.powi(2),
+        theta: (previous_centre - values[c]) / last_dt / DAYS_IN_YEAR as f64,
+    })
+}
+
+impl BlackScholesFiniteDifference for Call {
+    fn dirichlet_boundaries(
+        &self,
+        inputs: &BlackScholesInputs,
+        spot_range: (f64, f64),
+        time_to_expiry: f64,
+    ) -> (f64, f64) {
+        let (_, high_spot) = spot_range;
+        let forward_value = high_spot * (-inputs.forward_yield() * time_to_expiry).exp()
+            - self.strike() * (-inputs.discount_rate() * time_to_expiry).exp();
+        (0.0, forward_value.max(0.0))
+    }
+}
+
+impl BlackScholesFiniteDifference for Put {
+    fn dirichlet_boundaries(
+        &self,
+        inputs: &BlackScholesInputs,
+        spot_range: (f64, f64),
+        time_to_expiry: f64,
+    ) -> (f64, f64) {
+        let (low_spot, _) = spot_range;
+        let forward_value = self.strike() * (-inputs.discount_rate() * time_to_expiry).exp()
+            - low_spot * (-inputs.forward_yield() * time_to_expiry).exp();
+        (forward_value.max(0.0), 0.0)
+    }
+}
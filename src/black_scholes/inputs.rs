@@ -1,5 +1,8 @@
 use super::risk_factors::BlackScholesRiskFactors;
 
+use crate::risk_factors::dividend::{
+    present_value_of_cash, proportional_factor, OutstandingDividend,
+};
 use crate::shock::{ApplyShock, Scenario, Shock};
 
 use crate::utils::date::get_duration_in_years;
@@ -8,6 +11,7 @@ use chrono::{DateTime, Utc};
 
 pub struct BlackScholesInputs {
     pub delta_t: f64,
+    expiry: DateTime<Utc>,
     risk_factors: BlackScholesRiskFactors,
 }
 
@@ -20,20 +24,28 @@ impl BlackScholesInputs {
         let delta_t = get_duration_in_years(valuation_time, expiry);
         BlackScholesInputs {
             delta_t,
+            expiry,
             risk_factors,
         }
     }
     pub fn discount_rate(&self) -> f64 {
-        self.risk_factors.discount_rate()
+        self.risk_factors.discount_rate(self.delta_t)
     }
     pub fn annualised_dividend_rate(&self) -> f64 {
         self.risk_factors.annualised_dividend_rate()
     }
+    pub fn borrow_rate(&self) -> f64 {
+        self.risk_factors.borrow_rate()
+    }
+    // Continuous yields that reduce the forward, the dividend yield and the stock borrow fee
+    pub fn forward_yield(&self) -> f64 {
+        self.annualised_dividend_rate() + self.borrow_rate()
+    }
     pub fn price(&self) -> f64 {
         self.risk_factors.price()
     }
     pub fn volatility(&self) -> f64 {
-        self.risk_factors.volatility()
+        self.risk_factors.volatility(self.delta_t)
     }
     pub fn risk_free_adjustment(&self) -> f64 {
         self.risk_factors.discount_factor(self.delta_t)
@@ -42,11 +54,24 @@ impl BlackScholesInputs {
         self.risk_factors.volatility_for_delta_t(self.delta_t)
     }
 
+    // Discrete dividends follow the escrowed dividend model, cash dividends are taken out of the
+    // price up front and proportional dividends scale the price in the same way as a yield
+    pub fn outstanding_dividends(&self) -> Vec<OutstandingDividend> {
+        self.risk_factors
+            .outstanding_dividends(self.expiry, self.delta_t)
+    }
+    pub fn escrowed_price(&self) -> f64 {
+        self.price() - present_value_of_cash(&self.outstanding_dividends(), self.discount_rate())
+    }
+    pub fn proportional_dividend_adjustment(&self) -> f64 {
+        proportional_factor(&self.outstanding_dividends())
+    }
     pub fn dividend_adjustment(&self) -> f64 {
-        (-self.annualised_dividend_rate() * self.delta_t).exp()
+        (-self.forward_yield() * self.delta_t).exp()
+            * self.proportional_dividend_adjustment()
     }
     pub fn dividend_adjusted_price(&self) -> f64 {
-        self.price() * self.dividend_adjustment()
+        self.escrowed_price() * self.dividend_adjustment()
     }
 }
 
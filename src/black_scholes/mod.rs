@@ -11,4 +11,9 @@ use risk_factors::BlackScholesRiskFactors;
 use inputs::BlackScholesInputs;
 
 pub use analytical_greeks::BlackScholesGreeks;
+pub use common::black_scholes_price;
+pub use finite_difference::{
+    BlackScholesFiniteDifference, BoundaryCondition, FiniteDifferenceParams,
+    FiniteDifferenceScheme, FiniteDifferenceValuation,
+};
 pub use pricing::BlackScholes;
@@ -1,4 +1,4 @@
-use super::common::{gaussian, get_d1_and_d2};
+use super::common::black_scholes_value;
 use super::BlackScholesInputs;
 use super::BlackScholesRiskFactors;
 
@@ -15,8 +15,6 @@ use crate::risk_factors::volatility::{ImpliedVolatility, Volatility};
 
 use chrono::{DateTime, Utc};
 
-use statrs::distribution::ContinuousCDF;
-
 fn insensitive_risk_factor_err(risk_factor: &Symbol, symbol: &Symbol) -> PricerError {
     PricerError::new(
         format!(
@@ -42,6 +40,9 @@ pub trait BlackScholes: FinancialOption {
         check_symbols(risk_factors.price_risk_factor(), self.symbol())?;
         check_symbols(risk_factors.volatility_risk_factor(), self.symbol())?;
         check_symbols(risk_factors.dividend_risk_factor(), self.symbol())?;
+        if let Some(borrow_risk_factor) = risk_factors.borrow_risk_factor() {
+            check_symbols(borrow_risk_factor, self.symbol())?;
+        }
         Ok(())
     }
     fn get_black_scholes_risk_factors(
@@ -65,6 +66,7 @@ pub trait BlackScholes: FinancialOption {
                 self.symbol().clone(),
                 dividend_rate,
             ))],
+            borrow_sensitivities: vec![],
         }
     }
     fn value_black_scholes_impl(&self, inputs: BlackScholesInputs) -> PricerResult<f64>;
@@ -85,7 +87,8 @@ pub trait BlackScholes: FinancialOption {
             inputs
         };
         risk_factors
-            .try_into()
+            .as_of(valuation_time)
+            .and_then(TryInto::try_into)
             .and_then(check_sensitivity_to_risk_factors)
             .map(gather_model_inputs)
             .map(shock_inputs)
@@ -95,24 +98,12 @@ pub trait BlackScholes: FinancialOption {
 
 impl BlackScholes for Call {
     fn value_black_scholes_impl(&self, inputs: BlackScholesInputs) -> PricerResult<f64> {
-        let (d1, d2) = get_d1_and_d2(self.strike(), &inputs);
-        gaussian()
-            .map(|gaussian| {
-                inputs.dividend_adjusted_price() * gaussian.cdf(d1)
-                    - self.strike() * inputs.risk_free_adjustment() * gaussian.cdf(d2)
-            })
-            .map(|valuation| valuation - self.cost())
+        black_scholes_value(true, self.strike(), &inputs).map(|valuation| valuation - self.cost())
     }
 }
 
 impl BlackScholes for Put {
     fn value_black_scholes_impl(&self, inputs: BlackScholesInputs) -> PricerResult<f64> {
-        let (d1, d2) = get_d1_and_d2(self.strike(), &inputs);
-        gaussian()
-            .map(|gaussian| {
-                self.strike() * inputs.risk_free_adjustment() * gaussian.cdf(-d2)
-                    - inputs.dividend_adjusted_price() * gaussian.cdf(-d1)
-            })
-            .map(|valuation| valuation - self.cost())
+        black_scholes_value(false, self.strike(), &inputs).map(|valuation| valuation - self.cost())
     }
 }
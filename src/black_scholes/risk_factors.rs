@@ -1,7 +1,8 @@
 use crate::result::{PricerError, PricerResult};
 
+use crate::risk_factors::borrow::BorrowRate;
 use crate::risk_factors::discount::{DiscountFactor, DiscountRf, InterestRate};
-use crate::risk_factors::dividend::{AnnualisedDividendRate, Dividend};
+use crate::risk_factors::dividend::{AnnualisedDividendRate, Dividend, OutstandingDividend};
 use crate::risk_factors::price::{Price, PriceRf, PriceTick};
 use crate::risk_factors::volatility::{ImpliedVolatility, Volatility, VolatilityRf};
 use crate::risk_factors::{IdentifiableRiskFactor, RiskFactors};
@@ -9,28 +10,44 @@ use crate::risk_factors::{IdentifiableRiskFactor, RiskFactors};
 use crate::shock::{ApplyShock, Shock};
 use crate::symbol::Symbol;
 
+use chrono::{DateTime, Utc};
+
 pub struct BlackScholesRiskFactors {
     price_risk_factor: Price,
     volatility_risk_factor: Volatility,
     discount_factor: DiscountFactor,
-    dividend_factor: AnnualisedDividendRate,
+    dividend_factor: Dividend,
+    borrow_factor: Option<BorrowRate>,
 }
 
 impl BlackScholesRiskFactors {
-    pub fn discount_rate(&self) -> f64 {
-        self.discount_factor.rate()
+    pub fn discount_rate(&self, delta_t: f64) -> f64 {
+        self.discount_factor.zero_rate(delta_t)
     }
     pub fn discount_factor(&self, delta_t: f64) -> f64 {
         self.discount_factor.discount_factor(delta_t)
     }
     pub fn annualised_dividend_rate(&self) -> f64 {
-        self.dividend_factor.rate()
+        self.dividend_factor.annualised_rate()
+    }
+    pub fn borrow_rate(&self) -> f64 {
+        self.borrow_factor
+            .as_ref()
+            .map(|borrow| borrow.rate())
+            .unwrap_or(0.0)
+    }
+    pub fn outstanding_dividends(
+        &self,
+        expiry: DateTime<Utc>,
+        delta_t: f64,
+    ) -> Vec<OutstandingDividend> {
+        self.dividend_factor.outstanding(expiry, delta_t)
     }
     pub fn price(&self) -> f64 {
         self.price_risk_factor.price()
     }
-    pub fn volatility(&self) -> f64 {
-        self.volatility_risk_factor.volatility()
+    pub fn volatility(&self, delta_t: f64) -> f64 {
+        self.volatility_risk_factor.volatility_to(delta_t)
     }
     pub fn volatility_for_delta_t(&self, delta_t: f64) -> f64 {
         self.volatility_risk_factor.scaled_to_time(delta_t)
@@ -45,6 +62,9 @@ impl BlackScholesRiskFactors {
     pub fn dividend_risk_factor(&self) -> &Symbol {
         self.dividend_factor.id()
     }
+    pub fn borrow_risk_factor(&self) -> Option<&Symbol> {
+        self.borrow_factor.as_ref().map(|borrow| borrow.id())
+    }
 }
 
 impl BlackScholesRiskFactors {
@@ -66,7 +86,11 @@ impl BlackScholesRiskFactors {
                 rfr_symbol,
                 risk_free_rate,
             )),
-            dividend_factor: AnnualisedDividendRate::new(symbol, dividend_rate),
+            dividend_factor: Dividend::AnnualisedRate(AnnualisedDividendRate::new(
+                symbol,
+                dividend_rate,
+            )),
+            borrow_factor: None,
         }
     }
 }
@@ -84,6 +108,13 @@ fn get_first_and_ensure_one<RF>(mut risk_factors: Vec<RF>) -> PricerResult<RF> {
     Ok(risk_factors.remove(0))
 }
 
+fn get_at_most_one<RF>(risk_factors: Vec<RF>) -> PricerResult<Option<RF>> {
+    if risk_factors.is_empty() {
+        return Ok(None);
+    }
+    get_first_and_ensure_one(risk_factors).map(Some)
+}
+
 impl TryFrom<RiskFactors> for BlackScholesRiskFactors {
     type Error = PricerError;
     fn try_from(risk_factors: RiskFactors) -> PricerResult<Self> {
@@ -91,16 +122,14 @@ impl TryFrom<RiskFactors> for BlackScholesRiskFactors {
         let volatility_risk_factor =
             get_first_and_ensure_one(risk_factors.volatility_sensitivities)?;
         let discount_factor = get_first_and_ensure_one(risk_factors.discount_factors)?;
-        let dividend_factor = get_first_and_ensure_one(risk_factors.dividend_sensitivities)
-            .and_then(|dividend| match dividend {
-            Dividend::AnnualisedRate(adr) => Ok(adr),
-            Dividend::Schedule => Err(PricerError::new("Provided a dividend schedule to Black-Scholes, the pricer does not support this, please provide an annualised rate".into(), 5)),
-        })?;
+        let dividend_factor = get_first_and_ensure_one(risk_factors.dividend_sensitivities)?;
+        let borrow_factor = get_at_most_one(risk_factors.borrow_sensitivities)?;
         Ok(BlackScholesRiskFactors {
             price_risk_factor,
             volatility_risk_factor,
             discount_factor,
             dividend_factor,
+            borrow_factor,
         })
     }
 }
@@ -109,8 +138,15 @@ impl ApplyShock<BlackScholesRiskFactors> for Shock {
     fn apply(&self, applicant: &mut BlackScholesRiskFactors) {
         match self {
             Shock::InterestRateShock(shock) => shock.apply(&mut applicant.discount_factor),
+            Shock::KeyRateShock(shock) => shock.apply(&mut applicant.discount_factor),
             Shock::PriceShock(shock) => shock.apply(&mut applicant.price_risk_factor),
             Shock::VolatilityShock(shock) => shock.apply(&mut applicant.volatility_risk_factor),
+            Shock::DividendShock(shock) => shock.apply(&mut applicant.dividend_factor),
+            Shock::BorrowShock(shock) => {
+                if let Some(borrow) = applicant.borrow_factor.as_mut() {
+                    shock.apply(borrow)
+                }
+            }
             _ => (),
         }
     }
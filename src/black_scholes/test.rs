@@ -1,12 +1,23 @@
 use super::BlackScholes;
 
 use super::BlackScholesGreeks;
+use super::{
+    BlackScholesFiniteDifference, BoundaryCondition, FiniteDifferenceParams, FiniteDifferenceScheme,
+};
 
 use crate::greeks::FiniteDifferenceGreeks;
+use crate::option::{ExerciseStyle, FinancialOption};
 use crate::result::PricerResult;
+use crate::risk_factors::borrow::BorrowRate;
+use crate::risk_factors::discount::rfr_discount;
+use crate::risk_factors::dividend::DividendPayment;
+use crate::shock::{
+    absolute_shock, borrow_shock, dividend_shock, relative_percentage_shock, ShockDirection,
+};
+use crate::utils::date::get_duration_in_years;
 use crate::Priceable;
 
-use crate::utils::test_utils::{get_test_call, get_test_put, is_close};
+use crate::utils::test_utils::{get_test_call, get_test_dividend_schedule, get_test_put, is_close};
 
 #[test]
 #[allow(unused_must_use)]
@@ -78,3 +89,279 @@ fn black_scholes_finite_difference_theta_near_analytical_theta() -> PricerResult
     assert!(is_close(theta_finite_difference, theta_analytic, 0.05), "Finite difference theta ({}) differs from analytical theta({}) for Black-Scholes by more than 5%", theta_finite_difference, theta_analytic);
     Ok(())
 }
+
+#[test]
+fn crank_nicolson_call_near_closed_form() -> PricerResult<()> {
+    let (call, valuation_time, risk_factors) = get_test_call();
+    let closed_form = call.value_black_scholes(valuation_time, risk_factors.clone(), vec![])?;
+    let grid = call.value_finite_difference(
+        valuation_time,
+        risk_factors,
+        vec![],
+        FiniteDifferenceParams::default(),
+    )?;
+    assert!(
+        is_close(grid.value, closed_form, 0.001),
+        "Crank-Nicolson valuation ({}) differs from Black-Scholes ({}) by more than 0.1%",
+        grid.value,
+        closed_form
+    );
+    Ok(())
+}
+
+#[test]
+fn implicit_and_explicit_put_near_closed_form() -> PricerResult<()> {
+    let (put, valuation_time, risk_factors) = get_test_put();
+    let closed_form = put.value_black_scholes(valuation_time, risk_factors.clone(), vec![])?;
+    let implicit = put.value_finite_difference(
+        valuation_time,
+        risk_factors.clone(),
+        vec![],
+        FiniteDifferenceParams {
+            scheme: FiniteDifferenceScheme::Implicit,
+            boundary_condition: BoundaryCondition::Dirichlet,
+            time_steps: 1000,
+            ..Default::default()
+        },
+    )?;
+    let explicit = put.value_finite_difference(
+        valuation_time,
+        risk_factors,
+        vec![],
+        FiniteDifferenceParams {
+            scheme: FiniteDifferenceScheme::Explicit,
+            spot_steps: 100,
+            time_steps: 500,
+            ..Default::default()
+        },
+    )?;
+    assert!(
+        is_close(implicit.value, closed_form, 0.005),
+        "Implicit valuation ({}) differs from Black-Scholes ({}) by more than 0.5%",
+        implicit.value,
+        closed_form
+    );
+    assert!(
+        is_close(explicit.value, closed_form, 0.005),
+        "Explicit valuation ({}) differs from Black-Scholes ({}) by more than 0.5%",
+        explicit.value,
+        closed_form
+    );
+    Ok(())
+}
+
+#[test]
+fn unstable_explicit_scheme_is_rejected() {
+    let (put, valuation_time, risk_factors) = get_test_put();
+    let valuation = put.value_finite_difference(
+        valuation_time,
+        risk_factors,
+        vec![],
+        FiniteDifferenceParams {
+            scheme: FiniteDifferenceScheme::Explicit,
+            ..Default::default()
+        },
+    );
+    assert!(valuation.is_err());
+}
+
+#[test]
+fn crank_nicolson_grid_greeks_near_analytical_greeks() -> PricerResult<()> {
+    let (call, valuation_time, risk_factors) = get_test_call();
+    let grid = call.value_finite_difference(
+        valuation_time,
+        risk_factors.clone(),
+        vec![],
+        FiniteDifferenceParams::default(),
+    )?;
+    let delta = call.delta(valuation_time, risk_factors.clone())?;
+    let gamma = call.gamma(valuation_time, risk_factors.clone())?;
+    let theta = call.theta(valuation_time, risk_factors)?;
+    assert!(
+        is_close(grid.delta, delta, 0.01),
+        "Grid delta ({}) differs from analytical delta ({}) by more than 1%",
+        grid.delta,
+        delta
+    );
+    assert!(
+        is_close(grid.gamma, gamma, 0.01),
+        "Grid gamma ({}) differs from analytical gamma ({}) by more than 1%",
+        grid.gamma,
+        gamma
+    );
+    assert!(
+        is_close(grid.theta, theta, 0.01),
+        "Grid theta ({}) differs from analytical theta ({}) by more than 1%",
+        grid.theta,
+        theta
+    );
+    Ok(())
+}
+
+#[test]
+fn american_put_worth_at_least_european_put() -> PricerResult<()> {
+    let (put, valuation_time, risk_factors) = get_test_put();
+    let european = put.value_finite_difference(
+        valuation_time,
+        risk_factors.clone(),
+        vec![],
+        FiniteDifferenceParams::default(),
+    )?;
+    let american = put.value_finite_difference(
+        valuation_time,
+        risk_factors,
+        vec![],
+        FiniteDifferenceParams {
+            exercise_style: ExerciseStyle::American,
+            ..Default::default()
+        },
+    )?;
+    assert!(
+        american.value > european.value,
+        "American put ({}) should be worth more than European put ({})",
+        american.value,
+        european.value
+    );
+    Ok(())
+}
+
+#[test]
+fn american_call_without_dividends_matches_european_call() -> PricerResult<()> {
+    let (call, valuation_time, risk_factors) = get_test_call();
+    let european = call.value_finite_difference(
+        valuation_time,
+        risk_factors.clone(),
+        vec![],
+        FiniteDifferenceParams::default(),
+    )?;
+    let american = call.value_finite_difference(
+        valuation_time,
+        risk_factors,
+        vec![],
+        FiniteDifferenceParams {
+            exercise_style: ExerciseStyle::American,
+            ..Default::default()
+        },
+    )?;
+    assert!(is_close(american.value, european.value, 0.0001));
+    Ok(())
+}
+
+#[test]
+fn cash_dividend_values_as_escrowed_price() -> PricerResult<()> {
+    let (call, valuation_time, mut risk_factors) = get_test_call();
+    risk_factors.dividend_sensitivities =
+        vec![get_test_dividend_schedule(DividendPayment::Cash(1.0))];
+    let with_dividend = call.value_black_scholes(valuation_time, risk_factors, vec![])?;
+
+    let delta_t = get_duration_in_years(valuation_time, call.expiry());
+    let escrowed_price = 42.0 - (-0.05 * delta_t / 2.0).exp();
+    let escrowed_risk_factors = call.get_black_scholes_risk_factors(
+        escrowed_price,
+        0.2,
+        0.0,
+        rfr_discount("US Treasury 3M".into(), 0.05),
+    );
+    let expected = call.value_black_scholes(valuation_time, escrowed_risk_factors, vec![])?;
+    assert!(
+        is_close(with_dividend, expected, 0.000001),
+        "Call with a cash dividend ({}) differs from the call on the escrowed price ({})",
+        with_dividend,
+        expected
+    );
+    Ok(())
+}
+
+#[test]
+fn dividends_lower_call_and_raise_put() -> PricerResult<()> {
+    let (call, valuation_time, risk_factors) = get_test_call();
+    let (put, _, _) = get_test_put();
+    let mut dividend_risk_factors = risk_factors.clone();
+    dividend_risk_factors.dividend_sensitivities = vec![get_test_dividend_schedule(
+        DividendPayment::Proportional(0.02),
+    )];
+    assert!(
+        call.value_black_scholes(valuation_time, dividend_risk_factors.clone(), vec![])?
+            < call.value_black_scholes(valuation_time, risk_factors.clone(), vec![])?
+    );
+    assert!(
+        put.value_black_scholes(valuation_time, dividend_risk_factors, vec![])?
+            > put.value_black_scholes(valuation_time, risk_factors, vec![])?
+    );
+    Ok(())
+}
+
+#[test]
+fn dividend_shock_lowers_call_value() -> PricerResult<()> {
+    let (call, valuation_time, mut risk_factors) = get_test_call();
+    risk_factors.dividend_sensitivities =
+        vec![get_test_dividend_schedule(DividendPayment::Cash(1.0))];
+    let shock = dividend_shock(
+        "AAPL".into(),
+        relative_percentage_shock(50.0, ShockDirection::Up),
+    );
+    let unshocked = call.value_black_scholes(valuation_time, risk_factors.clone(), vec![])?;
+    let shocked = call.value_black_scholes(valuation_time, risk_factors, vec![shock])?;
+    assert!(
+        shocked < unshocked,
+        "Raising the dividend should lower the call value, got {} from {}",
+        shocked,
+        unshocked
+    );
+    Ok(())
+}
+
+#[test]
+fn finite_difference_with_cash_dividend_near_closed_form() -> PricerResult<()> {
+    let (call, valuation_time, mut risk_factors) = get_test_call();
+    risk_factors.dividend_sensitivities =
+        vec![get_test_dividend_schedule(DividendPayment::Cash(1.0))];
+    let closed_form = call.value_black_scholes(valuation_time, risk_factors.clone(), vec![])?;
+    let grid = call.value_finite_difference(
+        valuation_time,
+        risk_factors,
+        vec![],
+        FiniteDifferenceParams::default(),
+    )?;
+    assert!(
+        is_close(grid.value, closed_form, 0.001),
+        "Finite difference ({}) differs from closed form ({}) by more than 0.1%",
+        grid.value,
+        closed_form
+    );
+    Ok(())
+}
+
+#[test]
+fn borrow_fee_preserves_put_call_parity() -> PricerResult<()> {
+    let (call, valuation_time, mut risk_factors) = get_test_call();
+    let (put, _, _) = get_test_put();
+    risk_factors.borrow_sensitivities = vec![BorrowRate::new("AAPL".into(), 0.08)];
+    let call_value = call.value_black_scholes(valuation_time, risk_factors.clone(), vec![])?;
+    let put_value = put.value_black_scholes(valuation_time, risk_factors, vec![])?;
+    let delta_t = get_duration_in_years(valuation_time, call.expiry());
+    let forward_difference = 42.0 * (-0.08 * delta_t).exp() - 40.0 * (-0.05 * delta_t).exp();
+    assert!(
+        (call_value - put_value - forward_difference).abs() < 1e-9,
+        "Call less put ({}) should equal the discounted forward less strike ({})",
+        call_value - put_value,
+        forward_difference
+    );
+    Ok(())
+}
+
+#[test]
+fn borrow_shock_lowers_call_value() -> PricerResult<()> {
+    let (call, valuation_time, mut risk_factors) = get_test_call();
+    risk_factors.borrow_sensitivities = vec![BorrowRate::new("AAPL".into(), 0.02)];
+    let shock = borrow_shock("AAPL".into(), absolute_shock(0.05, ShockDirection::Up));
+    let unshocked = call.value_black_scholes(valuation_time, risk_factors.clone(), vec![])?;
+    let shocked = call.value_black_scholes(valuation_time, risk_factors, vec![shock])?;
+    assert!(
+        shocked < unshocked,
+        "Raising the borrow fee should lower the call value, got {} from {}",
+        shocked,
+        unshocked
+    );
+    Ok(())
+}
@@ -0,0 +1,52 @@
+use crate::black_scholes::black_scholes_price;
+use crate::result::{PricerError, PricerResult};
+
+const MIN_VOLATILITY: f64 = 1e-6;
+const MAX_VOLATILITY: f64 = 5.0;
+const MAX_BISECTIONS: usize = 200;
+
+fn no_implied_volatility_err(price: f64) -> PricerError {
+    PricerError::new(
+        format!("No Black-Scholes volatility reproduces the price {}", price),
+        13,
+    )
+}
+
+// Bisection on volatility, the price is monotone in it so this always lands when a root exists
+pub fn implied_volatility(
+    is_call: bool,
+    price: f64,
+    spot: f64,
+    strike: f64,
+    delta_t: f64,
+    rate: f64,
+    dividend_yield: f64,
+) -> PricerResult<f64> {
+    let price_at = |volatility| {
+        black_scholes_price(
+            is_call,
+            spot,
+            strike,
+            delta_t,
+            rate,
+            dividend_yield,
+            volatility,
+        )
+    };
+    let (mut lower, mut upper) = (MIN_VOLATILITY, MAX_VOLATILITY);
+    if !(price_at(lower)?..=price_at(upper)?).contains(&price) {
+        return Err(no_implied_volatility_err(price));
+    }
+    for _ in 0..MAX_BISECTIONS {
+        let middle = 0.5 * (lower + upper);
+        if price_at(middle)? < price {
+            lower = middle;
+        } else {
+            upper = middle;
+        }
+        if upper - lower < 1e-12 {
+            break;
+        }
+    }
+    Ok(0.5 * (lower + upper))
+}
@@ -0,0 +1,314 @@
+mod black;
+pub mod models;
+#[cfg(test)]
+mod test;
+
+pub use black::implied_volatility;
+
+use crate::option::{Call, FinancialOption, Put};
+use crate::result::{PricerError, PricerResult};
+use crate::risk_factors::discount::rfr_discount;
+use crate::risk_factors::dividend::{AnnualisedDividendRate, Dividend};
+use crate::risk_factors::price::{Price, PriceTick};
+use crate::risk_factors::volatility::{ImpliedVolatility, Volatility};
+use crate::risk_factors::RiskFactors;
+use crate::symbol::Symbol;
+use crate::utils::date::get_duration_in_years;
+use crate::utils::linear_algebra::{gram, invert};
+use crate::utils::optimise::{jacobian, levenberg_marquardt, nelder_mead, Minimum};
+
+use chrono::{DateTime, Utc};
+
+fn invalid_calibration_err(reason: &str) -> PricerError {
+    PricerError::new(format!("Unable to calibrate, {}", reason), 13)
+}
+
+// Market state shared by every quote, the model parameters are fitted on top of it
+#[derive(Clone)]
+pub struct CalibrationMarket {
+    pub valuation_time: DateTime<Utc>,
+    pub spot: f64,
+    pub risk_free_rate: f64,
+    pub rate_symbol: Symbol,
+    pub dividend_yield: f64,
+}
+
+pub enum QuotedOption {
+    Call(Call),
+    Put(Put),
+}
+
+impl QuotedOption {
+    pub fn option(&self) -> &dyn FinancialOption {
+        match self {
+            QuotedOption::Call(call) => call,
+            QuotedOption::Put(put) => put,
+        }
+    }
+    pub fn is_call(&self) -> bool {
+        matches!(self, QuotedOption::Call(_))
+    }
+    pub fn strike(&self) -> f64 {
+        self.option().strike()
+    }
+    pub fn delta_t(&self, market: &CalibrationMarket) -> f64 {
+        get_duration_in_years(market.valuation_time, self.option().expiry())
+    }
+}
+
+#[derive(Clone, Debug)]
+pub enum QuotedValue {
+    Price(f64),
+    ImpliedVolatility(f64),
+}
+
+pub struct CalibrationQuote {
+    pub option: QuotedOption,
+    pub value: QuotedValue,
+    pub weight: f64,
+}
+
+// A model the calibration can fit. Parameters are a flat vector in the order of
+// `parameter_names`, so the optimisers never need to know what the model is
+pub trait CalibrationModel {
+    fn parameter_names(&self) -> Vec<&'static str>;
+    // Inclusive lower and upper bound of each parameter
+    fn bounds(&self) -> Vec<(f64, f64)>;
+    fn initial_guess(&self, market: &CalibrationMarket, quotes: &[CalibrationQuote]) -> Vec<f64>;
+    // Present value of the option under the model
+    fn price(
+        &self,
+        parameters: &[f64],
+        market: &CalibrationMarket,
+        option: &QuotedOption,
+    ) -> PricerResult<f64>;
+}
+
+#[derive(Clone, Debug)]
+pub enum CalibrationMethod {
+    LevenbergMarquardt,
+    NelderMead,
+}
+
+#[derive(Clone, Debug)]
+pub struct CalibratedParameter {
+    pub name: &'static str,
+    pub value: f64,
+    // Asymptotic standard error from the Jacobian at the fit, a large value flags a parameter the
+    // quotes barely pin down. NaN when there are no more quotes than parameters
+    pub standard_error: f64,
+}
+
+pub struct Calibration<M: CalibrationModel> {
+    model: M,
+    market: CalibrationMarket,
+    pub parameters: Vec<CalibratedParameter>,
+    // Model minus quoted value per quote, in the units the quote was given in
+    pub residuals: Vec<f64>,
+    pub root_mean_squared_error: f64,
+    pub iterations: usize,
+    pub converged: bool,
+}
+
+const MAX_ITERATIONS: usize = 5000;
+const TOLERANCE: f64 = 1e-14;
+
+fn implied_volatility_of(
+    market: &CalibrationMarket,
+    option: &QuotedOption,
+    price: f64,
+) -> PricerResult<f64> {
+    implied_volatility(
+        option.is_call(),
+        price,
+        market.spot,
+        option.strike(),
+        option.delta_t(market),
+        market.risk_free_rate,
+        market.dividend_yield,
+    )
+}
+
+fn residual<M: CalibrationModel>(
+    model: &M,
+    parameters: &[f64],
+    market: &CalibrationMarket,
+    quote: &CalibrationQuote,
+) -> PricerResult<f64> {
+    let price = model.price(parameters, market, &quote.option)?;
+    match quote.value {
+        QuotedValue::Price(quoted) => Ok(price - quoted),
+        QuotedValue::ImpliedVolatility(quoted) => {
+            implied_volatility_of(market, &quote.option, price).map(|implied| implied - quoted)
+        }
+    }
+}
+
+fn weighted_residuals<M: CalibrationModel>(
+    model: &M,
+    parameters: &[f64],
+    market: &CalibrationMarket,
+    quotes: &[CalibrationQuote],
+) -> Option<Vec<f64>> {
+    quotes
+        .iter()
+        .map(|quote| {
+            residual(model, parameters, market, quote)
+                .ok()
+                .map(|residual| quote.weight.sqrt() * residual)
+        })
+        .collect()
+}
+
+// Standard errors from the inverse Gauss-Newton curvature scaled by the residual variance
+fn standard_errors<F: Fn(&[f64]) -> Option<Vec<f64>>>(
+    residuals: &F,
+    point: &[f64],
+    bounds: &[(f64, f64)],
+    sum_of_squares: f64,
+    quotes: usize,
+) -> Vec<f64> {
+    let unavailable = vec![f64::NAN; point.len()];
+    if quotes <= point.len() {
+        return unavailable;
+    }
+    let residual_variance = sum_of_squares / (quotes - point.len()) as f64;
+    jacobian(residuals, point, bounds)
+        .and_then(|jacobian| invert(&gram(&jacobian)))
+        .map(|covariance| {
+            (0..point.len())
+                .map(|i| (residual_variance * covariance[i][i]).max(0.0).sqrt())
+                .collect()
+        })
+        .unwrap_or(unavailable)
+}
+
+pub fn calibrate<M: CalibrationModel>(
+    model: M,
+    market: CalibrationMarket,
+    quotes: &[CalibrationQuote],
+    method: &CalibrationMethod,
+) -> PricerResult<Calibration<M>> {
+    if quotes.is_empty() {
+        return Err(invalid_calibration_err("no quotes were given"));
+    }
+    if quotes.iter().any(|quote| quote.weight < 0.0) || quotes.iter().all(|q| q.weight == 0.0) {
+        return Err(invalid_calibration_err(
+            "weights must be non-negative and not all zero",
+        ));
+    }
+    let bounds = model.bounds();
+    let start = model.initial_guess(&market, quotes);
+    let objective = |parameters: &[f64]| weighted_residuals(&model, parameters, &market, quotes);
+
+    let Minimum {
+        point,
+        value,
+        iterations,
+        converged,
+    } = match method {
+        CalibrationMethod::LevenbergMarquardt => {
+            levenberg_marquardt(objective, &start, &bounds, TOLERANCE, MAX_ITERATIONS)
+        }
+        CalibrationMethod::NelderMead => {
+            let within_bounds = |parameters: &[f64]| {
+                parameters
+                    .iter()
+                    .zip(&bounds)
+                    .all(|(value, (lower, upper))| (*lower..=*upper).contains(value))
+            };
+            let sum_of_squares = |parameters: &[f64]| {
+                if !within_bounds(parameters) {
+                    return f64::INFINITY;
+                }
+                objective(parameters).map_or(f64::INFINITY, |residuals| {
+                    residuals.iter().map(|residual| residual.powi(2)).sum()
+                })
+            };
+            let steps: Vec<f64> = start
+                .iter()
+                .zip(&bounds)
+                .map(|(value, (lower, upper))| {
+                    let step = 0.1 * value.abs().max(0.1 * (upper - lower).min(1.0));
+                    if value + step > *upper {
+                        -step
+                    } else {
+                        step
+                    }
+                })
+                .collect();
+            nelder_mead(sum_of_squares, &start, &steps, TOLERANCE, MAX_ITERATIONS)
+        }
+    };
+    if !value.is_finite() {
+        return Err(invalid_calibration_err(
+            "the model could not price the quotes at any trial parameters",
+        ));
+    }
+
+    let residuals = quotes
+        .iter()
+        .map(|quote| residual(&model, &point, &market, quote))
+        .collect::<PricerResult<Vec<f64>>>()?;
+    let total_weight: f64 = quotes.iter().map(|quote| quote.weight).sum();
+    let errors = standard_errors(&objective, &point, &bounds, value, quotes.len());
+    let parameters = model
+        .parameter_names()
+        .into_iter()
+        .zip(point.iter().zip(errors))
+        .map(|(name, (value, standard_error))| CalibratedParameter {
+            name,
+            value: *value,
+            standard_error,
+        })
+        .collect();
+    Ok(Calibration {
+        model,
+        market,
+        parameters,
+        residuals,
+        root_mean_squared_error: (value / total_weight).sqrt(),
+        iterations,
+        converged,
+    })
+}
+
+impl<M: CalibrationModel> Calibration<M> {
+    pub fn parameter_values(&self) -> Vec<f64> {
+        self.parameters
+            .iter()
+            .map(|parameter| parameter.value)
+            .collect()
+    }
+    pub fn price(&self, option: &QuotedOption) -> PricerResult<f64> {
+        self.model
+            .price(&self.parameter_values(), &self.market, option)
+    }
+    pub fn implied_volatility(&self, option: &QuotedOption) -> PricerResult<f64> {
+        self.price(option)
+            .and_then(|price| implied_volatility_of(&self.market, option, price))
+    }
+    // Risk factors that reproduce the calibrated model price of `option` in any engine, the
+    // model is carried through its Black-Scholes implied volatility at that strike and expiry
+    pub fn risk_factors(&self, option: &QuotedOption) -> PricerResult<RiskFactors> {
+        let symbol = option.option().symbol().clone();
+        self.implied_volatility(option)
+            .map(|volatility| RiskFactors {
+                price_sensitivities: vec![Price::PriceTick(PriceTick::new(
+                    symbol.clone(),
+                    self.market.spot,
+                ))],
+                volatility_sensitivities: vec![Volatility::ImpliedVolatility(
+                    ImpliedVolatility::new(symbol.clone(), volatility),
+                )],
+                discount_factors: vec![rfr_discount(
+                    self.market.rate_symbol.clone(),
+                    self.market.risk_free_rate,
+                )],
+                dividend_sensitivities: vec![Dividend::AnnualisedRate(
+                    AnnualisedDividendRate::new(symbol, self.market.dividend_yield),
+                )],
+                borrow_sensitivities: vec![],
+            })
+    }
+}
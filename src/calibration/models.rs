@@ -0,0 +1,191 @@
+use super::{CalibrationMarket, CalibrationModel, CalibrationQuote};
+use super::{QuotedOption, QuotedValue};
+
+use crate::black_scholes::black_scholes_price;
+use crate::result::PricerResult;
+
+// Sources of equations:
+//  - Hagan, Kumar, Lesniewski & Woodward (2002), Managing smile risk
+//  - Merton (1976), Option pricing when underlying stock returns are discontinuous
+
+fn black_scholes_with_volatility(
+    market: &CalibrationMarket,
+    option: &QuotedOption,
+    volatility: f64,
+) -> PricerResult<f64> {
+    black_scholes_price(
+        option.is_call(),
+        market.spot,
+        option.strike(),
+        option.delta_t(market),
+        market.risk_free_rate,
+        market.dividend_yield,
+        volatility,
+    )
+}
+
+fn mean_quoted_volatility(quotes: &[CalibrationQuote]) -> Option<f64> {
+    let volatilities: Vec<f64> = quotes
+        .iter()
+        .filter_map(|quote| match quote.value {
+            QuotedValue::ImpliedVolatility(volatility) => Some(volatility),
+            QuotedValue::Price(_) => None,
+        })
+        .collect();
+    (!volatilities.is_empty()).then(|| volatilities.iter().sum::<f64>() / volatilities.len() as f64)
+}
+
+// A single Black-Scholes volatility across all quotes
+pub struct FlatVolatility;
+
+impl CalibrationModel for FlatVolatility {
+    fn parameter_names(&self) -> Vec<&'static str> {
+        vec!["volatility"]
+    }
+    fn bounds(&self) -> Vec<(f64, f64)> {
+        vec![(1e-4, 5.0)]
+    }
+    fn initial_guess(&self, _: &CalibrationMarket, quotes: &[CalibrationQuote]) -> Vec<f64> {
+        vec![mean_quoted_volatility(quotes).unwrap_or(0.2)]
+    }
+    fn price(
+        &self,
+        parameters: &[f64],
+        market: &CalibrationMarket,
+        option: &QuotedOption,
+    ) -> PricerResult<f64> {
+        black_scholes_with_volatility(market, option, parameters[0])
+    }
+}
+
+// Stochastic alpha, beta, rho with `beta` held fixed, as is the market convention. Parameters
+// are the initial volatility alpha, the spot-volatility correlation rho and the volatility of
+// volatility nu
+pub struct Sabr {
+    pub beta: f64,
+}
+
+impl Sabr {
+    // Hagan's lognormal volatility approximation
+    pub fn implied_volatility(
+        &self,
+        parameters: &[f64],
+        forward: f64,
+        strike: f64,
+        delta_t: f64,
+    ) -> f64 {
+        let (alpha, rho, nu) = (parameters[0], parameters[1], parameters[2]);
+        let one_minus_beta = 1.0 - self.beta;
+        let log_moneyness = (forward / strike).ln();
+        let geometric_mean = (forward * strike).powf(0.5 * one_minus_beta);
+        let time_correction = 1.0
+            + (one_minus_beta.powi(2) / 24.0 * alpha.powi(2) / geometric_mean.powi(2)
+                + 0.25 * rho * self.beta * nu * alpha / geometric_mean
+                + (2.0 - 3.0 * rho.powi(2)) / 24.0 * nu.powi(2))
+                * delta_t;
+        let moneyness_correction = 1.0
+            + one_minus_beta.powi(2) / 24.0 * log_moneyness.powi(2)
+            + one_minus_beta.powi(4) / 1920.0 * log_moneyness.powi(4);
+        let z = nu / alpha * geometric_mean * log_moneyness;
+        let z_over_x = if z.abs() < 1e-8 {
+            1.0
+        } else {
+            let x = ((1.0 - 2.0 * rho * z + z.powi(2)).sqrt() + z - rho).ln() - (1.0 - rho).ln();
+            z / x
+        };
+        alpha / (geometric_mean * moneyness_correction) * z_over_x * time_correction
+    }
+}
+
+impl CalibrationModel for Sabr {
+    fn parameter_names(&self) -> Vec<&'static str> {
+        vec!["alpha", "rho", "nu"]
+    }
+    fn bounds(&self) -> Vec<(f64, f64)> {
+        vec![(1e-4, 10.0), (-0.999, 0.999), (1e-4, 5.0)]
+    }
+    fn initial_guess(&self, market: &CalibrationMarket, quotes: &[CalibrationQuote]) -> Vec<f64> {
+        let volatility = mean_quoted_volatility(quotes).unwrap_or(0.2);
+        vec![volatility * market.spot.powf(1.0 - self.beta), 0.0, 0.5]
+    }
+    fn price(
+        &self,
+        parameters: &[f64],
+        market: &CalibrationMarket,
+        option: &QuotedOption,
+    ) -> PricerResult<f64> {
+        let delta_t = option.delta_t(market);
+        let forward =
+            market.spot * ((market.risk_free_rate - market.dividend_yield) * delta_t).exp();
+        let volatility = self.implied_volatility(parameters, forward, option.strike(), delta_t);
+        black_scholes_with_volatility(market, option, volatility)
+    }
+}
+
+// Lognormal diffusion with Poisson arrivals of lognormal jumps. Parameters are the diffusion
+// volatility, the jump intensity per year and the mean and volatility of the log jump size
+pub struct MertonJumpDiffusion;
+
+const MAX_JUMPS: usize = 100;
+
+impl CalibrationModel for MertonJumpDiffusion {
+    fn parameter_names(&self) -> Vec<&'static str> {
+        vec![
+            "volatility",
+            "jump_intensity",
+            "jump_mean",
+            "jump_volatility",
+        ]
+    }
+    fn bounds(&self) -> Vec<(f64, f64)> {
+        vec![(1e-4, 3.0), (0.0, 10.0), (-1.0, 1.0), (1e-4, 2.0)]
+    }
+    fn initial_guess(&self, _: &CalibrationMarket, quotes: &[CalibrationQuote]) -> Vec<f64> {
+        vec![
+            mean_quoted_volatility(quotes).unwrap_or(0.2),
+            0.5,
+            -0.1,
+            0.15,
+        ]
+    }
+    // Sum of Black-Scholes prices conditional on the number of jumps, weighted by a Poisson
+    // distribution at the jump adjusted intensity which also absorbs the conditional discounting
+    fn price(
+        &self,
+        parameters: &[f64],
+        market: &CalibrationMarket,
+        option: &QuotedOption,
+    ) -> PricerResult<f64> {
+        let (volatility, intensity, jump_mean, jump_volatility) =
+            (parameters[0], parameters[1], parameters[2], parameters[3]);
+        let delta_t = option.delta_t(market);
+        let mean_jump = (jump_mean + 0.5 * jump_volatility.powi(2)).exp() - 1.0;
+        let adjusted_intensity = intensity * (1.0 + mean_jump) * delta_t;
+        let mut weight = (-adjusted_intensity).exp();
+        let mut price = 0.0;
+        for jumps in 0..MAX_JUMPS {
+            let n = jumps as f64;
+            if jumps > 0 {
+                weight *= adjusted_intensity / n;
+            }
+            let conditional_volatility =
+                (volatility.powi(2) + n * jump_volatility.powi(2) / delta_t).sqrt();
+            let conditional_rate = market.risk_free_rate - intensity * mean_jump
+                + n * (1.0 + mean_jump).ln() / delta_t;
+            let conditional_price = black_scholes_price(
+                option.is_call(),
+                market.spot,
+                option.strike(),
+                delta_t,
+                conditional_rate,
+                market.dividend_yield,
+                conditional_volatility,
+            )?;
+            price += weight * conditional_price;
+            if n > adjusted_intensity && weight < 1e-16 {
+                break;
+            }
+        }
+        Ok(price)
+    }
+}
@@ -0,0 +1,266 @@
+use super::models::{FlatVolatility, MertonJumpDiffusion, Sabr};
+use super::{calibrate, implied_volatility};
+use super::{CalibrationMarket, CalibrationMethod, CalibrationModel, CalibrationQuote};
+use super::{QuotedOption, QuotedValue};
+
+use crate::black_scholes::{black_scholes_price, BlackScholes};
+use crate::option::{get_call, get_put};
+use crate::result::PricerResult;
+use crate::utils::test_utils::is_close;
+
+use chrono::{DateTime, Duration, TimeZone, Utc};
+
+fn valuation_time() -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(2024, 3, 1, 16, 0, 0).unwrap()
+}
+
+fn market() -> CalibrationMarket {
+    CalibrationMarket {
+        valuation_time: valuation_time(),
+        spot: 100.0,
+        risk_free_rate: 0.04,
+        rate_symbol: "US Treasury 3M".into(),
+        dividend_yield: 0.01,
+    }
+}
+
+fn call(strike: f64, days: i64) -> QuotedOption {
+    QuotedOption::Call(get_call(
+        "AAPL".into(),
+        strike,
+        valuation_time() + Duration::days(days),
+        0.0,
+    ))
+}
+
+fn put(strike: f64, days: i64) -> QuotedOption {
+    QuotedOption::Put(get_put(
+        "AAPL".into(),
+        strike,
+        valuation_time() + Duration::days(days),
+        0.0,
+    ))
+}
+
+// Quotes read off a model at known parameters
+fn model_quotes<M: CalibrationModel>(
+    model: &M,
+    parameters: &[f64],
+    options: Vec<QuotedOption>,
+    as_volatility: bool,
+) -> Vec<CalibrationQuote> {
+    options
+        .into_iter()
+        .map(|option| {
+            let price = model.price(parameters, &market(), &option).unwrap();
+            let value = if as_volatility {
+                QuotedValue::ImpliedVolatility(
+                    implied_volatility(
+                        option.is_call(),
+                        price,
+                        100.0,
+                        option.strike(),
+                        option.delta_t(&market()),
+                        0.04,
+                        0.01,
+                    )
+                    .unwrap(),
+                )
+            } else {
+                QuotedValue::Price(price)
+            };
+            CalibrationQuote {
+                option,
+                value,
+                weight: 1.0,
+            }
+        })
+        .collect()
+}
+
+fn strip(strikes: impl Iterator<Item = f64>, days: i64) -> Vec<QuotedOption> {
+    strikes.map(|strike| call(strike, days)).collect()
+}
+
+#[test]
+fn implied_volatility_inverts_black_scholes() -> PricerResult<()> {
+    for (is_call, strike) in [(true, 90.0), (true, 120.0), (false, 80.0), (false, 105.0)] {
+        let price = black_scholes_price(is_call, 100.0, strike, 0.75, 0.04, 0.01, 0.32)?;
+        let implied = implied_volatility(is_call, price, 100.0, strike, 0.75, 0.04, 0.01)?;
+        assert!((implied - 0.32).abs() < 1e-9, "implied {}", implied);
+    }
+    assert!(implied_volatility(true, 150.0, 100.0, 100.0, 1.0, 0.04, 0.01).is_err());
+    Ok(())
+}
+
+#[test]
+fn flat_volatility_recovers_black_scholes_prices() -> PricerResult<()> {
+    for method in [
+        CalibrationMethod::LevenbergMarquardt,
+        CalibrationMethod::NelderMead,
+    ] {
+        let quotes = model_quotes(
+            &FlatVolatility,
+            &[0.25],
+            strip((80..=120).step_by(10).map(f64::from), 365),
+            false,
+        );
+        let calibration = calibrate(FlatVolatility, market(), &quotes, &method)?;
+        assert!(calibration.converged, "{:?} did not converge", method);
+        assert!(
+            (calibration.parameters[0].value - 0.25).abs() < 1e-6,
+            "{:?} calibrated volatility {}",
+            method,
+            calibration.parameters[0].value
+        );
+        assert!(calibration.residuals.iter().all(|r| r.abs() < 1e-6));
+    }
+    Ok(())
+}
+
+#[test]
+fn sabr_recovers_smile_parameters() -> PricerResult<()> {
+    let sabr = Sabr { beta: 0.5 };
+    let true_parameters = [2.5, -0.3, 0.6];
+    let quotes = model_quotes(
+        &sabr,
+        &true_parameters,
+        strip((70..=130).step_by(10).map(f64::from), 365),
+        true,
+    );
+    let calibration = calibrate(
+        sabr,
+        market(),
+        &quotes,
+        &CalibrationMethod::LevenbergMarquardt,
+    )?;
+    assert!(calibration.converged);
+    for (fitted, expected) in calibration.parameter_values().iter().zip(true_parameters) {
+        assert!(
+            (fitted - expected).abs() < 1e-4,
+            "fitted {} expected {}",
+            fitted,
+            expected
+        );
+    }
+    assert!(calibration.root_mean_squared_error < 1e-8);
+    Ok(())
+}
+
+#[test]
+fn merton_fits_prices_across_expiries() -> PricerResult<()> {
+    let true_parameters = [0.15, 0.8, -0.15, 0.2];
+    let options = strip((80..=120).step_by(5).map(f64::from), 182)
+        .into_iter()
+        .chain(strip((80..=120).step_by(5).map(f64::from), 365))
+        .collect();
+    let quotes = model_quotes(&MertonJumpDiffusion, &true_parameters, options, false);
+    let calibration = calibrate(
+        MertonJumpDiffusion,
+        market(),
+        &quotes,
+        &CalibrationMethod::LevenbergMarquardt,
+    )?;
+    assert!(
+        calibration.root_mean_squared_error < 1e-6,
+        "rmse {}",
+        calibration.root_mean_squared_error
+    );
+    for (fitted, expected) in calibration.parameter_values().iter().zip(true_parameters) {
+        assert!(
+            (fitted - expected).abs() < 1e-2,
+            "fitted {} expected {}",
+            fitted,
+            expected
+        );
+    }
+    Ok(())
+}
+
+#[test]
+fn merton_satisfies_put_call_parity() -> PricerResult<()> {
+    let parameters = [0.2, 1.5, -0.1, 0.25];
+    for strike in [80.0, 100.0, 125.0] {
+        let call_price = MertonJumpDiffusion.price(&parameters, &market(), &call(strike, 270))?;
+        let put_price = MertonJumpDiffusion.price(&parameters, &market(), &put(strike, 270))?;
+        let delta_t = call(strike, 270).delta_t(&market());
+        let forward_value = 100.0 * (-0.01 * delta_t).exp() - strike * (-0.04 * delta_t).exp();
+        assert!((call_price - put_price - forward_value).abs() < 1e-9);
+    }
+    Ok(())
+}
+
+#[test]
+fn standard_errors_reflect_quote_noise() -> PricerResult<()> {
+    // Volatility quotes scattered one vol point either side of 20%
+    let quotes: Vec<CalibrationQuote> = (0..10)
+        .map(|i| CalibrationQuote {
+            option: call(100.0, 365),
+            value: QuotedValue::ImpliedVolatility(if i % 2 == 0 { 0.19 } else { 0.21 }),
+            weight: 1.0,
+        })
+        .collect();
+    let calibration = calibrate(
+        FlatVolatility,
+        market(),
+        &quotes,
+        &CalibrationMethod::LevenbergMarquardt,
+    )?;
+    let volatility = &calibration.parameters[0];
+    assert!((volatility.value - 0.2).abs() < 1e-6);
+    // Residual variance 0.01^2 * 10 / 9 over 10 quotes
+    let expected = (0.0001 / 9.0f64).sqrt();
+    assert!(
+        is_close(volatility.standard_error, expected, 1e-3),
+        "standard error {}",
+        volatility.standard_error
+    );
+    Ok(())
+}
+
+#[test]
+fn calibrated_risk_factors_reprice_the_model() -> PricerResult<()> {
+    let sabr = Sabr { beta: 0.5 };
+    let quotes = model_quotes(
+        &sabr,
+        &[2.5, -0.3, 0.6],
+        strip((80..=120).step_by(10).map(f64::from), 365),
+        true,
+    );
+    let calibration = calibrate(
+        sabr,
+        market(),
+        &quotes,
+        &CalibrationMethod::LevenbergMarquardt,
+    )?;
+    let off_quote = || {
+        get_call(
+            "AAPL".into(),
+            93.0,
+            valuation_time() + Duration::days(300),
+            0.0,
+        )
+    };
+    let model_price = calibration.price(&QuotedOption::Call(off_quote()))?;
+    let risk_factors = calibration.risk_factors(&QuotedOption::Call(off_quote()))?;
+    let engine_price = off_quote().value_black_scholes(valuation_time(), risk_factors, vec![])?;
+    assert!(
+        (engine_price - model_price).abs() < 1e-6,
+        "Black-Scholes engine ({}) differs from the calibrated model ({})",
+        engine_price,
+        model_price
+    );
+    Ok(())
+}
+
+#[test]
+fn calibration_rejects_empty_or_unweighted_quotes() {
+    let method = CalibrationMethod::LevenbergMarquardt;
+    assert!(calibrate(FlatVolatility, market(), &[], &method).is_err());
+    let unweighted = vec![CalibrationQuote {
+        option: call(100.0, 365),
+        value: QuotedValue::ImpliedVolatility(0.2),
+        weight: 0.0,
+    }];
+    assert!(calibrate(FlatVolatility, market(), &unweighted, &method).is_err());
+}
@@ -4,12 +4,28 @@ use crate::Pricer;
 use crate::risk_factors::discount::DiscountFactor;
 use crate::risk_factors::{IdentifiableRiskFactor, RiskFactors};
 
-use crate::shock::{absolute_shock, absolute_time_shock};
-use crate::shock::{interest_rate_shock, price_shock, time_shock, volatility_shock};
+use crate::shock::{absolute_shock, absolute_time_shock, borrow_shock};
+use crate::shock::{
+    interest_rate_shock, key_rate_shock, price_shock, time_shock, volatility_shock,
+};
 use crate::shock::{Scenario, Shock, ShockDirection};
 
 use chrono::{DateTime, Duration, Utc};
 
+// Rho against the part of the curve around `tenor`, per 1% move in rates
+#[derive(Debug)]
+pub struct KeyRateRho {
+    pub tenor: f64,
+    pub rho: f64,
+}
+
+fn no_yield_curve_err() -> PricerError {
+    PricerError::new(
+        String::from("Key rate rho needs a yield curve among the discount factors"),
+        16,
+    )
+}
+
 pub trait FiniteDifferenceGreeks {
     fn delta_fd(
         &self,
@@ -18,6 +34,17 @@ pub trait FiniteDifferenceGreeks {
     ) -> PricerResult<f64>;
     fn rho_fd(&self, valuation_time: DateTime<Utc>, risk_factors: RiskFactors)
         -> PricerResult<f64>;
+    fn key_rate_rho_fd(
+        &self,
+        valuation_time: DateTime<Utc>,
+        risk_factors: RiskFactors,
+        key_rates: &[f64],
+    ) -> PricerResult<Vec<KeyRateRho>>;
+    fn borrow_rho_fd(
+        &self,
+        valuation_time: DateTime<Utc>,
+        risk_factors: RiskFactors,
+    ) -> PricerResult<f64>;
     fn theta_fd(
         &self,
         valuation_time: DateTime<Utc>,
@@ -30,19 +57,33 @@ pub trait FiniteDifferenceGreeks {
     ) -> PricerResult<f64>;
 }
 
+// Base and shocked values come from one call so that simulating pricers reuse their random draws
+// and the differences are not dominated by sampling noise
+fn bump_and_reprice_scenarios<T: Pricer>(
+    option: &T,
+    valuation_time: DateTime<Utc>,
+    risk_factors: RiskFactors,
+    scenarios: Vec<Scenario>,
+) -> PricerResult<Vec<f64>> {
+    let mut all_scenarios = vec![vec![]];
+    all_scenarios.extend(scenarios);
+    let values = option
+        .values(valuation_time, risk_factors, all_scenarios)
+        .map_err(|e| PricerError::new(format!("Failed bump and reprice: {}", e), 2))?;
+    Ok(values[1..]
+        .iter()
+        .map(|shocked| shocked - values[0])
+        .collect())
+}
+
 fn bump_and_reprice<T: Pricer>(
     option: &T,
     valuation_time: DateTime<Utc>,
     risk_factors: RiskFactors,
     scenario: Scenario,
 ) -> PricerResult<f64> {
-    let base = option
-        .value(valuation_time, risk_factors.clone(), vec![])
-        .map_err(|e| PricerError::new(format!("Failed base pricing: {}", e), 2))?;
-    let shock = option
-        .value(valuation_time, risk_factors, scenario)
-        .map_err(|e| PricerError::new(format!("Failed shock pricing: {}", e), 3))?;
-    Ok(shock - base)
+    bump_and_reprice_scenarios(option, valuation_time, risk_factors, vec![scenario])
+        .map(|differences| differences[0])
 }
 
 impl<T> FiniteDifferenceGreeks for T
@@ -61,6 +102,7 @@ where
             .collect();
         bump_and_reprice(self, valuation_time, risk_factors, delta_shocks)
     }
+    // Sensitivity to a parallel move of the rates per 1%, from a one basis point bump
     fn rho_fd(
         &self,
         valuation_time: DateTime<Utc>,
@@ -70,14 +112,78 @@ where
             .discount_factors
             .iter()
             .flat_map(|discount| match discount {
-                DiscountFactor::RiskFreeRate(rfr) => Some(interest_rate_shock(
-                    rfr.id().clone(),
-                    absolute_shock(1.0, ShockDirection::Up),
-                )),
+                DiscountFactor::RiskFreeRate(_) | DiscountFactor::YieldCurve(_) => {
+                    Some(interest_rate_shock(
+                        discount.id().clone(),
+                        absolute_shock(0.0001, ShockDirection::Up),
+                    ))
+                }
                 _ => None,
             })
             .collect();
-        bump_and_reprice(self, valuation_time, risk_factors, rho_shocks).map(|value| value / 100.0)
+        bump_and_reprice(self, valuation_time, risk_factors, rho_shocks).map(|value| value * 100.0)
+    }
+    // Each bucket takes a one basis point triangular shift of every curve, so the ladder sums to
+    // the parallel rho from `rho_fd`
+    fn key_rate_rho_fd(
+        &self,
+        valuation_time: DateTime<Utc>,
+        risk_factors: RiskFactors,
+        key_rates: &[f64],
+    ) -> PricerResult<Vec<KeyRateRho>> {
+        let has_yield_curve = risk_factors
+            .discount_factors
+            .iter()
+            .any(|discount| matches!(discount, DiscountFactor::YieldCurve(_)));
+        if !has_yield_curve {
+            return Err(no_yield_curve_err());
+        }
+        let scenarios = (0..key_rates.len())
+            .map(|bucket| {
+                risk_factors
+                    .discount_factors
+                    .iter()
+                    .flat_map(|discount| match discount {
+                        DiscountFactor::YieldCurve(curve) => Some(key_rate_shock(
+                            curve.id().clone(),
+                            key_rates.to_vec(),
+                            bucket,
+                            absolute_shock(0.0001, ShockDirection::Up),
+                        )),
+                        _ => None,
+                    })
+                    .collect()
+            })
+            .collect();
+        bump_and_reprice_scenarios(self, valuation_time, risk_factors, scenarios).map(|values| {
+            key_rates
+                .iter()
+                .zip(values)
+                .map(|(tenor, value)| KeyRateRho {
+                    tenor: *tenor,
+                    rho: value * 100.0,
+                })
+                .collect()
+        })
+    }
+    // Sensitivity to the stock borrow fee per 1% move, from a one basis point bump
+    fn borrow_rho_fd(
+        &self,
+        valuation_time: DateTime<Utc>,
+        risk_factors: RiskFactors,
+    ) -> PricerResult<f64> {
+        let borrow_shocks = risk_factors
+            .borrow_sensitivities
+            .iter()
+            .map(|borrow| {
+                borrow_shock(
+                    borrow.id().clone(),
+                    absolute_shock(0.0001, ShockDirection::Up),
+                )
+            })
+            .collect();
+        bump_and_reprice(self, valuation_time, risk_factors, borrow_shocks)
+            .map(|value| value * 100.0)
     }
     fn theta_fd(
         &self,
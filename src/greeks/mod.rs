@@ -2,7 +2,7 @@ mod finite_difference;
 #[cfg(test)]
 mod test;
 
-pub use finite_difference::FiniteDifferenceGreeks;
+pub use finite_difference::{FiniteDifferenceGreeks, KeyRateRho};
 
 use crate::result::PricerResult;
 use crate::risk_factors::RiskFactors;
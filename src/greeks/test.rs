@@ -1,8 +1,30 @@
+use crate::black_scholes::BlackScholesGreeks;
 use crate::greeks::FiniteDifferenceGreeks;
+use crate::option::FinancialOption;
 use crate::result::PricerResult;
+use crate::risk_factors::borrow::BorrowRate;
+use crate::risk_factors::discount::DiscountFactor;
+use crate::risk_factors::yield_curve::{CurveInterpolation, YieldCurve};
+use crate::risk_factors::RiskFactors;
+use crate::shock::{absolute_shock, key_rate_shock, Shock, ShockDirection};
+use crate::symbol::Symbol;
+use crate::utils::date::get_duration_in_years;
 use crate::utils::test_utils::{get_test_call, is_close};
 use crate::Priceable;
 
+const KEY_RATES: [f64; 4] = [0.25, 0.5, 1.0, 2.0];
+
+fn with_test_curve(mut risk_factors: RiskFactors) -> PricerResult<RiskFactors> {
+    let curve = YieldCurve::new(
+        Symbol::from("US Treasury 3M"),
+        KEY_RATES.to_vec(),
+        vec![0.04, 0.045, 0.05, 0.052],
+        CurveInterpolation::LinearZero,
+    )?;
+    risk_factors.discount_factors = vec![DiscountFactor::YieldCurve(curve)];
+    Ok(risk_factors)
+}
+
 #[test]
 fn monte_carlo_near_black_scholes_finite_difference_delta() -> PricerResult<()> {
     let (call, valuation_time, risk_factors) = get_test_call();
@@ -12,8 +34,8 @@ fn monte_carlo_near_black_scholes_finite_difference_delta() -> PricerResult<()>
         black_scholes_priceable.delta_fd(valuation_time, risk_factors.clone())?;
     let monte_carlo_delta = monte_carlo_priceable.delta_fd(valuation_time, risk_factors)?;
     assert!(
-        is_close(black_scholes_delta, monte_carlo_delta, 0.2),
-        "Black-Scholes finite difference delta ({}) differs from Monte Carlo ({}) by more than 20%",
+        is_close(black_scholes_delta, monte_carlo_delta, 0.05),
+        "Black-Scholes finite difference delta ({}) differs from Monte Carlo ({}) by more than 5%",
         black_scholes_delta,
         monte_carlo_delta,
     );
@@ -29,8 +51,8 @@ fn monte_carlo_near_black_scholes_finite_difference_vega() -> PricerResult<()> {
         black_scholes_priceable.vega_fd(valuation_time, risk_factors.clone())?;
     let monte_carlo_vega = monte_carlo_priceable.vega_fd(valuation_time, risk_factors)?;
     assert!(
-        is_close(black_scholes_vega, monte_carlo_vega, 0.2),
-        "Black-Scholes finite difference vega ({}) differs from Monte Carlo ({}) by more than 20%",
+        is_close(black_scholes_vega, monte_carlo_vega, 0.05),
+        "Black-Scholes finite difference vega ({}) differs from Monte Carlo ({}) by more than 5%",
         black_scholes_vega,
         monte_carlo_vega,
     );
@@ -45,10 +67,104 @@ fn monte_carlo_near_black_scholes_finite_difference_rho() -> PricerResult<()> {
     let black_scholes_rho = black_scholes_priceable.rho_fd(valuation_time, risk_factors.clone())?;
     let monte_carlo_rho = monte_carlo_priceable.rho_fd(valuation_time, risk_factors)?;
     assert!(
-        is_close(black_scholes_rho, monte_carlo_rho, 0.2),
-        "Black-Scholes finite difference rho ({}) differs from Monte Carlo ({}) by more than 20%",
+        is_close(black_scholes_rho, monte_carlo_rho, 0.05),
+        "Black-Scholes finite difference rho ({}) differs from Monte Carlo ({}) by more than 5%",
         black_scholes_rho,
         monte_carlo_rho,
     );
     Ok(())
 }
+
+#[test]
+fn key_rate_rho_ladder_sums_to_parallel_rho() -> PricerResult<()> {
+    let (call, valuation_time, risk_factors) = get_test_call();
+    let risk_factors = with_test_curve(risk_factors)?;
+    let priceable = Priceable::BlackScholes(&call);
+    let ladder = priceable.key_rate_rho_fd(valuation_time, risk_factors.clone(), &KEY_RATES)?;
+    let ladder_rho: f64 = ladder.iter().map(|key_rate| key_rate.rho).sum();
+    let parallel_rho = priceable.rho_fd(valuation_time, risk_factors.clone())?;
+    assert!(
+        (ladder_rho - parallel_rho).abs() < 1e-6 * parallel_rho.abs(),
+        "Key rate rho ladder sums to {}, parallel rho is {}",
+        ladder_rho,
+        parallel_rho
+    );
+    let analytical_rho = call.rho(valuation_time, risk_factors)?;
+    assert!(
+        is_close(ladder_rho, analytical_rho, 0.01),
+        "Key rate rho ladder ({:?}) sums to {}, analytical rho is {}",
+        ladder,
+        ladder_rho,
+        analytical_rho
+    );
+    Ok(())
+}
+
+#[test]
+fn key_rate_rho_is_concentrated_around_expiry() -> PricerResult<()> {
+    let (call, valuation_time, risk_factors) = get_test_call();
+    let risk_factors = with_test_curve(risk_factors)?;
+    let priceable = Priceable::BlackScholes(&call);
+    let ladder = priceable.key_rate_rho_fd(valuation_time, risk_factors, &KEY_RATES)?;
+    assert!(ladder
+        .iter()
+        .map(|key_rate| key_rate.tenor)
+        .eq(KEY_RATES.iter().cloned()));
+    // The option expires just over half a year out, between the second and third key rates
+    assert!(ladder[1].rho > 0.0 && ladder[2].rho > 0.0);
+    assert!(ladder[0].rho.abs() < 1e-12 && ladder[3].rho.abs() < 1e-12);
+    Ok(())
+}
+
+#[test]
+fn key_rate_rho_needs_a_yield_curve() {
+    let (call, valuation_time, risk_factors) = get_test_call();
+    let priceable = Priceable::BlackScholes(&call);
+    assert!(priceable
+        .key_rate_rho_fd(valuation_time, risk_factors, &KEY_RATES)
+        .is_err_and(|error| error.code == 16));
+}
+
+#[test]
+fn key_rate_shock_weights_sum_to_parallel_shift() {
+    for t in [0.1, 0.3, 0.75, 1.5, 3.0] {
+        let total: f64 = (0..KEY_RATES.len())
+            .map(|bucket| {
+                match key_rate_shock(
+                    "USD".into(),
+                    KEY_RATES.to_vec(),
+                    bucket,
+                    absolute_shock(0.0001, ShockDirection::Up),
+                ) {
+                    Shock::KeyRateShock(shock) => shock.weight(t),
+                    _ => 0.0,
+                }
+            })
+            .sum();
+        assert!(
+            (total - 1.0).abs() < 1e-12,
+            "Weights at {} sum to {}",
+            t,
+            total
+        );
+    }
+}
+
+#[test]
+fn borrow_rho_near_analytical_borrow_rho() -> PricerResult<()> {
+    let (call, valuation_time, mut risk_factors) = get_test_call();
+    risk_factors.borrow_sensitivities = vec![BorrowRate::new("AAPL".into(), 0.03)];
+    let priceable = Priceable::BlackScholes(&call);
+    let borrow_rho = priceable.borrow_rho_fd(valuation_time, risk_factors.clone())?;
+    // The borrow fee enters like a dividend yield, so its rho is -T S e^(-bT) N(d1)
+    let delta_t = get_duration_in_years(valuation_time, call.expiry());
+    let delta = call.delta(valuation_time, risk_factors)?;
+    let analytical_borrow_rho = -0.01 * delta_t * 42.0 * delta;
+    assert!(
+        is_close(borrow_rho, analytical_borrow_rho, 0.01),
+        "Finite difference borrow rho ({}) differs from analytical ({}) by more than 1%",
+        borrow_rho,
+        analytical_borrow_rho
+    );
+    Ok(())
+}
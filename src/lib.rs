@@ -1,3 +1,4 @@
+pub mod calibration;
 pub mod option;
 pub mod result;
 pub mod shock_grid;
@@ -16,9 +17,22 @@ mod utils;
 use pyo3::prelude::*;
 
 use chrono::{DateTime, Utc};
+use std::time::Duration;
 
-pub use black_scholes::BlackScholes;
-use monte_carlo::{MonteCarlo, MonteCarloParams};
+pub use black_scholes::{black_scholes_price, BlackScholes};
+pub use black_scholes::{
+    BlackScholesFiniteDifference, BoundaryCondition, FiniteDifferenceParams,
+    FiniteDifferenceScheme, FiniteDifferenceValuation,
+};
+pub use greeks::{FiniteDifferenceGreeks, KeyRateRho};
+pub use monte_carlo::LongstaffSchwartzMonteCarlo;
+pub use monte_carlo::{greek_estimates_monte_carlo, Barrier, BarrierPayoff, DigitalPayoff};
+pub use monte_carlo::{GreekEstimator, MonteCarloGreekEstimates};
+use monte_carlo::{LongstaffSchwartzParams, Measure, MonteCarlo, MonteCarloEstimate};
+use monte_carlo::{MonteCarloParams, TargetAccuracy, Tolerance};
+pub use tree::lattice::{LatticeScheme, TrinomialScheme};
+use tree::BinomialTree;
+pub use tree::{TreeParams, TreeScheme};
 
 use option::{Call, Put};
 use risk_factors::{discount::rfr_discount, RiskFactors};
@@ -26,12 +40,15 @@ use shock_grid::{generate_shock_grid, ShockGrid, ShockLimits};
 
 use result::PricerResult;
 use shock::Scenario;
+pub use shock::{absolute_shock, dividend_shock, key_rate_shock, ShockDirection};
 
 use log::debug;
 
 pub enum Priceable<'a> {
     BlackScholes(&'a dyn BlackScholes),
     MonteCarlo(&'a dyn MonteCarlo),
+    LongstaffSchwartz(&'a dyn LongstaffSchwartzMonteCarlo),
+    Tree(&'a dyn BinomialTree, TreeParams),
 }
 
 pub trait Pricer {
@@ -41,6 +58,47 @@ pub trait Pricer {
         risk_factors: RiskFactors,
         scenario: Scenario,
     ) -> PricerResult<f64>;
+    // Values under each scenario. Pricers that simulate value every scenario on the same paths
+    fn values(
+        &self,
+        valuation_time: DateTime<Utc>,
+        risk_factors: RiskFactors,
+        scenarios: Vec<Scenario>,
+    ) -> PricerResult<Vec<f64>> {
+        scenarios
+            .into_iter()
+            .map(|scenario| self.value(valuation_time, risk_factors.clone(), scenario))
+            .collect()
+    }
+}
+
+impl Priceable<'_> {
+    // Simulates until the standard error is a percent of the value, giving up after ten million
+    // paths. Bump and reprice greeks share their paths, so their differences are far more accurate
+    // than that. No time budget, so values and greeks are reproducible
+    fn monte_carlo_params() -> MonteCarloParams {
+        MonteCarloParams {
+            repetitions: 10_000_000,
+            target: Some(TargetAccuracy {
+                tolerance: Tolerance::Relative(0.01),
+                ..Default::default()
+            }),
+            ..Default::default()
+        }
+    }
+    // Exercisable at fifty dates, with the exercise rule fitted on independent paths so the value
+    // is not biased upwards by foresight
+    fn longstaff_schwartz_params() -> MonteCarloParams {
+        MonteCarloParams {
+            steps: 50,
+            repetitions: 20_000,
+            longstaff_schwartz: LongstaffSchwartzParams {
+                out_of_sample: true,
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
 }
 
 impl Pricer for Priceable<'_> {
@@ -54,15 +112,46 @@ impl Pricer for Priceable<'_> {
             Priceable::BlackScholes(bs_option) => {
                 bs_option.value_black_scholes(valuation_time, risk_factors, scenario)
             }
-            Priceable::MonteCarlo(ms_option) => ms_option.value_monte_carlo(
-                valuation_time,
-                risk_factors,
-                scenario,
-                MonteCarloParams {
-                    steps: 10000,
-                    repetitions: 1000,
-                },
-            ),
+            Priceable::MonteCarlo(ms_option) => ms_option
+                .value_monte_carlo(
+                    valuation_time,
+                    risk_factors,
+                    scenario,
+                    Priceable::monte_carlo_params(),
+                )
+                .map(|estimate| estimate.value),
+            Priceable::LongstaffSchwartz(ls_option) => ls_option
+                .value_monte_carlo_ls(
+                    valuation_time,
+                    risk_factors,
+                    scenario,
+                    Priceable::longstaff_schwartz_params(),
+                )
+                .map(|estimate| estimate.value),
+            Priceable::Tree(tree_option, parameters) => {
+                tree_option.value_tree(valuation_time, risk_factors, scenario, parameters)
+            }
+        }
+    }
+    fn values(
+        &self,
+        valuation_time: DateTime<Utc>,
+        risk_factors: RiskFactors,
+        scenarios: Vec<Scenario>,
+    ) -> PricerResult<Vec<f64>> {
+        match &self {
+            Priceable::MonteCarlo(ms_option) => ms_option
+                .value_monte_carlo_scenarios(
+                    valuation_time,
+                    risk_factors,
+                    &scenarios,
+                    Priceable::monte_carlo_params(),
+                )
+                .map(|estimates| estimates.iter().map(|estimate| estimate.value).collect()),
+            _ => scenarios
+                .into_iter()
+                .map(|scenario| self.value(valuation_time, risk_factors.clone(), scenario))
+                .collect(),
         }
     }
 }
@@ -92,27 +181,119 @@ pub fn price_black_scholes(
 }
 
 #[pyfunction]
+#[pyo3(signature = (
+    py_call,
+    underlying_price,
+    underlying_volatility,
+    apr,
+    annualised_historic_return,
+    real_world,
+    seed = 0
+))]
 pub fn gen_monte_carlo_paths(
     py_call: Bound<Call>,
     underlying_price: f64,
     underlying_volatility: f64,
+    apr: f64,
     annualised_historic_return: f64,
+    real_world: bool,
+    seed: u64,
 ) -> PyResult<Vec<Vec<f64>>> {
     let call = py_call.borrow();
-    let risk_factors = call.get_monte_carlo_risk_factors(
+    let risk_factors = MonteCarlo::get_monte_carlo_risk_factors(
+        &*call,
         underlying_price,
         underlying_volatility,
+        rfr_discount("US Treasury 3M".into(), apr),
         annualised_historic_return,
     );
-    call.generate_monte_carlo_paths(
+    let measure = if real_world {
+        Measure::RealWorld
+    } else {
+        Measure::RiskNeutral
+    };
+    MonteCarlo::generate_monte_carlo_paths(
+        &*call,
         Utc::now(),
         risk_factors,
         MonteCarloParams {
             steps: 10000,
             repetitions: 1000,
+            seed,
+            ..Default::default()
+        },
+        measure,
+    )
+    .map_err(|e| e.into())
+}
+
+#[pyfunction]
+#[pyo3(signature = (
+    py_call,
+    underlying_price,
+    underlying_volatility,
+    apr,
+    steps = 10000,
+    repetitions = 1000,
+    seed = 0,
+    target_standard_error = None,
+    target_relative_error = None,
+    time_budget_seconds = None
+))]
+#[allow(clippy::too_many_arguments)]
+pub fn price_monte_carlo(
+    py_call: Bound<Call>,
+    underlying_price: f64,
+    underlying_volatility: f64,
+    apr: f64,
+    steps: usize,
+    repetitions: usize,
+    seed: u64,
+    target_standard_error: Option<f64>,
+    target_relative_error: Option<f64>,
+    time_budget_seconds: Option<f64>,
+) -> PyResult<MonteCarloEstimate> {
+    let call = py_call.borrow();
+    // The historic return only drives real-world paths and has no bearing on the price
+    let risk_factors = MonteCarlo::get_monte_carlo_risk_factors(
+        &*call,
+        underlying_price,
+        underlying_volatility,
+        rfr_discount("US Treasury 3M".into(), apr),
+        0.,
+    );
+    // With a target, repetitions caps the paths. A target standard error takes precedence
+    let tolerance = target_standard_error
+        .map(Tolerance::StandardError)
+        .or(target_relative_error.map(Tolerance::Relative));
+    let target = tolerance.map(|tolerance| TargetAccuracy {
+        tolerance,
+        // Without a budget the run is reproducible. A budget that is not a duration stops after
+        // the first batch
+        time_budget: time_budget_seconds.map_or(Duration::MAX, |seconds| {
+            Duration::try_from_secs_f64(seconds).unwrap_or_default()
+        }),
+        ..Default::default()
+    });
+    call.value_monte_carlo(
+        Utc::now(),
+        risk_factors,
+        vec![],
+        MonteCarloParams {
+            steps,
+            repetitions,
+            seed,
+            target,
+            ..Default::default()
         },
     )
     .map_err(|e| e.into())
+    .inspect(|estimate| {
+        debug!(
+            "Valued call at {} with standard error {}",
+            estimate.value, estimate.standard_error
+        )
+    })
 }
 
 #[pymodule]
@@ -128,5 +309,7 @@ fn pricer(m: &Bound<'_, PyModule>) -> PyResult<()> {
     m.add_function(wrap_pyfunction!(generate_shock_grid, m)?)?;
 
     m.add_function(wrap_pyfunction!(gen_monte_carlo_paths, m)?)?;
+    m.add_function(wrap_pyfunction!(price_monte_carlo, m)?)?;
+    m.add_class::<MonteCarloEstimate>()?;
     Ok(())
 }
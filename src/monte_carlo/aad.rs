@@ -0,0 +1,197 @@
+use super::draws::{fold_blocks, DrawGenerator};
+use super::european::observation_dates;
+use super::{MonteCarloInputs, MonteCarloParams, PathConstruction};
+
+use crate::result::PricerResult;
+use crate::risk_factors::dividend::DividendPayment;
+use crate::utils::aad::{Tape, Var};
+use crate::utils::date::DAYS_IN_YEAR;
+
+// Value and first order sensitivities from a single simulation, in the units of the analytical
+// greeks: vega and rho per percentage point and theta per calendar day
+#[derive(Debug)]
+pub struct MonteCarloGreeks {
+    pub value: f64,
+    pub delta: f64,
+    pub vega: f64,
+    pub rho: f64,
+    pub theta: f64,
+}
+
+impl MonteCarloGreeks {
+    // Early exercise today is worth the intrinsic value and only moves with the price
+    pub fn immediate_exercise(value: f64, delta: f64) -> MonteCarloGreeks {
+        MonteCarloGreeks {
+            value,
+            delta,
+            vega: 0.0,
+            rho: 0.0,
+            theta: 0.0,
+        }
+    }
+}
+
+// The inputs a path is differentiated against, recorded first on each path's tape. The rate and
+// volatility are those to expiry, theta leaves them unchanged as time passes
+pub struct TapedInputs<'t> {
+    tape: &'t Tape,
+    pub price: Var<'t>,
+    pub volatility: Var<'t>,
+    pub rate: Var<'t>,
+    pub expiry: Var<'t>,
+    carry: Var<'t>,
+}
+
+impl<'t> TapedInputs<'t> {
+    fn record(tape: &'t Tape, inputs: &MonteCarloInputs) -> TapedInputs<'t> {
+        let rate = tape.variable(inputs.discount_rate());
+        TapedInputs {
+            tape,
+            price: tape.variable(inputs.price()),
+            volatility: tape.variable(inputs.volatility()),
+            rate,
+            expiry: tape.variable(inputs.delta_t),
+            // The dividend yield and borrow fee are held fixed, so the carry moves with the rate
+            carry: rate - (inputs.dividend_yield() + inputs.borrow_rate()),
+        }
+    }
+    // A value that is not differentiated against
+    pub fn constant(&self, value: f64) -> Var<'t> {
+        self.tape.variable(value)
+    }
+    // Risk-neutral lognormal step of `price` over `interval` driven by the standard normal `draw`
+    pub fn evolve(&self, price: Var<'t>, interval: Var<'t>, draw: f64) -> Var<'t> {
+        if interval.value() <= 0.0 {
+            return price;
+        }
+        let drift = (self.carry - 0.5 * self.volatility * self.volatility) * interval;
+        let diffusion = self.volatility * interval.sqrt() * draw;
+        price * (drift + diffusion).exp()
+    }
+    // Discounts a value received after `time` at the rate to expiry
+    pub fn discount(&self, value: Var<'t>, time: Var<'t>) -> Var<'t> {
+        value * (-(self.rate * time)).exp()
+    }
+}
+
+pub fn pay_taped_dividends<'t>(price: Var<'t>, payments: &[DividendPayment]) -> Var<'t> {
+    payments.iter().fold(price, |price, payment| match payment {
+        DividendPayment::Cash(amount) => (price - *amount).max(0.0),
+        DividendPayment::Proportional(fraction) => price * (1.0 - fraction),
+    })
+}
+
+pub fn vanilla_payoff(is_call: bool, strike: f64, price: Var<'_>) -> Var<'_> {
+    if is_call {
+        (price - strike).max(0.0)
+    } else {
+        (strike - price).max(0.0)
+    }
+}
+
+// Sums of the discounted path values and of their adjoints to each taped input
+#[derive(Clone, Copy, Debug, Default)]
+struct PathSensitivities {
+    paths: f64,
+    value: f64,
+    price: f64,
+    volatility: f64,
+    rate: f64,
+    expiry: f64,
+}
+
+impl PathSensitivities {
+    fn push(&mut self, value: Var<'_>, inputs: &TapedInputs<'_>) {
+        let adjoints = value.adjoints();
+        let adjoint = |input: &Var<'_>| adjoints.get(input.index()).copied().unwrap_or(0.0);
+        self.paths += 1.0;
+        self.value += value.value();
+        self.price += adjoint(&inputs.price);
+        self.volatility += adjoint(&inputs.volatility);
+        self.rate += adjoint(&inputs.rate);
+        self.expiry += adjoint(&inputs.expiry);
+    }
+    fn merge(&self, other: &PathSensitivities) -> PathSensitivities {
+        PathSensitivities {
+            paths: self.paths + other.paths,
+            value: self.value + other.value,
+            price: self.price + other.price,
+            volatility: self.volatility + other.volatility,
+            rate: self.rate + other.rate,
+            expiry: self.expiry + other.expiry,
+        }
+    }
+    fn greeks(&self) -> MonteCarloGreeks {
+        MonteCarloGreeks {
+            value: self.value / self.paths,
+            delta: self.price / self.paths,
+            vega: 0.01 * self.volatility / self.paths,
+            rho: 0.01 * self.rate / self.paths,
+            theta: -self.expiry / self.paths / DAYS_IN_YEAR as f64,
+        }
+    }
+}
+
+// Averages the pathwise derivatives of every path's discounted value, which `value` records on a
+// fresh tape per path. One backward sweep per path gives all the greeks at once, so the cost is a
+// small multiple of pricing rather than a re-simulation per greek
+pub fn differentiate_paths<F>(inputs: &MonteCarloInputs, paths: usize, value: F) -> MonteCarloGreeks
+where
+    F: for<'t> Fn(usize, &TapedInputs<'t>) -> Var<'t> + Sync,
+{
+    fold_blocks(
+        0..paths,
+        |block| {
+            let mut sensitivities = PathSensitivities::default();
+            for path in block {
+                let tape = Tape::new();
+                let taped = TapedInputs::record(&tape, inputs);
+                sensitivities.push(value(path, &taped), &taped);
+            }
+            sensitivities
+        },
+        |lhs, rhs| lhs.merge(&rhs),
+    )
+    .unwrap_or_default()
+    .greeks()
+}
+
+// Greeks of a vanilla European option from the same draws as its price, stepping only between
+// the dates the payoff observes. Ex-dates are held relative to expiry, so the time to expiry
+// only moves the first interval
+pub fn european_greeks(
+    is_call: bool,
+    strike: f64,
+    inputs: &MonteCarloInputs,
+    parameters: &MonteCarloParams,
+) -> PricerResult<MonteCarloGreeks> {
+    let dates = observation_dates(inputs, 1);
+    let generator = DrawGenerator::new(
+        parameters,
+        dates.intervals.len(),
+        &PathConstruction::Incremental,
+    )?;
+    let after_first: f64 = dates.intervals[1..].iter().sum();
+    Ok(differentiate_paths(
+        inputs,
+        parameters.repetitions,
+        |path, taped| {
+            let draws = generator.draws(path);
+            let tape_interval = |index: usize| {
+                if index == 0 {
+                    taped.expiry - after_first
+                } else {
+                    taped.constant(dates.intervals[index])
+                }
+            };
+            let price = draws.iter().zip(&dates.dividends).enumerate().fold(
+                taped.price,
+                |price, (index, (draw, payments))| {
+                    let evolved = taped.evolve(price, tape_interval(index), *draw);
+                    pay_taped_dividends(evolved, payments)
+                },
+            );
+            taped.discount(vanilla_payoff(is_call, strike, price), taped.expiry)
+        },
+    ))
+}
@@ -1,7 +1,15 @@
-use super::{MonteCarloInputs, MonteCarloParams};
+use super::aad::{differentiate_paths, pay_taped_dividends, vanilla_payoff, MonteCarloGreeks};
+use super::conventional::{
+    dividends_by_step, generate_monte_carlo_paths, simulate_monte_carlo_paths,
+};
+use super::draws::DrawGenerator;
+use super::estimate::{PayoffMoments, SampleMean};
+use super::regression::ExercisePolicy;
+use super::variance_reduction::vanilla_expected_payoff;
+use super::{Measure, MonteCarloEstimate, MonteCarloInputs, MonteCarloParams};
 
 use crate::option::{Call, FinancialOption, Put};
-use crate::result::{make_not_implemented_error, PricerError, PricerResult};
+use crate::result::{make_not_implemented_error, PricerResult};
 
 use crate::risk_factors::discount::{DiscountFactor, HistoricReturn};
 use crate::risk_factors::price::{Price, PriceTick};
@@ -11,18 +19,14 @@ use crate::risk_factors::RiskFactors;
 use crate::shock::{ApplyShock, Scenario};
 
 use chrono::{DateTime, Utc};
-use ndarray::{Array, Array2};
-use rand::Rng;
-use rayon::prelude::*;
-use statrs::distribution::Normal;
-use statrs::statistics::Statistics;
-use statrs::StatsError;
+use std::time::Instant;
 
 pub trait LongstaffSchwartzMonteCarlo: FinancialOption {
     fn get_monte_carlo_risk_factors(
         &self,
         price: f64,
         volatility: f64,
+        discount_factor: DiscountFactor,
         historic_return: f64,
     ) -> RiskFactors {
         RiskFactors {
@@ -34,88 +38,96 @@ pub trait LongstaffSchwartzMonteCarlo: FinancialOption {
                 self.symbol().clone(),
                 volatility,
             ))],
-            discount_factors: vec![DiscountFactor::HistoricReturn(HistoricReturn::new(
-                self.symbol().clone(),
-                historic_return,
-            ))],
+            discount_factors: vec![
+                discount_factor,
+                DiscountFactor::HistoricReturn(HistoricReturn::new(
+                    self.symbol().clone(),
+                    historic_return,
+                )),
+            ],
             dividend_sensitivities: vec![],
+            borrow_sensitivities: vec![],
         }
     }
     fn value_monte_carlo_ls_impl(
         &self,
         inputs: MonteCarloInputs,
         parameters: MonteCarloParams,
-    ) -> PricerResult<f64>;
+    ) -> PricerResult<SampleMean>;
     fn value_monte_carlo_ls(
         &self,
         valuation_time: DateTime<Utc>,
         risk_factors: RiskFactors,
         shock_scenarios: Scenario,
         parameters: MonteCarloParams,
-    ) -> PricerResult<f64> {
-        risk_factors.try_into().and_then(|risk_factors| {
-            let mut inputs = MonteCarloInputs::gather(self.expiry(), valuation_time, risk_factors);
-            shock_scenarios.apply(&mut inputs);
-            self.value_monte_carlo_ls_impl(inputs, parameters)
-        })
+    ) -> PricerResult<MonteCarloEstimate> {
+        let start = Instant::now();
+        let paths = parameters.repetitions;
+        risk_factors
+            .as_of(valuation_time)
+            .and_then(TryInto::try_into)
+            .and_then(|risk_factors| {
+                let mut inputs =
+                    MonteCarloInputs::gather(self.expiry(), valuation_time, risk_factors);
+                shock_scenarios.apply(&mut inputs);
+                self.value_monte_carlo_ls_impl(inputs, parameters)
+            })
+            .map(|sample| MonteCarloEstimate::new(sample, paths, None, start.elapsed()))
+    }
+    fn greeks_monte_carlo_ls_impl(
+        &self,
+        inputs: MonteCarloInputs,
+        parameters: MonteCarloParams,
+    ) -> PricerResult<MonteCarloGreeks>;
+    // Delta, vega, rho and theta differentiated through the same paths and exercise decisions as
+    // the price
+    fn greeks_monte_carlo_ls(
+        &self,
+        valuation_time: DateTime<Utc>,
+        risk_factors: RiskFactors,
+        shock_scenarios: Scenario,
+        parameters: MonteCarloParams,
+    ) -> PricerResult<MonteCarloGreeks> {
+        risk_factors
+            .as_of(valuation_time)
+            .and_then(TryInto::try_into)
+            .and_then(|risk_factors| {
+                let mut inputs =
+                    MonteCarloInputs::gather(self.expiry(), valuation_time, risk_factors);
+                shock_scenarios.apply(&mut inputs);
+                self.greeks_monte_carlo_ls_impl(inputs, parameters)
+            })
     }
     fn generate_monte_carlo_paths(
         &self,
         valuation_time: DateTime<Utc>,
         risk_factors: RiskFactors,
         parameters: MonteCarloParams,
+        measure: Measure,
     ) -> PricerResult<Vec<Vec<f64>>> {
-        risk_factors.try_into().and_then(|risk_factors| {
-            let inputs = MonteCarloInputs::gather(self.expiry(), valuation_time, risk_factors);
-            generate_monte_carlo_paths(&inputs, &parameters)
-        })
-    }
-}
-
-fn failed_to_create_gaussian_error(_: StatsError) -> PricerError {
-    PricerError {
-        code: 2,
-        message: String::from(
-            "Failed to construct Gaussian disrtribution for Black-Scholes pricing",
-        ),
+        risk_factors
+            .as_of(valuation_time)
+            .and_then(TryInto::try_into)
+            .and_then(|risk_factors| {
+                let inputs = MonteCarloInputs::gather(self.expiry(), valuation_time, risk_factors);
+                generate_monte_carlo_paths(&inputs, &parameters, &measure)
+            })
     }
 }
 
-fn gaussian() -> PricerResult<Normal> {
-    Normal::new(0.0, 1.0).map_err(failed_to_create_gaussian_error)
-}
-
-pub fn generate_monte_carlo_paths(
-    inputs: &MonteCarloInputs,
-    parameters: &MonteCarloParams,
-) -> PricerResult<Vec<Vec<f64>>> {
-    let dt = inputs.delta_t / parameters.steps as f64;
-    let nudt = (inputs.discount_rate() - 0.5 * inputs.volatility().powi(2)) * dt;
-    let sidt = inputs.volatility() * dt.sqrt();
-
-    let gaussian = gaussian()?;
-    Ok((0..parameters.repetitions)
-        .into_par_iter()
-        .map(|_| {
-            let mut rng = rand::thread_rng();
-            (0..parameters.steps)
-                .map(|_| rng.sample(gaussian))
-                .map(|sample| (nudt + sidt * sample).exp())
-                .scan(inputs.price(), |acc, v| {
-                    *acc = *acc * v;
-                    Some(*acc)
-                })
-                .collect()
-        })
-        .collect())
-}
-
 impl LongstaffSchwartzMonteCarlo for Call {
     fn value_monte_carlo_ls_impl(
         &self,
-        inputs: MonteCarloInputs,
-        parameters: MonteCarloParams,
-    ) -> PricerResult<f64> {
+        _inputs: MonteCarloInputs,
+        _parameters: MonteCarloParams,
+    ) -> PricerResult<SampleMean> {
+        Err(make_not_implemented_error())
+    }
+    fn greeks_monte_carlo_ls_impl(
+        &self,
+        _inputs: MonteCarloInputs,
+        _parameters: MonteCarloParams,
+    ) -> PricerResult<MonteCarloGreeks> {
         Err(make_not_implemented_error())
     }
 }
@@ -128,12 +140,59 @@ fn zero_or_more(val: f64) -> f64 {
     }
 }
 
-fn vectomatrix(vec: Vec<Vec<f64>>) -> PricerResult<Array2<f64>> {
-    let shape = (vec.len(), vec.first().map(|v| v.len()).unwrap_or(0));
-    Ok(
-        Array::from_shape_vec(shape, vec.into_iter().flatten().collect())
-            .map_err(|_| PricerError::new("f".into(), 1))?,
-    )
+// The Longstaff-Schwartz exercise of a put on the pricing paths
+struct PutRegression {
+    // Cash flow of each path discounted to the valuation time
+    payoffs: Vec<f64>,
+    // Date each path is exercised at, counted in steps from the valuation time
+    exercise_dates: Vec<usize>,
+    controls: Vec<f64>,
+}
+
+// Price at the valuation time followed by the price after each step
+fn with_valuation_price(paths: Vec<Vec<f64>>, price: f64) -> Vec<Vec<f64>> {
+    paths
+        .into_iter()
+        .map(|path| [price].into_iter().chain(path).collect())
+        .collect()
+}
+
+fn regress_put_exercise(
+    strike: f64,
+    inputs: &MonteCarloInputs,
+    parameters: &MonteCarloParams,
+) -> PricerResult<PutRegression> {
+    let exercise_value = |price: f64| zero_or_more(strike - price);
+    let dt = inputs.delta_t / parameters.steps as f64;
+    let step_discount = (-dt * inputs.discount_rate()).exp();
+
+    let simulated = simulate_monte_carlo_paths(inputs, parameters, &Measure::RiskNeutral)?;
+    let paths = with_valuation_price(simulated.paths, inputs.price());
+    let training_paths = if parameters.longstaff_schwartz.out_of_sample {
+        let training =
+            simulate_monte_carlo_paths(inputs, &parameters.independent(), &Measure::RiskNeutral)?;
+        Some(with_valuation_price(training.paths, inputs.price()))
+    } else {
+        None
+    };
+    let policy = ExercisePolicy::fit(
+        &parameters.longstaff_schwartz,
+        strike,
+        training_paths.as_ref().unwrap_or(&paths),
+        exercise_value,
+        step_discount,
+    );
+    let exercise_dates = policy.exercise_dates(&paths, exercise_value);
+    let payoffs = paths
+        .iter()
+        .zip(&exercise_dates)
+        .map(|(path, date)| exercise_value(path[*date]) * step_discount.powi(*date as i32))
+        .collect();
+    Ok(PutRegression {
+        payoffs,
+        exercise_dates,
+        controls: simulated.controls,
+    })
 }
 
 impl LongstaffSchwartzMonteCarlo for Put {
@@ -141,55 +200,86 @@ impl LongstaffSchwartzMonteCarlo for Put {
         &self,
         inputs: MonteCarloInputs,
         parameters: MonteCarloParams,
-    ) -> PricerResult<f64> {
-        let mut paths = generate_monte_carlo_paths(&inputs, &parameters)?;
-        paths
-            .iter_mut()
-            .for_each(|path: &mut Vec<f64>| path.insert(0, inputs.price()));
+    ) -> PricerResult<SampleMean> {
+        let regression = regress_put_exercise(self.strike(), &inputs, &parameters)?;
 
-        let dt = inputs.delta_t / parameters.steps as f64;
-        let step_discount = (-dt * inputs.discount_rate()).exp();
-
-        let mut payoffs: Vec<f64> = paths
+        let immediate_exercise = self.strike() - inputs.price();
+        let unit = if parameters.variance_reduction.antithetic {
+            2
+        } else {
+            1
+        };
+        // The European put on the same draws, its discounted payoff has the Black-Scholes price
+        // as its expectation
+        let samples: Vec<(f64, f64)> = regression
+            .payoffs
             .iter()
-            .map(|path| path.last().unwrap_or(&0.))
-            .map(|final_value| self.strike() - final_value)
-            .map(zero_or_more)
+            .zip(&regression.controls)
+            .map(|(payoff, terminal)| {
+                (
+                    *payoff,
+                    inputs.discount(zero_or_more(self.strike() - terminal)),
+                )
+            })
             .collect();
-
-        for step in (0..parameters.steps - 1).rev() {
-            let in_the_money: Vec<bool> = payoffs.iter().map(|v| v > &0.).collect();
-            let A: Vec<Vec<f64>> = paths
-                .iter()
-                .map(|path| path[step])
-                .map(|s_val| (0..3).map(|pow| s_val.powi(pow)).collect())
-                .collect();
-            let B: Vec<Vec<f64>> = A
+        let mut moments = PayoffMoments::default();
+        samples.chunks(unit).for_each(|unit| moments.push(unit));
+        let expected_value = if parameters.variance_reduction.control_variate {
+            let carry = inputs.carry(&Measure::RiskNeutral)?;
+            let control_mean = inputs.discount(vanilla_expected_payoff(
+                false,
+                self.strike(),
+                &inputs,
+                carry,
+            )?);
+            moments.control_variate(control_mean)
+        } else {
+            moments.sample_mean()
+        };
+        Ok(if expected_value.mean > immediate_exercise {
+            expected_value
+        } else {
+            SampleMean::exact(immediate_exercise)
+        })
+    }
+    // Each path's cash flow is differentiated through its simulation up to the exercise the
+    // regression chose, holding the exercise decisions fixed
+    fn greeks_monte_carlo_ls_impl(
+        &self,
+        inputs: MonteCarloInputs,
+        parameters: MonteCarloParams,
+    ) -> PricerResult<MonteCarloGreeks> {
+        let strike = self.strike();
+        let regression = regress_put_exercise(strike, &inputs, &parameters)?;
+        let steps = parameters.steps;
+        let dividends = dividends_by_step(
+            inputs.outstanding_dividends(),
+            inputs.delta_t / steps as f64,
+            steps,
+        );
+        let generator = DrawGenerator::new(&parameters, steps, &parameters.path_construction)?;
+        let greeks = differentiate_paths(&inputs, parameters.repetitions, |path, taped| {
+            let exercise_date = regression.exercise_dates[path];
+            let dt = taped.expiry * (1.0 / steps as f64);
+            let price = generator
+                .draws(path)
                 .iter()
-                .zip(in_the_money.iter())
-                .filter(|(_, is_itm)| **is_itm)
-                .map(|(a_row, _)| a_row.clone())
-                .collect();
-            let mat_a = vectomatrix(A)?;
-            let mat_b = vectomatrix(B)?;
-            let cv = mat_a.dot(&mat_b.t());
+                .zip(&dividends)
+                .take(exercise_date)
+                .fold(taped.price, |price, (draw, payments)| {
+                    pay_taped_dividends(taped.evolve(price, dt, *draw), payments)
+                });
+            taped.discount(
+                vanilla_payoff(false, strike, price),
+                dt * exercise_date as f64,
+            )
+        });
 
-            for p in 0..parameters.repetitions {
-                let exercise_value = zero_or_more(self.strike() - paths[p][step]);
-                if in_the_money[p] && cv[[p, 0]] < exercise_value {
-                    payoffs[p] = exercise_value * step_discount;
-                } else {
-                    payoffs[p] = payoffs[p] * step_discount;
-                }
-            }
-        }
-
-        let immediate_exercise = self.strike() - inputs.price();
-        let expected_value = payoffs.mean();
-        Ok(if expected_value > immediate_exercise {
-            expected_value
+        let immediate_exercise = strike - inputs.price();
+        Ok(if greeks.value > immediate_exercise {
+            greeks
         } else {
-            immediate_exercise
+            MonteCarloGreeks::immediate_exercise(immediate_exercise, -1.0)
         })
     }
 }
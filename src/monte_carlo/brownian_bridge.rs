@@ -0,0 +1,89 @@
+// Sources of equations:
+//  - Jäckel (2002), Monte Carlo methods in finance, section 10.8
+
+// Builds a Brownian path on equally spaced steps from the terminal value inwards, so the first
+// draws fix the large scale shape of the path. Paired with Sobol points this concentrates the
+// variance in the best distributed low dimensions
+pub struct BrownianBridge {
+    // Order in which the points of the path are filled in, with the points bracketing each one
+    bridge_index: Vec<usize>,
+    left_index: Vec<usize>,
+    right_index: Vec<usize>,
+    left_weight: Vec<f64>,
+    right_weight: Vec<f64>,
+    standard_deviation: Vec<f64>,
+}
+
+impl BrownianBridge {
+    pub fn new(steps: usize) -> BrownianBridge {
+        let mut bridge = BrownianBridge {
+            bridge_index: vec![0; steps],
+            left_index: vec![0; steps],
+            right_index: vec![0; steps],
+            left_weight: vec![0.0; steps],
+            right_weight: vec![0.0; steps],
+            standard_deviation: vec![0.0; steps],
+        };
+        if steps == 0 {
+            return bridge;
+        }
+        // Times are measured in steps, the point at index i is at time i + 1
+        let mut filled = vec![false; steps];
+        filled[steps - 1] = true;
+        bridge.bridge_index[0] = steps - 1;
+        bridge.standard_deviation[0] = (steps as f64).sqrt();
+        let mut j = 0;
+        for i in 1..steps {
+            while filled[j] {
+                j += 1;
+            }
+            let mut k = j;
+            while !filled[k] {
+                k += 1;
+            }
+            // Midpoint of the unfilled run j..k, bracketed by j - 1 (or the origin) and k
+            let l = j + (k - 1 - j) / 2;
+            filled[l] = true;
+            let span = (k + 1 - j) as f64;
+            bridge.bridge_index[i] = l;
+            bridge.left_index[i] = j;
+            bridge.right_index[i] = k;
+            bridge.left_weight[i] = (k - l) as f64 / span;
+            bridge.right_weight[i] = (l + 1 - j) as f64 / span;
+            bridge.standard_deviation[i] = ((l + 1 - j) as f64 * (k - l) as f64 / span).sqrt();
+            j = k + 1;
+            if j >= steps {
+                j = 0;
+            }
+        }
+        bridge
+    }
+
+    // Maps independent standard normal draws to the standardised increments of a Brownian path,
+    // themselves independent standard normals
+    pub fn increments(&self, draws: &[f64]) -> Vec<f64> {
+        let steps = draws.len();
+        let mut path = vec![0.0; steps];
+        for (i, draw) in draws.iter().enumerate() {
+            let l = self.bridge_index[i];
+            path[l] = if i == 0 {
+                self.standard_deviation[0] * draw
+            } else {
+                let left = match self.left_index[i] {
+                    0 => 0.0,
+                    j => self.left_weight[i] * path[j - 1],
+                };
+                left + self.right_weight[i] * path[self.right_index[i]]
+                    + self.standard_deviation[i] * draw
+            };
+        }
+        let mut previous = 0.0;
+        path.iter()
+            .map(|value| {
+                let increment = value - previous;
+                previous = *value;
+                increment
+            })
+            .collect()
+    }
+}
@@ -1,9 +1,18 @@
-use super::{MonteCarloInputs, MonteCarloParams};
+use super::aad::{european_greeks, MonteCarloGreeks};
+use super::draws::DrawGenerator;
+use super::estimate::SampleMean;
+use super::european::{european_expected_payoffs, ExpectedPayoffs};
+use super::greek_estimators::{greek_estimates_monte_carlo, MonteCarloGreekEstimates};
+use super::payoff::VanillaPayoff;
+use super::variance_reduction::vanilla_expected_payoff;
+use super::MonteCarloValuation;
+use super::{GreekEstimator, Measure, MonteCarloEstimate, MonteCarloInputs, MonteCarloParams};
 
 use crate::option::{Call, FinancialOption, Put};
-use crate::result::{PricerError, PricerResult};
+use crate::result::PricerResult;
 
 use crate::risk_factors::discount::{DiscountFactor, HistoricReturn};
+use crate::risk_factors::dividend::{DividendPayment, OutstandingDividend};
 use crate::risk_factors::price::{Price, PriceTick};
 use crate::risk_factors::volatility::{ImpliedVolatility, Volatility};
 use crate::risk_factors::RiskFactors;
@@ -11,16 +20,16 @@ use crate::risk_factors::RiskFactors;
 use crate::shock::{ApplyShock, Scenario};
 
 use chrono::{DateTime, Utc};
-use rand::Rng;
 use rayon::prelude::*;
-use statrs::distribution::Normal;
-use statrs::StatsError;
+use std::time::Instant;
 
-pub trait MonteCarlo: FinancialOption {
+pub trait MonteCarlo: FinancialOption + Sync {
+    fn is_call(&self) -> bool;
     fn get_monte_carlo_risk_factors(
         &self,
         price: f64,
         volatility: f64,
+        discount_factor: DiscountFactor,
         historic_return: f64,
     ) -> RiskFactors {
         RiskFactors {
@@ -32,29 +41,185 @@ pub trait MonteCarlo: FinancialOption {
                 self.symbol().clone(),
                 volatility,
             ))],
-            discount_factors: vec![DiscountFactor::HistoricReturn(HistoricReturn::new(
-                self.symbol().clone(),
-                historic_return,
-            ))],
+            discount_factors: vec![
+                discount_factor,
+                DiscountFactor::HistoricReturn(HistoricReturn::new(
+                    self.symbol().clone(),
+                    historic_return,
+                )),
+            ],
             dividend_sensitivities: vec![],
+            borrow_sensitivities: vec![],
         }
     }
-    fn value_monte_carlo_impl(
+    // Mean undiscounted payoff at expiry over paths simulated under `measure`
+    fn expected_payoff(
+        &self,
+        inputs: &MonteCarloInputs,
+        parameters: &MonteCarloParams,
+        measure: &Measure,
+    ) -> PricerResult<SampleMean> {
+        self.expected_payoffs(std::slice::from_ref(inputs), parameters, measure)
+            .map(|expectations| expectations.means[0])
+    }
+    // Mean undiscounted payoff under each scenario's inputs, all driven by the same draws
+    fn expected_payoffs(
+        &self,
+        scenarios: &[MonteCarloInputs],
+        parameters: &MonteCarloParams,
+        measure: &Measure,
+    ) -> PricerResult<ExpectedPayoffs> {
+        let control_means = scenarios
+            .iter()
+            .map(|inputs| {
+                if parameters.variance_reduction.control_variate {
+                    Ok(Some(vanilla_expected_payoff(
+                        self.is_call(),
+                        self.strike(),
+                        inputs,
+                        inputs.carry(measure)?,
+                    )?))
+                } else {
+                    Ok(None)
+                }
+            })
+            .collect::<PricerResult<Vec<Option<f64>>>>()?;
+        european_expected_payoffs(
+            scenarios,
+            parameters,
+            measure,
+            |price| self.value_if_executed(price).max(0.),
+            &control_means,
+        )
+    }
+    fn value_monte_carlo(
+        &self,
+        valuation_time: DateTime<Utc>,
+        risk_factors: RiskFactors,
+        shock_scenarios: Scenario,
+        parameters: MonteCarloParams,
+    ) -> PricerResult<MonteCarloEstimate> {
+        self.value_monte_carlo_scenarios(
+            valuation_time,
+            risk_factors,
+            std::slice::from_ref(&shock_scenarios),
+            parameters,
+        )
+        .map(|mut estimates| estimates.remove(0))
+    }
+    // Values under each scenario on one set of paths, so that differences between scenarios,
+    // such as bump and reprice greeks, are not swamped by sampling noise
+    fn value_monte_carlo_scenarios(
+        &self,
+        valuation_time: DateTime<Utc>,
+        risk_factors: RiskFactors,
+        shock_scenarios: &[Scenario],
+        parameters: MonteCarloParams,
+    ) -> PricerResult<Vec<MonteCarloEstimate>> {
+        let start = Instant::now();
+        let scenarios = shock_scenarios
+            .iter()
+            .map(|scenario| {
+                risk_factors
+                    .as_of(valuation_time)
+                    .and_then(TryInto::try_into)
+                    .map(|risk_factors| {
+                        let mut inputs =
+                            MonteCarloInputs::gather(self.expiry(), valuation_time, risk_factors);
+                        scenario.apply(&mut inputs);
+                        inputs
+                    })
+            })
+            .collect::<PricerResult<Vec<MonteCarloInputs>>>()?;
+        let expectations = self.expected_payoffs(&scenarios, &parameters, &Measure::RiskNeutral)?;
+        let runtime = start.elapsed();
+        Ok(scenarios
+            .iter()
+            .zip(expectations.means)
+            .map(|(inputs, expected_payoff)| {
+                MonteCarloEstimate::new(
+                    expected_payoff.scale(inputs.discount(1.0)),
+                    expectations.paths,
+                    expectations.target_met,
+                    runtime,
+                )
+            })
+            .collect())
+    }
+    fn greeks_monte_carlo_impl(
         &self,
         inputs: MonteCarloInputs,
         parameters: MonteCarloParams,
-    ) -> PricerResult<f64>;
-    fn value_monte_carlo(
+    ) -> PricerResult<MonteCarloGreeks> {
+        european_greeks(self.is_call(), self.strike(), &inputs, &parameters)
+    }
+    // Delta, vega, rho and theta differentiated through the same paths as the price
+    fn greeks_monte_carlo(
+        &self,
+        valuation_time: DateTime<Utc>,
+        risk_factors: RiskFactors,
+        shock_scenarios: Scenario,
+        parameters: MonteCarloParams,
+    ) -> PricerResult<MonteCarloGreeks> {
+        risk_factors
+            .as_of(valuation_time)
+            .and_then(TryInto::try_into)
+            .and_then(|risk_factors| {
+                let mut inputs =
+                    MonteCarloInputs::gather(self.expiry(), valuation_time, risk_factors);
+                shock_scenarios.apply(&mut inputs);
+                self.greeks_monte_carlo_impl(inputs, parameters)
+            })
+    }
+    // Greeks with standard errors from the pricing paths by the pathwise or likelihood ratio
+    // method, lighter than adjoint differentiation
+    fn greek_estimates_monte_carlo(
+        &self,
+        valuation_time: DateTime<Utc>,
+        risk_factors: RiskFactors,
+        shock_scenarios: Scenario,
+        parameters: MonteCarloParams,
+        estimator: GreekEstimator,
+    ) -> PricerResult<MonteCarloGreekEstimates> {
+        let payoff = VanillaPayoff {
+            is_call: self.is_call(),
+            strike: self.strike(),
+        };
+        greek_estimates_monte_carlo(
+            &payoff,
+            self.expiry(),
+            valuation_time,
+            risk_factors,
+            shock_scenarios,
+            parameters,
+            estimator,
+        )
+    }
+    // Prices under the risk-neutral measure and reports the real-world expectation alongside
+    fn value_monte_carlo_with_measures(
         &self,
         valuation_time: DateTime<Utc>,
         risk_factors: RiskFactors,
         shock_scenarios: Scenario,
         parameters: MonteCarloParams,
-    ) -> PricerResult<f64> {
-        risk_factors.try_into().and_then(|risk_factors| {
-            let mut inputs = MonteCarloInputs::gather(self.expiry(), valuation_time, risk_factors);
-            shock_scenarios.apply(&mut inputs);
-            self.value_monte_carlo_impl(inputs, parameters)
+    ) -> PricerResult<MonteCarloValuation> {
+        let mut inputs = risk_factors
+            .as_of(valuation_time)
+            .and_then(TryInto::try_into)
+            .map(|risk_factors| {
+                MonteCarloInputs::gather(self.expiry(), valuation_time, risk_factors)
+            })?;
+        shock_scenarios.apply(&mut inputs);
+        let risk_neutral = self
+            .expected_payoff(&inputs, &parameters, &Measure::RiskNeutral)?
+            .mean;
+        let real_world = self
+            .expected_payoff(&inputs, &parameters, &Measure::RealWorld)?
+            .mean;
+        Ok(MonteCarloValuation {
+            value: inputs.discount(risk_neutral),
+            real_world_expected_payoff: real_world,
+            expected_pnl: real_world - risk_neutral,
         })
     }
     fn generate_monte_carlo_paths(
@@ -62,82 +227,101 @@ pub trait MonteCarlo: FinancialOption {
         valuation_time: DateTime<Utc>,
         risk_factors: RiskFactors,
         parameters: MonteCarloParams,
+        measure: Measure,
     ) -> PricerResult<Vec<Vec<f64>>> {
-        risk_factors.try_into().and_then(|risk_factors| {
-            let inputs = MonteCarloInputs::gather(self.expiry(), valuation_time, risk_factors);
-            generate_monte_carlo_paths(&inputs, &parameters)
-        })
+        risk_factors
+            .as_of(valuation_time)
+            .and_then(TryInto::try_into)
+            .and_then(|risk_factors| {
+                let inputs = MonteCarloInputs::gather(self.expiry(), valuation_time, risk_factors);
+                generate_monte_carlo_paths(&inputs, &parameters, &measure)
+            })
     }
 }
 
-fn failed_to_create_gaussian_error(_: StatsError) -> PricerError {
-    PricerError {
-        code: 2,
-        message: String::from(
-            "Failed to construct Gaussian disrtribution for Black-Scholes pricing",
-        ),
+// Dividends going ex in each step, paid at the end of the step after the diffusion
+pub fn dividends_by_step(
+    dividends: Vec<OutstandingDividend>,
+    dt: f64,
+    steps: usize,
+) -> Vec<Vec<DividendPayment>> {
+    let mut by_step = vec![vec![]; steps];
+    for dividend in dividends {
+        let step = ((dividend.time / dt).ceil() as usize).clamp(1, steps) - 1;
+        by_step[step].push(dividend.payment);
     }
+    by_step
 }
 
-fn gaussian() -> PricerResult<Normal> {
-    Normal::new(0.0, 1.0).map_err(failed_to_create_gaussian_error)
+pub fn pay_dividends(price: f64, payments: &[DividendPayment]) -> f64 {
+    payments.iter().fold(price, |price, payment| match payment {
+        DividendPayment::Cash(amount) => (price - amount).max(0.0),
+        DividendPayment::Proportional(fraction) => price * (1.0 - fraction),
+    })
 }
 
-pub fn generate_monte_carlo_paths(
+pub struct SimulatedPaths {
+    // Price of the underlying at the end of each step, one row per path
+    pub paths: Vec<Vec<f64>>,
+    // Terminal price of each path's draws without discrete dividends, the underlying of the
+    // control variate
+    pub controls: Vec<f64>,
+}
+
+pub fn simulate_monte_carlo_paths(
     inputs: &MonteCarloInputs,
     parameters: &MonteCarloParams,
-) -> PricerResult<Vec<Vec<f64>>> {
+    measure: &Measure,
+) -> PricerResult<SimulatedPaths> {
     let dt = inputs.delta_t / parameters.steps as f64;
-    let nudt = (inputs.discount_rate() - 0.5 * inputs.volatility().powi(2)) * dt;
+    let carry = inputs.carry(measure)?;
+    let nudt = (carry - 0.5 * inputs.volatility().powi(2)) * dt;
     let sidt = inputs.volatility() * dt.sqrt();
+    let dividends = dividends_by_step(inputs.outstanding_dividends(), dt, parameters.steps);
 
-    let gaussian = gaussian()?;
-    Ok((0..parameters.repetitions)
+    let generator =
+        DrawGenerator::new(parameters, parameters.steps, &parameters.path_construction)?;
+    let normals: Vec<Vec<f64>> = (0..parameters.repetitions)
+        .into_par_iter()
+        .map(|path| generator.draws(path))
+        .collect();
+    let controls = normals
+        .iter()
+        .map(|draws| {
+            let total_draw: f64 = draws.iter().sum();
+            inputs.price() * (nudt * parameters.steps as f64 + sidt * total_draw).exp()
+        })
+        .collect();
+    let paths = normals
         .into_par_iter()
-        .map(|_| {
-            let mut rng = rand::thread_rng();
-            (0..parameters.steps)
-                .map(|_| rng.sample(gaussian))
-                .map(|sample| (nudt + sidt * sample).exp())
-                .scan(inputs.price(), |acc, v| {
-                    *acc = *acc * v;
-                    Some(*acc)
-                })
-                .collect()
+        .map(|mut draws| {
+            let mut price = inputs.price();
+            for (draw, payments) in draws.iter_mut().zip(dividends.iter()) {
+                price = pay_dividends(price * (nudt + sidt * *draw).exp(), payments);
+                *draw = price;
+            }
+            draws
         })
-        .collect())
+        .collect();
+    Ok(SimulatedPaths { paths, controls })
+}
+
+pub fn generate_monte_carlo_paths(
+    inputs: &MonteCarloInputs,
+    parameters: &MonteCarloParams,
+    measure: &Measure,
+) -> PricerResult<Vec<Vec<f64>>> {
+    simulate_monte_carlo_paths(inputs, parameters, measure).map(|simulated| simulated.paths)
 }
 
 impl MonteCarlo for Call {
-    fn value_monte_carlo_impl(
-        &self,
-        inputs: MonteCarloInputs,
-        parameters: MonteCarloParams,
-    ) -> PricerResult<f64> {
-        let paths = generate_monte_carlo_paths(&inputs, &parameters)?;
-        let payoffs = paths
-            .iter()
-            .flat_map(|path| path.last())
-            .map(|value| value - self.strike())
-            .map(|value| if value > 0. { value } else { 0. });
-        let expected_payoff = payoffs.sum::<f64>() / parameters.repetitions as f64;
-        Ok(inputs.discount(expected_payoff))
+    fn is_call(&self) -> bool {
+        true
     }
 }
 
 impl MonteCarlo for Put {
-    fn value_monte_carlo_impl(
-        &self,
-        inputs: MonteCarloInputs,
-        parameters: MonteCarloParams,
-    ) -> PricerResult<f64> {
-        let paths = generate_monte_carlo_paths(&inputs, &parameters)?;
-        let payoffs = paths
-            .iter()
-            .flat_map(|path| path.last())
-            .map(|value| self.strike() - value)
-            .map(|value| if value > 0. { value } else { 0. });
-        let expected_payoff = payoffs.sum::<f64>() / parameters.repetitions as f64;
-        Ok(inputs.discount(expected_payoff))
+    fn is_call(&self) -> bool {
+        false
     }
 }
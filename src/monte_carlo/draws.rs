@@ -0,0 +1,142 @@
+use super::brownian_bridge::BrownianBridge;
+use super::estimate::RunningMoments;
+use super::sobol::Sobol;
+use super::{MonteCarloParams, PathConstruction, Sampling};
+
+use crate::result::{PricerError, PricerResult};
+
+use rand::Rng;
+use rayon::prelude::*;
+use statrs::distribution::{ContinuousCDF, Normal};
+use statrs::StatsError;
+
+use std::ops::Range;
+
+// Paths folded together on one thread. Blocks are merged in path order, which keeps sums
+// independent of how rayon distributes the blocks
+const BLOCK_PATHS: usize = 1024;
+
+// Folds each block of the path indices `paths` in parallel and merges the results in path order.
+// None when there are no paths
+pub fn fold_blocks<T, F, M>(paths: Range<usize>, fold: F, merge: M) -> Option<T>
+where
+    T: Send,
+    F: Fn(Range<usize>) -> T + Sync,
+    M: Fn(T, T) -> T,
+{
+    let first = paths.start;
+    let blocks: Vec<T> = (0..paths.len().div_ceil(BLOCK_PATHS))
+        .into_par_iter()
+        .map(|block| {
+            let start = first + block * BLOCK_PATHS;
+            fold(start..paths.end.min(start + BLOCK_PATHS))
+        })
+        .collect();
+    blocks.into_iter().reduce(merge)
+}
+
+fn failed_to_create_gaussian_error(_: StatsError) -> PricerError {
+    PricerError {
+        code: 2,
+        message: String::from("Failed to construct Gaussian distribution for Monte Carlo draws"),
+    }
+}
+
+// Standard normal draws of any path on demand. A path's draws depend only on the parameters and
+// its index, so they can be regenerated instead of stored
+pub struct DrawGenerator<'a> {
+    parameters: &'a MonteCarloParams,
+    dimensions: usize,
+    gaussian: Normal,
+    sobol: Option<Sobol>,
+    bridge: Option<BrownianBridge>,
+    // Mean and standard deviation of each dimension across all paths when moment matching
+    moments: Option<Vec<(f64, f64)>>,
+}
+
+impl<'a> DrawGenerator<'a> {
+    pub fn new(
+        parameters: &'a MonteCarloParams,
+        dimensions: usize,
+        path_construction: &PathConstruction,
+    ) -> PricerResult<DrawGenerator<'a>> {
+        let mut generator = DrawGenerator {
+            parameters,
+            dimensions,
+            gaussian: Normal::new(0.0, 1.0).map_err(failed_to_create_gaussian_error)?,
+            sobol: match parameters.sampling {
+                Sampling::PseudoRandom => None,
+                Sampling::Sobol => Some(Sobol::new(dimensions, parameters.seed)),
+            },
+            bridge: match path_construction {
+                PathConstruction::Incremental => None,
+                PathConstruction::BrownianBridge => Some(BrownianBridge::new(dimensions)),
+            },
+            moments: None,
+        };
+        if parameters.variance_reduction.moment_matching {
+            generator.moments = Some(generator.draw_moments());
+        }
+        Ok(generator)
+    }
+
+    // Antithetic pairs share a stream or point with opposite signs
+    fn raw_draws(&self, path: usize) -> Vec<f64> {
+        let (stream, sign) = match (self.parameters.variance_reduction.antithetic, path % 2) {
+            (true, 0) => (path / 2, 1.0),
+            (true, _) => (path / 2, -1.0),
+            (false, _) => (path, 1.0),
+        };
+        let draws: Vec<f64> = match &self.sobol {
+            Some(sobol) => sobol
+                .point(stream)
+                .map(|uniform| sign * self.gaussian.inverse_cdf(uniform))
+                .collect(),
+            None => {
+                let mut rng = self.parameters.path_rng(stream);
+                (0..self.dimensions)
+                    .map(|_| sign * rng.sample(self.gaussian))
+                    .collect()
+            }
+        };
+        match &self.bridge {
+            Some(bridge) => bridge.increments(&draws),
+            None => draws,
+        }
+    }
+
+    // A first pass over every path for the statistics moment matching standardises with
+    fn draw_moments(&self) -> Vec<(f64, f64)> {
+        let moments = fold_blocks(
+            0..self.parameters.repetitions,
+            |paths| {
+                let mut moments = vec![RunningMoments::default(); self.dimensions];
+                for path in paths {
+                    for (moment, draw) in moments.iter_mut().zip(self.raw_draws(path)) {
+                        moment.push(draw);
+                    }
+                }
+                moments
+            },
+            |lhs, rhs| lhs.iter().zip(&rhs).map(|(l, r)| l.merge(r)).collect(),
+        );
+        moments
+            .unwrap_or_default()
+            .iter()
+            .map(|moment| match moment.sample_variance() {
+                Some(variance) if variance > 0.0 => (moment.mean, variance.sqrt()),
+                _ => (0.0, 1.0),
+            })
+            .collect()
+    }
+
+    pub fn draws(&self, path: usize) -> Vec<f64> {
+        let mut draws = self.raw_draws(path);
+        if let Some(moments) = &self.moments {
+            for (draw, (mean, standard_deviation)) in draws.iter_mut().zip(moments) {
+                *draw = (*draw - mean) / standard_deviation;
+            }
+        }
+        draws
+    }
+}
@@ -0,0 +1,233 @@
+use super::Tolerance;
+
+use pyo3::prelude::*;
+
+use std::time::Duration;
+
+// Two-sided 95% quantile of the standard normal
+const CONFIDENCE_QUANTILE: f64 = 1.959_963_984_540_054;
+
+// Mean of independent samples together with the standard error of that mean
+#[derive(Clone, Copy, Debug)]
+pub struct SampleMean {
+    pub mean: f64,
+    pub standard_error: f64,
+}
+
+impl SampleMean {
+    pub fn of(samples: &[f64]) -> SampleMean {
+        let n = samples.len() as f64;
+        let mean = samples.iter().sum::<f64>() / n;
+        let standard_error = if samples.len() < 2 {
+            f64::NAN
+        } else {
+            let variance = samples.iter().map(|s| (s - mean).powi(2)).sum::<f64>() / (n - 1.0);
+            (variance / n).sqrt()
+        };
+        SampleMean {
+            mean,
+            standard_error,
+        }
+    }
+    // A value known without sampling error
+    pub fn exact(value: f64) -> SampleMean {
+        SampleMean {
+            mean: value,
+            standard_error: 0.0,
+        }
+    }
+    pub fn scale(self, factor: f64) -> SampleMean {
+        SampleMean {
+            mean: self.mean * factor,
+            standard_error: self.standard_error * factor.abs(),
+        }
+    }
+    // False while too few samples have been seen to estimate the error
+    pub fn is_within(&self, tolerance: &Tolerance) -> bool {
+        match tolerance {
+            Tolerance::StandardError(limit) => self.standard_error <= *limit,
+            Tolerance::Relative(fraction) => self.standard_error <= fraction * self.mean.abs(),
+        }
+    }
+}
+
+// Mean and sum of squared deviations of a stream of samples, updated and merged with Chan's
+// pairwise formulas so blocks of paths can be folded separately
+#[derive(Clone, Copy, Debug, Default)]
+pub struct RunningMoments {
+    count: f64,
+    pub mean: f64,
+    squared_deviations: f64,
+}
+
+impl RunningMoments {
+    pub fn push(&mut self, sample: f64) {
+        self.count += 1.0;
+        let deviation = sample - self.mean;
+        self.mean += deviation / self.count;
+        self.squared_deviations += deviation * (sample - self.mean);
+    }
+    pub fn merge(&self, other: &RunningMoments) -> RunningMoments {
+        let count = self.count + other.count;
+        if count == 0.0 {
+            return *self;
+        }
+        let deviation = other.mean - self.mean;
+        RunningMoments {
+            count,
+            mean: self.mean + deviation * other.count / count,
+            squared_deviations: self.squared_deviations
+                + other.squared_deviations
+                + deviation.powi(2) * self.count * other.count / count,
+        }
+    }
+    pub fn sample_variance(&self) -> Option<f64> {
+        (self.count > 1.0).then(|| self.squared_deviations / (self.count - 1.0))
+    }
+}
+
+// Streaming statistics of per-path payoffs and their control variates. Samples arrive in units,
+// a single path or an antithetic pair, whose averages are independent of each other
+#[derive(Clone, Copy, Debug, Default)]
+pub struct PayoffMoments {
+    paths: f64,
+    payoff_sum: f64,
+    control_sum: f64,
+    units: f64,
+    payoff_mean: f64,
+    control_mean: f64,
+    payoff_deviations: f64,
+    control_deviations: f64,
+    co_deviations: f64,
+}
+
+impl PayoffMoments {
+    // Adds a unit of (payoff, control) samples
+    pub fn push(&mut self, unit: &[(f64, f64)]) {
+        let size = unit.len() as f64;
+        let (payoff_sum, control_sum) =
+            unit.iter().fold((0.0, 0.0), |(p, c), (payoff, control)| {
+                (p + payoff, c + control)
+            });
+        *self = self.merge(&PayoffMoments {
+            paths: size,
+            payoff_sum,
+            control_sum,
+            units: 1.0,
+            payoff_mean: payoff_sum / size,
+            control_mean: control_sum / size,
+            ..Default::default()
+        });
+    }
+    pub fn merge(&self, other: &PayoffMoments) -> PayoffMoments {
+        if self.units == 0.0 {
+            return *other;
+        }
+        let units = self.units + other.units;
+        let payoff_deviation = other.payoff_mean - self.payoff_mean;
+        let control_deviation = other.control_mean - self.control_mean;
+        let weight = self.units * other.units / units;
+        PayoffMoments {
+            paths: self.paths + other.paths,
+            payoff_sum: self.payoff_sum + other.payoff_sum,
+            control_sum: self.control_sum + other.control_sum,
+            units,
+            payoff_mean: self.payoff_mean + payoff_deviation * other.units / units,
+            control_mean: self.control_mean + control_deviation * other.units / units,
+            payoff_deviations: self.payoff_deviations
+                + other.payoff_deviations
+                + payoff_deviation.powi(2) * weight,
+            control_deviations: self.control_deviations
+                + other.control_deviations
+                + control_deviation.powi(2) * weight,
+            co_deviations: self.co_deviations
+                + other.co_deviations
+                + payoff_deviation * control_deviation * weight,
+        }
+    }
+    fn standard_error(&self, squared_deviations: f64) -> f64 {
+        if self.units < 2.0 {
+            return f64::NAN;
+        }
+        (squared_deviations.max(0.0) / (self.units - 1.0) / self.units).sqrt()
+    }
+    pub fn sample_mean(&self) -> SampleMean {
+        SampleMean {
+            mean: self.payoff_sum / self.paths,
+            standard_error: self.standard_error(self.payoff_deviations),
+        }
+    }
+    // Payoff mean corrected by the optimal multiple of the control's error against its known
+    // expectation `control_mean`
+    pub fn control_variate(&self, control_mean: f64) -> SampleMean {
+        let beta = if self.control_deviations > 0.0 {
+            self.co_deviations / self.control_deviations
+        } else {
+            0.0
+        };
+        let residual_deviations = self.payoff_deviations - 2.0 * beta * self.co_deviations
+            + beta.powi(2) * self.control_deviations;
+        SampleMean {
+            mean: (self.payoff_sum - beta * (self.control_sum - control_mean * self.paths))
+                / self.paths,
+            standard_error: self.standard_error(residual_deviations),
+        }
+    }
+}
+
+// A Monte Carlo valuation with the statistics needed to judge whether it is signal or noise. The
+// standard error treats paths as independent, which overstates the error of moment matched and
+// Sobol paths
+#[pyclass(frozen)]
+#[derive(Clone, Debug)]
+pub struct MonteCarloEstimate {
+    pub value: f64,
+    pub standard_error: f64,
+    // 95% interval around the value from the normal approximation
+    pub confidence_interval: (f64, f64),
+    pub paths: usize,
+    pub runtime: Duration,
+    // Whether the valuation reached its target accuracy, None when it had none
+    pub target_met: Option<bool>,
+}
+
+impl MonteCarloEstimate {
+    pub fn new(
+        sample: SampleMean,
+        paths: usize,
+        target_met: Option<bool>,
+        runtime: Duration,
+    ) -> MonteCarloEstimate {
+        let half_width = CONFIDENCE_QUANTILE * sample.standard_error;
+        MonteCarloEstimate {
+            value: sample.mean,
+            standard_error: sample.standard_error,
+            confidence_interval: (sample.mean - half_width, sample.mean + half_width),
+            paths,
+            runtime,
+            target_met,
+        }
+    }
+}
+
+#[pymethods]
+impl MonteCarloEstimate {
+    fn value(&self) -> f64 {
+        self.value
+    }
+    fn standard_error(&self) -> f64 {
+        self.standard_error
+    }
+    fn confidence_interval(&self) -> (f64, f64) {
+        self.confidence_interval
+    }
+    fn paths(&self) -> usize {
+        self.paths
+    }
+    fn runtime_seconds(&self) -> f64 {
+        self.runtime.as_secs_f64()
+    }
+    fn target_met(&self) -> Option<bool> {
+        self.target_met
+    }
+}
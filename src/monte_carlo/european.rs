@@ -0,0 +1,236 @@
+use super::conventional::pay_dividends;
+use super::draws::{fold_blocks, DrawGenerator};
+use super::estimate::{PayoffMoments, SampleMean};
+use super::{Measure, MonteCarloInputs, MonteCarloParams, PathConstruction};
+
+use crate::result::{PricerError, PricerResult};
+use crate::risk_factors::dividend::DividendPayment;
+
+use std::time::Instant;
+
+// The dates a European payoff needs the underlying on: each ex-dividend date, where the price
+// drops, and `monitoring` evenly spaced dates ending at expiry. Between them the lognormal
+// transition is exact however long the interval
+pub struct ObservationDates {
+    pub intervals: Vec<f64>,
+    pub dividends: Vec<Vec<DividendPayment>>,
+}
+
+pub fn observation_dates(inputs: &MonteCarloInputs, monitoring: usize) -> ObservationDates {
+    let monitoring = monitoring.max(1);
+    let mut events: Vec<(f64, Option<DividendPayment>)> = inputs
+        .outstanding_dividends()
+        .into_iter()
+        .map(|dividend| {
+            (
+                dividend.time.clamp(0.0, inputs.delta_t),
+                Some(dividend.payment),
+            )
+        })
+        .collect();
+    events.extend(
+        (1..monitoring).map(|date| (inputs.delta_t * date as f64 / monitoring as f64, None)),
+    );
+    events.push((inputs.delta_t, None));
+    events.sort_by(|lhs, rhs| lhs.0.total_cmp(&rhs.0));
+    let mut times: Vec<f64> = vec![];
+    let mut dividends: Vec<Vec<DividendPayment>> = vec![];
+    for (time, payment) in events {
+        if times.last() != Some(&time) {
+            times.push(time);
+            dividends.push(vec![]);
+        }
+        if let (Some(payment), Some(payments)) = (payment, dividends.last_mut()) {
+            payments.push(payment);
+        }
+    }
+    let intervals = times
+        .iter()
+        .scan(0.0, |previous, time| {
+            let interval = time - *previous;
+            *previous = *time;
+            Some(interval)
+        })
+        .collect();
+    ObservationDates {
+        intervals,
+        dividends,
+    }
+}
+
+fn moment_matching_with_target_err() -> PricerError {
+    PricerError::new(
+        "Moment matching needs every path up front and cannot stop at a target accuracy".into(),
+        14,
+    )
+}
+
+// The exact lognormal steps between one set of inputs' observation dates
+struct ObservationSteps {
+    price: f64,
+    drifts: Vec<f64>,
+    diffusions: Vec<f64>,
+    dividends: Vec<Vec<DividendPayment>>,
+    total_drift: f64,
+}
+
+impl ObservationSteps {
+    fn new(inputs: &MonteCarloInputs, measure: &Measure) -> PricerResult<ObservationSteps> {
+        let carry = inputs.carry(measure)?;
+        let volatility = inputs.volatility();
+        let dates = observation_dates(inputs, 1);
+        let drifts: Vec<f64> = dates
+            .intervals
+            .iter()
+            .map(|interval| (carry - 0.5 * volatility.powi(2)) * interval)
+            .collect();
+        let diffusions: Vec<f64> = dates
+            .intervals
+            .iter()
+            .map(|interval| volatility * interval.sqrt())
+            .collect();
+        let total_drift: f64 = drifts.iter().sum();
+        Ok(ObservationSteps {
+            price: inputs.price(),
+            drifts,
+            diffusions,
+            dividends: dates.dividends,
+            total_drift,
+        })
+    }
+    // Price at expiry and dividend-free price at expiry of the path driven by `draws`
+    fn simulate(&self, draws: &[f64]) -> (f64, f64) {
+        let mut price = self.price;
+        let mut total_diffusion = 0.0;
+        for (((draw, drift), diffusion), payments) in draws
+            .iter()
+            .zip(&self.drifts)
+            .zip(&self.diffusions)
+            .zip(&self.dividends)
+        {
+            price = pay_dividends(price * (drift + diffusion * draw).exp(), payments);
+            total_diffusion += diffusion * draw;
+        }
+        (
+            price,
+            self.price * (self.total_drift + total_diffusion).exp(),
+        )
+    }
+}
+
+// Expected payoffs of every scenario from one set of paths
+pub struct ExpectedPayoffs {
+    pub means: Vec<SampleMean>,
+    pub paths: usize,
+    // Whether every scenario's discounted value reached the target accuracy, None without one
+    pub target_met: Option<bool>,
+}
+
+// Mean undiscounted `payoff` of the price at expiry under each scenario's inputs, simulating only
+// the observation dates and folding payoffs into running moments so memory does not grow with the
+// number of paths. Each path's draws are generated once and drive every scenario, so differences
+// between scenarios are free of most of the sampling noise, and a scenario with fewer observation
+// dates uses the leading draws. With a control mean the dividend-free terminal price's payoff
+// serves as a control variate. With a target accuracy the paths are simulated in batches that
+// continue the path indices, so stopping early gives the leading paths of a longer run
+pub fn european_expected_payoffs<P>(
+    scenarios: &[MonteCarloInputs],
+    parameters: &MonteCarloParams,
+    measure: &Measure,
+    payoff: P,
+    control_means: &[Option<f64>],
+) -> PricerResult<ExpectedPayoffs>
+where
+    P: Fn(f64) -> f64 + Sync,
+{
+    if parameters.target.is_some() && parameters.variance_reduction.moment_matching {
+        return Err(moment_matching_with_target_err());
+    }
+    let start = Instant::now();
+    let steps = scenarios
+        .iter()
+        .map(|inputs| ObservationSteps::new(inputs, measure))
+        .collect::<PricerResult<Vec<ObservationSteps>>>()?;
+    let dimensions = steps
+        .iter()
+        .map(|steps| steps.drifts.len())
+        .max()
+        .unwrap_or(0);
+
+    // The few, unevenly spaced dates are driven incrementally whatever the path construction
+    let generator = DrawGenerator::new(parameters, dimensions, &PathConstruction::Incremental)?;
+    let simulate = |path: usize| -> Vec<(f64, f64)> {
+        let draws = generator.draws(path);
+        steps
+            .iter()
+            .map(|steps| {
+                let (price, control) = steps.simulate(&draws);
+                (payoff(price), payoff(control))
+            })
+            .collect()
+    };
+    let unit = if parameters.variance_reduction.antithetic {
+        2
+    } else {
+        1
+    };
+    let means = |moments: &[PayoffMoments]| -> Vec<SampleMean> {
+        moments
+            .iter()
+            .zip(control_means)
+            .map(|(moments, control_mean)| match control_mean {
+                Some(control_mean) => moments.control_variate(*control_mean),
+                None => moments.sample_mean(),
+            })
+            .collect()
+    };
+    let mut moments = vec![PayoffMoments::default(); steps.len()];
+    let mut paths = 0;
+    loop {
+        let batch = paths..parameters.repetitions.min(paths + parameters.batch_paths());
+        paths = batch.end;
+        if let Some(batch_moments) = fold_blocks(
+            batch,
+            |block| {
+                let mut moments = vec![PayoffMoments::default(); steps.len()];
+                let end = block.end;
+                for first in block.step_by(unit) {
+                    let samples: Vec<Vec<(f64, f64)>> =
+                        (first..end.min(first + unit)).map(simulate).collect();
+                    for (scenario, moments) in moments.iter_mut().enumerate() {
+                        let unit: Vec<(f64, f64)> =
+                            samples.iter().map(|path| path[scenario]).collect();
+                        moments.push(&unit);
+                    }
+                }
+                moments
+            },
+            |lhs, rhs| lhs.iter().zip(&rhs).map(|(l, r)| l.merge(r)).collect(),
+        ) {
+            moments = moments
+                .iter()
+                .zip(&batch_moments)
+                .map(|(l, r)| l.merge(r))
+                .collect();
+        }
+        let Some(target) = &parameters.target else {
+            return Ok(ExpectedPayoffs {
+                means: means(&moments),
+                paths,
+                target_met: None,
+            });
+        };
+        let estimates = means(&moments);
+        let target_met = estimates.iter().zip(scenarios).all(|(mean, inputs)| {
+            mean.scale(inputs.discount(1.0))
+                .is_within(&target.tolerance)
+        });
+        if target_met || paths >= parameters.repetitions || start.elapsed() >= target.time_budget {
+            return Ok(ExpectedPayoffs {
+                means: estimates,
+                paths,
+                target_met: Some(target_met),
+            });
+        }
+    }
+}
@@ -0,0 +1,253 @@
+use super::draws::{fold_blocks, DrawGenerator};
+use super::estimate::{PayoffMoments, SampleMean};
+use super::european::observation_dates;
+use super::payoff::ObservedPayoff;
+use super::{GreekEstimator, Measure, MonteCarloInputs, MonteCarloParams, PathConstruction};
+
+use crate::result::{PricerError, PricerResult};
+use crate::risk_factors::dividend::DividendPayment;
+use crate::risk_factors::RiskFactors;
+use crate::shock::{ApplyShock, Scenario};
+use crate::utils::date::DAYS_IN_YEAR;
+
+use chrono::{DateTime, Utc};
+
+// Sources of equations:
+//  - Glasserman (2003), Monte Carlo methods in financial engineering, sections 7.2 and 7.3
+
+// The inputs greeks are taken against, in the order of the arrays below: price, volatility,
+// rate and time to expiry
+const INPUTS: usize = 4;
+
+// Greeks estimated from the pricing paths, each with its standard error, in the units of the
+// analytical greeks: vega and rho per percentage point and theta per calendar day
+#[derive(Debug)]
+pub struct MonteCarloGreekEstimates {
+    pub value: SampleMean,
+    pub delta: SampleMean,
+    pub vega: SampleMean,
+    pub rho: SampleMean,
+    pub theta: SampleMean,
+}
+
+fn pathwise_requires_lipschitz_payoff_error() -> PricerError {
+    PricerError::new(
+        "Pathwise greeks require a Lipschitz payoff, use likelihood ratio greeks instead".into(),
+        15,
+    )
+}
+
+// A path's discounted payoff and its estimates of the derivative against each input
+struct PathGreeks {
+    value: f64,
+    derivatives: [f64; INPUTS],
+}
+
+// Streaming statistics of the value and of each derivative over paths
+#[derive(Clone, Copy, Default)]
+struct GreekMoments([PayoffMoments; INPUTS + 1]);
+
+impl GreekMoments {
+    fn push(&mut self, unit: &[PathGreeks]) {
+        for (index, moments) in self.0.iter_mut().enumerate() {
+            let samples: Vec<(f64, f64)> = unit
+                .iter()
+                .map(|path| match index {
+                    0 => (path.value, 0.0),
+                    _ => (path.derivatives[index - 1], 0.0),
+                })
+                .collect();
+            moments.push(&samples);
+        }
+    }
+    fn merge(&self, other: &GreekMoments) -> GreekMoments {
+        let mut merged = *self;
+        for (moments, other) in merged.0.iter_mut().zip(&other.0) {
+            *moments = moments.merge(other);
+        }
+        merged
+    }
+    fn estimates(&self) -> MonteCarloGreekEstimates {
+        let [value, delta, vega, rho, expiry] = self.0.map(|moments| moments.sample_mean());
+        MonteCarloGreekEstimates {
+            value,
+            delta,
+            vega: vega.scale(0.01),
+            rho: rho.scale(0.01),
+            theta: expiry.scale(-1.0 / DAYS_IN_YEAR as f64),
+        }
+    }
+}
+
+// Pays the dividends going ex on a date, carrying the price's derivatives through them. A cash
+// dividend larger than the price leaves nothing to differentiate
+fn pay_dividends_with_tangents(
+    price: f64,
+    tangents: &mut [f64; INPUTS],
+    payments: &[DividendPayment],
+) -> f64 {
+    payments.iter().fold(price, |price, payment| match payment {
+        DividendPayment::Cash(amount) if price > *amount => price - amount,
+        DividendPayment::Cash(_) => {
+            *tangents = [0.0; INPUTS];
+            0.0
+        }
+        DividendPayment::Proportional(fraction) => {
+            for tangent in tangents.iter_mut() {
+                *tangent *= 1.0 - fraction;
+            }
+            price * (1.0 - fraction)
+        }
+    })
+}
+
+// Simulates `payoff` exactly on its observation dates under the risk-neutral measure and
+// estimates delta, vega, rho and theta on the same paths as the value. Ex-dates and monitoring
+// dates are held relative to expiry, so the time to expiry only moves the first interval, and the
+// rate and volatility to expiry are held as time passes
+pub fn estimate_greeks<P>(
+    payoff: &P,
+    inputs: &MonteCarloInputs,
+    parameters: &MonteCarloParams,
+    estimator: &GreekEstimator,
+) -> PricerResult<MonteCarloGreekEstimates>
+where
+    P: ObservedPayoff + ?Sized,
+{
+    if matches!(estimator, GreekEstimator::Pathwise) && !payoff.is_lipschitz() {
+        return Err(pathwise_requires_lipschitz_payoff_error());
+    }
+    let price = inputs.price();
+    let volatility = inputs.volatility();
+    let rate = inputs.discount_rate();
+    let carry = inputs.carry(&Measure::RiskNeutral)?;
+    let dates = observation_dates(inputs, payoff.monitoring_dates());
+    let drift_rate = carry - 0.5 * volatility.powi(2);
+    let discount = (-rate * inputs.delta_t).exp();
+    // Derivative of the discount factor against each input
+    let discount_derivatives = [0.0, 0.0, -inputs.delta_t * discount, -rate * discount];
+
+    let generator = DrawGenerator::new(
+        parameters,
+        dates.intervals.len(),
+        &PathConstruction::Incremental,
+    )?;
+    let simulate = |path: usize| {
+        let draws = generator.draws(path);
+        let mut observed = price;
+        // Derivatives of the observed price against each input, and the score of the path's
+        // density against each input
+        let mut tangents = [1.0, 0.0, 0.0, 0.0];
+        let mut scores = [0.0; INPUTS];
+        let mut prices = Vec::with_capacity(draws.len());
+        let mut price_tangents = Vec::with_capacity(draws.len());
+        for (index, ((draw, interval), payments)) in draws
+            .iter()
+            .zip(&dates.intervals)
+            .zip(&dates.dividends)
+            .enumerate()
+        {
+            if *interval > 0.0 {
+                let root = interval.sqrt();
+                let growth = (drift_rate * interval + volatility * root * draw).exp();
+                observed *= growth;
+                let first = if index == 0 { 1.0 } else { 0.0 };
+                let log_growth_tangents = [
+                    0.0,
+                    root * draw - volatility * interval,
+                    *interval,
+                    first * (drift_rate + 0.5 * volatility * draw / root),
+                ];
+                for (tangent, log_tangent) in tangents.iter_mut().zip(log_growth_tangents) {
+                    *tangent = *tangent * growth + observed * log_tangent;
+                }
+                let step_scores = [
+                    first * draw / (price * volatility * root),
+                    (draw.powi(2) - 1.0) / volatility - draw * root,
+                    draw * root / volatility,
+                    first
+                        * ((draw.powi(2) - 1.0) / (2.0 * interval)
+                            + draw * drift_rate / (volatility * root)),
+                ];
+                for (score, step_score) in scores.iter_mut().zip(step_scores) {
+                    *score += step_score;
+                }
+            }
+            observed = pay_dividends_with_tangents(observed, &mut tangents, payments);
+            prices.push(observed);
+            price_tangents.push(tangents);
+        }
+        let payoff_value = payoff.payoff(&prices);
+        let derivatives = match estimator {
+            GreekEstimator::Pathwise => {
+                let payoff_derivatives = payoff.price_derivatives(&prices);
+                let mut derivatives = [0.0; INPUTS];
+                for (payoff_derivative, tangents) in payoff_derivatives.iter().zip(&price_tangents)
+                {
+                    for (derivative, tangent) in derivatives.iter_mut().zip(tangents) {
+                        *derivative += payoff_derivative * tangent;
+                    }
+                }
+                derivatives
+            }
+            GreekEstimator::LikelihoodRatio => scores.map(|score| payoff_value * score),
+        };
+        let mut discounted = [0.0; INPUTS];
+        for ((discounted, derivative), discount_derivative) in discounted
+            .iter_mut()
+            .zip(derivatives)
+            .zip(discount_derivatives)
+        {
+            *discounted = discount * derivative + payoff_value * discount_derivative;
+        }
+        PathGreeks {
+            value: discount * payoff_value,
+            derivatives: discounted,
+        }
+    };
+    let unit = if parameters.variance_reduction.antithetic {
+        2
+    } else {
+        1
+    };
+    let moments = fold_blocks(
+        0..parameters.repetitions,
+        |paths| {
+            let mut moments = GreekMoments::default();
+            let end = paths.end;
+            for first in paths.step_by(unit) {
+                let samples: Vec<PathGreeks> =
+                    (first..end.min(first + unit)).map(simulate).collect();
+                moments.push(&samples);
+            }
+            moments
+        },
+        |lhs, rhs| lhs.merge(&rhs),
+    )
+    .unwrap_or_default();
+    Ok(moments.estimates())
+}
+
+// Greeks of `payoff` on an underlying expiring at `expiry`, for payoffs that are not options of
+// their own such as digitals and barriers
+pub fn greek_estimates_monte_carlo<P>(
+    payoff: &P,
+    expiry: DateTime<Utc>,
+    valuation_time: DateTime<Utc>,
+    risk_factors: RiskFactors,
+    shock_scenarios: Scenario,
+    parameters: MonteCarloParams,
+    estimator: GreekEstimator,
+) -> PricerResult<MonteCarloGreekEstimates>
+where
+    P: ObservedPayoff + ?Sized,
+{
+    risk_factors
+        .as_of(valuation_time)
+        .and_then(TryInto::try_into)
+        .and_then(|risk_factors| {
+            let mut inputs = MonteCarloInputs::gather(expiry, valuation_time, risk_factors);
+            shock_scenarios.apply(&mut inputs);
+            estimate_greeks(payoff, &inputs, &parameters, &estimator)
+        })
+}
@@ -1,6 +1,10 @@
-use super::MonteCarloRiskFactors;
+use super::{Measure, MonteCarloRiskFactors};
 
-use crate::shock::{ApplyShock, Shock, Scenario};
+use crate::result::{PricerError, PricerResult};
+
+use crate::risk_factors::dividend::OutstandingDividend;
+
+use crate::shock::{ApplyShock, Scenario, Shock};
 
 use crate::utils::date::get_duration_in_years;
 
@@ -8,6 +12,7 @@ use chrono::{DateTime, Utc};
 
 pub struct MonteCarloInputs {
     pub delta_t: f64,
+    expiry: DateTime<Utc>,
     risk_factors: MonteCarloRiskFactors,
 }
 
@@ -20,29 +25,56 @@ impl MonteCarloInputs {
         let delta_t = get_duration_in_years(valuation_time, expiry);
         MonteCarloInputs {
             delta_t,
+            expiry,
             risk_factors,
         }
     }
 
     pub fn discount_rate(&self) -> f64 {
-        self.risk_factors.discount_rate()
+        self.risk_factors.discount_rate(self.delta_t)
     }
     pub fn price(&self) -> f64 {
         self.risk_factors.price()
     }
     pub fn volatility(&self) -> f64 {
-        self.risk_factors.volatility()
+        self.risk_factors.volatility(self.delta_t)
+    }
+    pub fn dividend_yield(&self) -> f64 {
+        self.risk_factors.dividend_yield()
+    }
+    pub fn borrow_rate(&self) -> f64 {
+        self.risk_factors.borrow_rate()
+    }
+    pub fn outstanding_dividends(&self) -> Vec<OutstandingDividend> {
+        self.risk_factors
+            .outstanding_dividends(self.expiry, self.delta_t)
+    }
+    pub fn real_world_return(&self) -> Option<f64> {
+        self.risk_factors.real_world_return()
+    }
+    // Growth rate of the underlying between dividends under the simulation measure, the stock
+    // borrow fee only reduces the forward under the risk-neutral measure
+    pub fn carry(&self, measure: &Measure) -> PricerResult<f64> {
+        match measure {
+            Measure::RiskNeutral => {
+                Ok(self.discount_rate() - self.dividend_yield() - self.borrow_rate())
+            }
+            Measure::RealWorld => self
+                .real_world_return()
+                .map(|historic_return| historic_return - self.dividend_yield())
+                .ok_or_else(|| {
+                    PricerError::new(
+                        "Real-world simulation requires a historic return risk factor".into(),
+                        1,
+                    )
+                }),
+        }
     }
     pub fn discount(&self, value: f64) -> f64 {
         value * (-self.delta_t * self.discount_rate()).exp()
     }
 }
 
-pub struct MonteCarloParams {
-    pub steps: u64,
-    pub repetitions: u64,
-}
-
 impl ApplyShock<MonteCarloInputs> for Shock {
     fn apply(&self, applicant: &mut MonteCarloInputs) {
         match self {
@@ -1,9 +1,19 @@
 mod conventional;
 mod aad_ls;
 
+mod aad;
+mod brownian_bridge;
+mod draws;
+mod estimate;
+mod european;
+mod greek_estimators;
 mod inputs;
 mod params;
+mod payoff;
+mod regression;
 mod risk_factors;
+mod sobol;
+mod variance_reduction;
 
 #[cfg(test)]
 mod test;
@@ -13,4 +23,20 @@ use risk_factors::MonteCarloRiskFactors;
 
 pub use aad_ls::LongstaffSchwartzMonteCarlo;
 pub use conventional::MonteCarlo;
-pub use params::MonteCarloParams;
+pub use estimate::MonteCarloEstimate;
+pub use greek_estimators::{greek_estimates_monte_carlo, MonteCarloGreekEstimates};
+pub use params::{
+    GreekEstimator, LongstaffSchwartzParams, Measure, MonteCarloParams, PathConstruction,
+    RegressionBasis, Sampling, TargetAccuracy, Tolerance,
+};
+pub use payoff::{Barrier, BarrierPayoff, DigitalPayoff};
+
+pub struct MonteCarloValuation {
+    // Risk-neutral price, discounted on the risk-free curve
+    pub value: f64,
+    // Expected payoff at expiry with the underlying drifting at its historic return
+    pub real_world_expected_payoff: f64,
+    // Expected profit at expiry from buying at `value` funded at the risk-free rate, the
+    // difference between the real-world and risk-neutral expected payoffs
+    pub expected_pnl: f64,
+}
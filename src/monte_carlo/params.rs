@@ -1,4 +1,179 @@
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+
+use std::time::Duration;
+
+// Mixed into the seed for a set of paths independent of the pricing paths
+const INDEPENDENT_SEED: u64 = 0x9e37_79b9_7f4a_7c15;
+
+// Techniques that lower the variance of the estimate for a given number of paths, any
+// combination can be switched on
+#[derive(Clone, Debug, Default)]
+pub struct VarianceReduction {
+    // Paths come in pairs driven by opposite draws
+    pub antithetic: bool,
+    // The payoff on the same draws without discrete dividends, priced by Black-Scholes, absorbs
+    // most of the sampling error of a vanilla payoff
+    pub control_variate: bool,
+    // Draws at each step are standardised across paths to exactly zero mean and unit variance
+    pub moment_matching: bool,
+}
+
+// Source of the uniform draws behind each path
+#[derive(Clone, Debug)]
+pub enum Sampling {
+    PseudoRandom,
+    // Owen scrambled Sobol points, one dimension per step, scrambled by the seed
+    Sobol,
+}
+
+// How the Gaussian draws of a path are turned into its Brownian increments
+#[derive(Clone, Debug)]
+pub enum PathConstruction {
+    // Each draw drives the next step
+    Incremental,
+    // The first draw sets the terminal value and later draws fill in ever finer detail
+    BrownianBridge,
+}
+
+// Functions of the moneyness, the price over the strike, that the Longstaff-Schwartz regression
+// fits continuation values with
+#[derive(Clone, Debug)]
+pub enum RegressionBasis {
+    Monomial,
+    // Laguerre polynomials damped by exp(-x / 2), as in Longstaff and Schwartz
+    Laguerre,
+    // Probabilists' Hermite polynomials
+    Hermite,
+}
+
+#[derive(Clone, Debug)]
+pub struct LongstaffSchwartzParams {
+    pub basis: RegressionBasis,
+    // Highest order of the basis functions, the regression fits one more coefficient than this
+    pub degree: usize,
+    // Penalty on the squared size of the regression coefficients, zero for least squares
+    pub ridge: f64,
+    // Fits the exercise rule on an independent set of paths and applies it to the pricing paths,
+    // removing the upward bias of a rule that has seen the paths it is valued on
+    pub out_of_sample: bool,
+}
+
+impl Default for LongstaffSchwartzParams {
+    fn default() -> Self {
+        LongstaffSchwartzParams {
+            basis: RegressionBasis::Monomial,
+            degree: 2,
+            ridge: 0.0,
+            out_of_sample: false,
+        }
+    }
+}
+
+// Accuracy a valuation stops at, judged on the discounted value
+#[derive(Clone, Debug)]
+pub enum Tolerance {
+    StandardError(f64),
+    // Standard error as a fraction of the value
+    Relative(f64),
+}
+
+// Simulates batches of paths until the estimate is within the tolerance, the time budget runs out
+// or `repetitions` paths have been simulated. The default budget never runs out, so the paths
+// simulated depend only on the parameters. A run cut short by a finite budget depends on the
+// speed of the machine and is not reproducible. Moment matching standardises the draws over all
+// `repetitions` paths before the first batch, so it cannot be combined with a target
+#[derive(Clone, Debug)]
+pub struct TargetAccuracy {
+    pub tolerance: Tolerance,
+    pub time_budget: Duration,
+    // Paths between checks of the running estimate, rounded up to keep antithetic pairs together
+    pub batch_paths: usize,
+}
+
+impl Default for TargetAccuracy {
+    fn default() -> Self {
+        TargetAccuracy {
+            tolerance: Tolerance::Relative(0.001),
+            time_budget: Duration::MAX,
+            batch_paths: 16384,
+        }
+    }
+}
+
+#[derive(Clone)]
 pub struct MonteCarloParams {
+    // Time steps of full paths. European valuations simulate only the dates their payoff observes
     pub steps: usize,
+    // Paths simulated, or the most simulated when there is a target accuracy
     pub repetitions: usize,
+    // Equal seeds give bit-identical paths, whatever the number of threads they are generated on
+    pub seed: u64,
+    pub variance_reduction: VarianceReduction,
+    pub sampling: Sampling,
+    pub path_construction: PathConstruction,
+    pub longstaff_schwartz: LongstaffSchwartzParams,
+    // Stops streamed European valuations early once accurate enough. Valuations that hold every
+    // path, such as Longstaff-Schwartz, always simulate `repetitions` paths
+    pub target: Option<TargetAccuracy>,
+}
+
+impl Default for MonteCarloParams {
+    fn default() -> Self {
+        MonteCarloParams {
+            steps: 10000,
+            repetitions: 1000,
+            seed: 0,
+            variance_reduction: VarianceReduction::default(),
+            sampling: Sampling::PseudoRandom,
+            path_construction: PathConstruction::Incremental,
+            longstaff_schwartz: LongstaffSchwartzParams::default(),
+            target: None,
+        }
+    }
+}
+
+impl MonteCarloParams {
+    // Each path draws from its own ChaCha stream keyed by the seed and numbered by the path, so a
+    // path's random numbers do not depend on which thread generates it or in what order
+    pub fn path_rng(&self, path: usize) -> ChaCha8Rng {
+        let mut rng = ChaCha8Rng::seed_from_u64(self.seed);
+        rng.set_stream(path as u64);
+        rng
+    }
+    // Paths simulated before the running estimate is next checked
+    pub fn batch_paths(&self) -> usize {
+        match &self.target {
+            Some(target) if self.variance_reduction.antithetic => {
+                target.batch_paths.max(1).next_multiple_of(2)
+            }
+            Some(target) => target.batch_paths.max(1),
+            None => self.repetitions,
+        }
+    }
+    // The same simulation on paths independent of these ones
+    pub fn independent(&self) -> MonteCarloParams {
+        MonteCarloParams {
+            seed: self.seed ^ INDEPENDENT_SEED,
+            ..self.clone()
+        }
+    }
+}
+
+// Probability measure the paths are simulated under. Prices are always risk-neutral, real-world
+// paths drift at the historic return and serve scenario analysis and expected P&L
+#[derive(Clone, Debug)]
+pub enum Measure {
+    RiskNeutral,
+    RealWorld,
+}
+
+// How greeks are estimated from the pricing paths without re-simulating
+#[derive(Clone, Debug)]
+pub enum GreekEstimator {
+    // Differentiates each path's payoff along the path, the payoff must be Lipschitz
+    Pathwise,
+    // Weights each payoff by the derivative of the log density of its draws, any payoff works
+    // but the estimate is noisier
+    LikelihoodRatio,
 }
@@ -0,0 +1,118 @@
+// A payoff of the underlying observed on each ex-dividend date and on evenly spaced monitoring
+// dates ending at expiry. `prices` holds the price after each observation, expiry last
+pub trait ObservedPayoff: Sync {
+    // Number of evenly spaced dates ending at expiry the payoff looks at
+    fn monitoring_dates(&self) -> usize {
+        1
+    }
+    fn payoff(&self, prices: &[f64]) -> f64;
+    // Whether the payoff is continuous with bounded derivatives, as pathwise greeks require
+    fn is_lipschitz(&self) -> bool;
+    // Derivative of a Lipschitz payoff with respect to each observed price
+    fn price_derivatives(&self, prices: &[f64]) -> Vec<f64> {
+        vec![0.0; prices.len()]
+    }
+}
+
+fn terminal(prices: &[f64]) -> f64 {
+    prices.last().copied().unwrap_or(0.0)
+}
+
+fn in_the_money(is_call: bool, strike: f64, price: f64) -> bool {
+    if is_call {
+        price > strike
+    } else {
+        price < strike
+    }
+}
+
+fn vanilla(is_call: bool, strike: f64, price: f64) -> f64 {
+    if is_call {
+        (price - strike).max(0.0)
+    } else {
+        (strike - price).max(0.0)
+    }
+}
+
+pub struct VanillaPayoff {
+    pub is_call: bool,
+    pub strike: f64,
+}
+
+impl ObservedPayoff for VanillaPayoff {
+    fn payoff(&self, prices: &[f64]) -> f64 {
+        vanilla(self.is_call, self.strike, terminal(prices))
+    }
+    fn is_lipschitz(&self) -> bool {
+        true
+    }
+    fn price_derivatives(&self, prices: &[f64]) -> Vec<f64> {
+        let mut derivatives = vec![0.0; prices.len()];
+        if let Some(last) = derivatives.last_mut() {
+            if in_the_money(self.is_call, self.strike, terminal(prices)) {
+                *last = if self.is_call { 1.0 } else { -1.0 };
+            }
+        }
+        derivatives
+    }
+}
+
+// Cash-or-nothing digital paying one unit when it expires in the money
+pub struct DigitalPayoff {
+    pub is_call: bool,
+    pub strike: f64,
+}
+
+impl ObservedPayoff for DigitalPayoff {
+    fn payoff(&self, prices: &[f64]) -> f64 {
+        if in_the_money(self.is_call, self.strike, terminal(prices)) {
+            1.0
+        } else {
+            0.0
+        }
+    }
+    fn is_lipschitz(&self) -> bool {
+        false
+    }
+}
+
+#[derive(Clone, Debug)]
+pub enum Barrier {
+    UpAndOut(f64),
+    UpAndIn(f64),
+    DownAndOut(f64),
+    DownAndIn(f64),
+}
+
+// Vanilla payoff knocked out, or only knocked in, by the price crossing a barrier on any
+// observation date
+pub struct BarrierPayoff {
+    pub is_call: bool,
+    pub strike: f64,
+    pub barrier: Barrier,
+    pub monitoring_dates: usize,
+}
+
+impl ObservedPayoff for BarrierPayoff {
+    fn monitoring_dates(&self) -> usize {
+        self.monitoring_dates
+    }
+    fn payoff(&self, prices: &[f64]) -> f64 {
+        let highest = prices.iter().copied().fold(f64::MIN, f64::max);
+        let lowest = prices.iter().copied().fold(f64::MAX, f64::min);
+        let alive = match self.barrier {
+            Barrier::UpAndOut(level) => highest < level,
+            Barrier::UpAndIn(level) => highest >= level,
+            Barrier::DownAndOut(level) => lowest > level,
+            Barrier::DownAndIn(level) => lowest <= level,
+        };
+        if alive {
+            vanilla(self.is_call, self.strike, terminal(prices))
+        } else {
+            0.0
+        }
+    }
+    fn is_lipschitz(&self) -> bool {
+        false
+    }
+}
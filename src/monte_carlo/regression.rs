@@ -0,0 +1,139 @@
+use super::{LongstaffSchwartzParams, RegressionBasis};
+
+use crate::utils::linear_algebra::least_squares;
+
+// Sources of equations:
+//  - Longstaff & Schwartz (2001), Valuing American options by simulation: a simple least-squares
+//    approach
+//  - Glasserman (2003), Monte Carlo methods in financial engineering, section 8.6
+
+impl RegressionBasis {
+    // Basis functions of orders zero to `degree` at the moneyness `x`
+    pub fn evaluate(&self, degree: usize, x: f64) -> Vec<f64> {
+        let mut values = Vec::with_capacity(degree + 1);
+        match self {
+            RegressionBasis::Monomial => {
+                (0..=degree).fold(1.0, |power, _| {
+                    values.push(power);
+                    power * x
+                });
+            }
+            RegressionBasis::Laguerre => {
+                let damping = (-0.5 * x).exp();
+                let (mut previous, mut current) = (0.0, 1.0);
+                for order in 0..=degree {
+                    values.push(damping * current);
+                    let next = ((2 * order + 1) as f64 - x) * current - order as f64 * previous;
+                    (previous, current) = (current, next / (order + 1) as f64);
+                }
+            }
+            RegressionBasis::Hermite => {
+                let (mut previous, mut current) = (0.0, 1.0);
+                for order in 0..=degree {
+                    values.push(current);
+                    (previous, current) = (current, x * current - order as f64 * previous);
+                }
+            }
+        }
+        values
+    }
+}
+
+// Exercise rule from regressing discounted future cash flows on the price at each exercise date.
+// Paths hold the price at the valuation time first and then the price after each step
+pub struct ExercisePolicy<'a> {
+    parameters: &'a LongstaffSchwartzParams,
+    strike: f64,
+    // Continuation value coefficients for each date, None where too few paths were in the money
+    // to fit them and the option is held
+    coefficients: Vec<Option<Vec<f64>>>,
+}
+
+impl<'a> ExercisePolicy<'a> {
+    // Fits the rule by backward induction over `paths`, regressing only on paths in the money
+    // where the exercise decision is made
+    pub fn fit<F>(
+        parameters: &'a LongstaffSchwartzParams,
+        strike: f64,
+        paths: &[Vec<f64>],
+        exercise_value: F,
+        step_discount: f64,
+    ) -> ExercisePolicy<'a>
+    where
+        F: Fn(f64) -> f64,
+    {
+        let expiry = paths.first().map_or(0, |path| path.len().saturating_sub(1));
+        let mut policy = ExercisePolicy {
+            parameters,
+            strike,
+            coefficients: vec![None; expiry + 1],
+        };
+        let mut exercise_dates = vec![expiry; paths.len()];
+        for date in (1..expiry).rev() {
+            let in_the_money: Vec<usize> = (0..paths.len())
+                .filter(|path| exercise_value(paths[*path][date]) > 0.0)
+                .collect();
+            let rows: Vec<Vec<f64>> = in_the_money
+                .iter()
+                .map(|path| policy.basis(paths[*path][date]))
+                .collect();
+            let targets: Vec<f64> = in_the_money
+                .iter()
+                .map(|path| {
+                    let exercise_date = exercise_dates[*path];
+                    exercise_value(paths[*path][exercise_date])
+                        * step_discount.powi((exercise_date - date) as i32)
+                })
+                .collect();
+            policy.coefficients[date] = least_squares(&rows, &targets, parameters.ridge);
+            for path in in_the_money {
+                if policy.exercises(date, paths[path][date], &exercise_value) {
+                    exercise_dates[path] = date;
+                }
+            }
+        }
+        policy
+    }
+
+    fn basis(&self, price: f64) -> Vec<f64> {
+        self.parameters
+            .basis
+            .evaluate(self.parameters.degree, price / self.strike)
+    }
+
+    fn exercises<F>(&self, date: usize, price: f64, exercise_value: &F) -> bool
+    where
+        F: Fn(f64) -> f64,
+    {
+        let value = exercise_value(price);
+        match &self.coefficients[date] {
+            Some(coefficients) if value > 0.0 => {
+                let continuation: f64 = self
+                    .basis(price)
+                    .iter()
+                    .zip(coefficients)
+                    .map(|(basis, coefficient)| basis * coefficient)
+                    .sum();
+                value > continuation
+            }
+            _ => false,
+        }
+    }
+
+    // First date after the valuation time the rule exercises each path at, expiry when it never
+    // exercises early
+    pub fn exercise_dates<F>(&self, paths: &[Vec<f64>], exercise_value: F) -> Vec<usize>
+    where
+        F: Fn(f64) -> f64,
+    {
+        let expiry = self.coefficients.len() - 1;
+        paths
+            .iter()
+            .map(|path| {
+                (1..expiry)
+                    .find(|date| self.exercises(*date, path[*date], &exercise_value))
+                    .unwrap_or(expiry)
+            })
+            .collect()
+    }
+}
@@ -1,27 +1,59 @@
 use crate::result::{PricerError, PricerResult};
 
+use crate::risk_factors::borrow::BorrowRate;
 use crate::risk_factors::discount::{DiscountFactor, DiscountRf};
+use crate::risk_factors::dividend::{Dividend, OutstandingDividend};
 use crate::risk_factors::price::{Price, PriceRf};
 use crate::risk_factors::volatility::{Volatility, VolatilityRf};
 use crate::risk_factors::RiskFactors;
 
 use crate::shock::{ApplyShock, Shock};
 
+use chrono::{DateTime, Utc};
+
 pub struct MonteCarloRiskFactors {
     price_risk_factor: Price,
     volatility_risk_factor: Volatility,
     discount_factor: DiscountFactor,
+    real_world_drift: Option<DiscountFactor>,
+    dividend_factor: Option<Dividend>,
+    borrow_factor: Option<BorrowRate>,
 }
 
 impl MonteCarloRiskFactors {
-    pub fn discount_rate(&self) -> f64 {
-        self.discount_factor.rate()
+    pub fn discount_rate(&self, delta_t: f64) -> f64 {
+        self.discount_factor.zero_rate(delta_t)
+    }
+    pub fn real_world_return(&self) -> Option<f64> {
+        self.real_world_drift.as_ref().map(|drift| drift.rate())
     }
     pub fn price(&self) -> f64 {
         self.price_risk_factor.price()
     }
-    pub fn volatility(&self) -> f64 {
-        self.volatility_risk_factor.volatility()
+    pub fn volatility(&self, delta_t: f64) -> f64 {
+        self.volatility_risk_factor.volatility_to(delta_t)
+    }
+    pub fn dividend_yield(&self) -> f64 {
+        self.dividend_factor
+            .as_ref()
+            .map(|dividend| dividend.annualised_rate())
+            .unwrap_or(0.0)
+    }
+    pub fn borrow_rate(&self) -> f64 {
+        self.borrow_factor
+            .as_ref()
+            .map(|borrow| borrow.rate())
+            .unwrap_or(0.0)
+    }
+    pub fn outstanding_dividends(
+        &self,
+        expiry: DateTime<Utc>,
+        delta_t: f64,
+    ) -> Vec<OutstandingDividend> {
+        self.dividend_factor
+            .as_ref()
+            .map(|dividend| dividend.outstanding(expiry, delta_t))
+            .unwrap_or_default()
     }
 }
 
@@ -38,17 +70,42 @@ fn get_first_and_ensure_one<RF>(mut risk_factors: Vec<RF>) -> PricerResult<RF> {
     Ok(risk_factors.remove(0))
 }
 
+fn get_at_most_one<RF>(risk_factors: Vec<RF>) -> PricerResult<Option<RF>> {
+    if risk_factors.is_empty() {
+        return Ok(None);
+    }
+    get_first_and_ensure_one(risk_factors).map(Some)
+}
+
+// Historic returns give the real-world drift, every other discount factor is risk-free
+fn is_historic(discount_factor: &DiscountFactor) -> bool {
+    matches!(
+        discount_factor,
+        DiscountFactor::HistoricReturn(_) | DiscountFactor::HistoricReturnSeries(_)
+    )
+}
+
 impl TryFrom<RiskFactors> for MonteCarloRiskFactors {
     type Error = PricerError;
     fn try_from(risk_factors: RiskFactors) -> PricerResult<Self> {
         let price_risk_factor = get_first_and_ensure_one(risk_factors.price_sensitivities)?;
         let volatility_risk_factor =
             get_first_and_ensure_one(risk_factors.volatility_sensitivities)?;
-        let discount_factor = get_first_and_ensure_one(risk_factors.discount_factors)?;
+        let (historic, risk_free): (Vec<_>, Vec<_>) = risk_factors
+            .discount_factors
+            .into_iter()
+            .partition(is_historic);
+        let discount_factor = get_first_and_ensure_one(risk_free)?;
+        let real_world_drift = get_at_most_one(historic)?;
+        let dividend_factor = get_at_most_one(risk_factors.dividend_sensitivities)?;
+        let borrow_factor = get_at_most_one(risk_factors.borrow_sensitivities)?;
         Ok(MonteCarloRiskFactors {
             price_risk_factor,
             volatility_risk_factor,
             discount_factor,
+            real_world_drift,
+            dividend_factor,
+            borrow_factor,
         })
     }
 }
@@ -57,8 +114,19 @@ impl ApplyShock<MonteCarloRiskFactors> for Shock {
     fn apply(&self, applicant: &mut MonteCarloRiskFactors) {
         match self {
             Shock::InterestRateShock(shock) => shock.apply(&mut applicant.discount_factor),
+            Shock::KeyRateShock(shock) => shock.apply(&mut applicant.discount_factor),
             Shock::PriceShock(shock) => shock.apply(&mut applicant.price_risk_factor),
             Shock::VolatilityShock(shock) => shock.apply(&mut applicant.volatility_risk_factor),
+            Shock::DividendShock(shock) => {
+                if let Some(dividend) = applicant.dividend_factor.as_mut() {
+                    shock.apply(dividend)
+                }
+            }
+            Shock::BorrowShock(shock) => {
+                if let Some(borrow) = applicant.borrow_factor.as_mut() {
+                    shock.apply(borrow)
+                }
+            }
             _ => (),
         }
     }
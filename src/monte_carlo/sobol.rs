@@ -0,0 +1,184 @@
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+// Sources of equations:
+//  - Bratley & Fox (1988), Algorithm 659: implementing Sobol's quasirandom sequence generator
+//  - Owen (1995), Randomly permuted (t,m,s)-nets and (t,s)-sequences
+//  - Burley (2020), Practical hash-based Owen scrambling
+
+const BITS: usize = 32;
+
+// Seed of the free initial direction numbers. Any odd m_k < 2^k gives a valid Sobol sequence,
+// they are drawn once from a fixed generator rather than read from an optimised table, and
+// never depend on the scrambling seed
+const DIRECTION_SEED: u64 = 0x50b0_1000;
+
+// Carry-less product of polynomials over GF(2) reduced modulo `modulus` of degree `degree`
+fn multiply_mod(lhs: u64, rhs: u64, modulus: u64, degree: u32) -> u64 {
+    let mut product = 0;
+    let mut shifted = lhs;
+    for bit in 0..degree {
+        if rhs >> bit & 1 == 1 {
+            product ^= shifted;
+        }
+        shifted <<= 1;
+        if shifted >> degree & 1 == 1 {
+            shifted ^= modulus;
+        }
+    }
+    product
+}
+
+// x^exponent modulo `modulus`
+fn power_of_x_mod(exponent: u64, modulus: u64, degree: u32) -> u64 {
+    let mut result = 1;
+    let mut base = if degree == 1 { 1 } else { 2 };
+    let mut exponent = exponent;
+    while exponent > 0 {
+        if exponent & 1 == 1 {
+            result = multiply_mod(result, base, modulus, degree);
+        }
+        base = multiply_mod(base, base, modulus, degree);
+        exponent >>= 1;
+    }
+    result
+}
+
+fn prime_factors(mut n: u64) -> Vec<u64> {
+    let mut factors = vec![];
+    let mut divisor = 2;
+    while divisor * divisor <= n {
+        if n.is_multiple_of(divisor) {
+            factors.push(divisor);
+            while n.is_multiple_of(divisor) {
+                n /= divisor;
+            }
+        }
+        divisor += 1;
+    }
+    if n > 1 {
+        factors.push(n);
+    }
+    factors
+}
+
+// Primitive polynomials over GF(2) of each degree in turn until `count` are found, as bit masks
+// with the leading and constant terms set. A polynomial is primitive when x has order 2^d - 1
+fn primitive_polynomials(count: usize) -> Vec<(u64, u32)> {
+    let mut polynomials = vec![];
+    let mut degree = 1;
+    while polynomials.len() < count && (degree as usize) < BITS {
+        let order = (1u64 << degree) - 1;
+        let factors = prime_factors(order);
+        for interior in 0..1u64 << (degree - 1) {
+            if polynomials.len() == count {
+                break;
+            }
+            let polynomial = 1 << degree | interior << 1 | 1;
+            let is_primitive = power_of_x_mod(order, polynomial, degree) == 1
+                && factors
+                    .iter()
+                    .all(|factor| power_of_x_mod(order / factor, polynomial, degree) != 1);
+            if is_primitive {
+                polynomials.push((polynomial, degree));
+            }
+        }
+        degree += 1;
+    }
+    polynomials
+}
+
+fn direction_numbers(polynomial: u64, degree: u32, rng: &mut ChaCha8Rng) -> [u32; BITS] {
+    let degree = degree as usize;
+    let mut m = [0u64; BITS];
+    for (k, value) in m.iter_mut().enumerate().take(degree) {
+        // m_(k+1) is odd and below 2^(k+1)
+        *value = if k == 0 {
+            1
+        } else {
+            rng.gen_range(0..1u64 << k) << 1 | 1
+        };
+    }
+    for k in degree..BITS {
+        let mut value = m[k - degree] ^ m[k - degree] << degree;
+        for i in 1..degree {
+            if polynomial >> (degree - i) & 1 == 1 {
+                value ^= m[k - i] << i;
+            }
+        }
+        m[k] = value;
+    }
+    let mut directions = [0u32; BITS];
+    for (k, direction) in directions.iter_mut().enumerate() {
+        *direction = (m[k] << (BITS - 1 - k)) as u32;
+    }
+    directions
+}
+
+fn mix(mut value: u64) -> u64 {
+    value = value.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    value = (value ^ value >> 30).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    value = (value ^ value >> 27).wrapping_mul(0x94d0_49bb_1331_11eb);
+    value ^ value >> 31
+}
+
+// Nested uniform scramble: each bit is flipped by a hash of the bits above it, so every
+// elementary interval is permuted independently of the others
+fn owen_scramble(value: u32, seed: u32) -> u32 {
+    let mut x = value.reverse_bits();
+    x ^= x.wrapping_mul(0x3d20_adea);
+    x = x.wrapping_add(seed);
+    x = x.wrapping_mul(seed >> 16 | 1);
+    x ^= x.wrapping_mul(0x0552_6c56);
+    x ^= x.wrapping_mul(0x53a2_2864);
+    x.reverse_bits()
+}
+
+// Owen scrambled Sobol points in `dimensions` dimensions. Independent scrambling seeds give
+// independent unbiased estimates, whose spread measures the integration error
+pub struct Sobol {
+    directions: Vec<[u32; BITS]>,
+    scrambles: Vec<u32>,
+}
+
+impl Sobol {
+    pub fn new(dimensions: usize, seed: u64) -> Sobol {
+        let mut rng = ChaCha8Rng::seed_from_u64(DIRECTION_SEED);
+        let mut van_der_corput = [0u32; BITS];
+        for (k, direction) in van_der_corput.iter_mut().enumerate() {
+            *direction = 1 << (BITS - 1 - k);
+        }
+        let directions = std::iter::once(van_der_corput)
+            .chain(
+                primitive_polynomials(dimensions.saturating_sub(1))
+                    .into_iter()
+                    .map(|(polynomial, degree)| direction_numbers(polynomial, degree, &mut rng)),
+            )
+            .take(dimensions)
+            .collect();
+        let scrambles = (0..dimensions as u64)
+            .map(|dimension| mix(mix(seed) ^ dimension) as u32)
+            .collect();
+        Sobol {
+            directions,
+            scrambles,
+        }
+    }
+
+    // Coordinates of point `index` in (0, 1), built directly from its Gray code so points can be
+    // generated in any order
+    pub fn point(&self, index: usize) -> impl Iterator<Item = f64> + '_ {
+        let gray = index ^ index >> 1;
+        self.directions
+            .iter()
+            .zip(&self.scrambles)
+            .map(move |(directions, scramble)| {
+                let value = directions
+                    .iter()
+                    .enumerate()
+                    .filter(|(bit, _)| gray >> bit & 1 == 1)
+                    .fold(0u32, |value, (_, direction)| value ^ direction);
+                (owen_scramble(value, *scramble) as f64 + 0.5) / (1u64 << BITS) as f64
+            })
+    }
+}
@@ -1,13 +1,35 @@
-use super::{LongstaffSchwartzMonteCarlo, MonteCarlo, MonteCarloParams};
-use crate::black_scholes::BlackScholes;
+use super::brownian_bridge::BrownianBridge;
+use super::params::VarianceReduction;
+use super::sobol::Sobol;
+use super::{greek_estimates_monte_carlo, Barrier, BarrierPayoff, DigitalPayoff, GreekEstimator};
+use super::{LongstaffSchwartzMonteCarlo, LongstaffSchwartzParams, Measure, MonteCarlo};
+use super::{MonteCarloEstimate, MonteCarloParams, RegressionBasis, TargetAccuracy, Tolerance};
+use super::{MonteCarloGreekEstimates, PathConstruction, Sampling};
+use crate::black_scholes::{BlackScholes, BlackScholesGreeks};
+use crate::option::{ExerciseStyle, FinancialOption};
 
 use crate::result::PricerResult;
-use crate::utils::test_utils::{get_test_call, get_test_ls_put, get_test_put, is_close};
+use crate::risk_factors::borrow::BorrowRate;
+use crate::risk_factors::discount::{rfr_discount, DiscountFactor, HistoricReturn};
+use crate::risk_factors::dividend::DividendPayment;
+use crate::risk_factors::RiskFactors;
+use crate::shock::{absolute_shock, absolute_time_shock, price_shock, time_shock, ShockDirection};
+use crate::symbol::Symbol;
+use crate::tree::{BinomialTree, TreeParams};
+use crate::utils::date::get_duration_in_years;
+use crate::{Priceable, Pricer};
+
+use crate::utils::test_utils::{
+    get_test_call, get_test_dividend_schedule, get_test_ls_put, get_test_put, is_close,
+};
+use chrono::Duration;
 
 fn monte_carlo_params() -> MonteCarloParams {
     MonteCarloParams {
         steps: 10000,
         repetitions: 1000,
+        seed: 7,
+        ..Default::default()
     }
 }
 
@@ -16,8 +38,9 @@ fn half_year_call_monte_carlo_near_black_scholes() -> PricerResult<()> {
     let (call, valuation_time, risk_factors) = get_test_call();
     let black_scholes_valuation =
         call.value_black_scholes(valuation_time, risk_factors.clone(), vec![])?;
-    let monte_carlo_valuation =
-        call.value_monte_carlo(valuation_time, risk_factors, vec![], monte_carlo_params())?;
+    let monte_carlo_valuation = call
+        .value_monte_carlo(valuation_time, risk_factors, vec![], monte_carlo_params())?
+        .value;
     assert!(
         is_close(black_scholes_valuation, monte_carlo_valuation, 0.15),
         "Monte Carlo valuation ({}) differs from Black-Scholes ({}) by more than 15%",
@@ -32,8 +55,46 @@ fn half_year_put_monte_carlo_near_black_scholes() -> PricerResult<()> {
     let (put, valuation_time, risk_factors) = get_test_put();
     let black_scholes_valuation =
         put.value_black_scholes(valuation_time, risk_factors.clone(), vec![])?;
-    let monte_carlo_valuation =
-        put.value_monte_carlo(valuation_time, risk_factors, vec![], monte_carlo_params())?;
+    let monte_carlo_valuation = put
+        .value_monte_carlo(valuation_time, risk_factors, vec![], monte_carlo_params())?
+        .value;
+    assert!(
+        is_close(black_scholes_valuation, monte_carlo_valuation, 0.15),
+        "Monte Carlo valuation ({}) differs from Black-Scholes ({}) by more than 15%",
+        monte_carlo_valuation,
+        black_scholes_valuation
+    );
+    Ok(())
+}
+
+#[test]
+fn call_with_cash_dividend_monte_carlo_near_black_scholes() -> PricerResult<()> {
+    let (call, valuation_time, mut risk_factors) = get_test_call();
+    risk_factors.dividend_sensitivities =
+        vec![get_test_dividend_schedule(DividendPayment::Cash(1.0))];
+    let black_scholes_valuation =
+        call.value_black_scholes(valuation_time, risk_factors.clone(), vec![])?;
+    let monte_carlo_valuation = call
+        .value_monte_carlo(valuation_time, risk_factors, vec![], monte_carlo_params())?
+        .value;
+    assert!(
+        is_close(black_scholes_valuation, monte_carlo_valuation, 0.15),
+        "Monte Carlo valuation ({}) differs from Black-Scholes ({}) by more than 15%",
+        monte_carlo_valuation,
+        black_scholes_valuation
+    );
+    Ok(())
+}
+
+#[test]
+fn put_with_borrow_fee_monte_carlo_near_black_scholes() -> PricerResult<()> {
+    let (put, valuation_time, mut risk_factors) = get_test_put();
+    risk_factors.borrow_sensitivities = vec![BorrowRate::new("AAPL".into(), 0.1)];
+    let black_scholes_valuation =
+        put.value_black_scholes(valuation_time, risk_factors.clone(), vec![])?;
+    let monte_carlo_valuation = put
+        .value_monte_carlo(valuation_time, risk_factors, vec![], monte_carlo_params())?
+        .value;
     assert!(
         is_close(black_scholes_valuation, monte_carlo_valuation, 0.15),
         "Monte Carlo valuation ({}) differs from Black-Scholes ({}) by more than 15%",
@@ -46,12 +107,23 @@ fn half_year_put_monte_carlo_near_black_scholes() -> PricerResult<()> {
 #[test]
 fn direct_mcls_test() -> PricerResult<()> {
     let (put, valuation_time, risk_factors) = get_test_ls_put();
-    let ls_valuation = put.value_monte_carlo_ls(
-        valuation_time,
-        risk_factors.clone(),
-        vec![],
-        monte_carlo_params(),
-    )?;
+    let ls_valuation = put
+        .value_monte_carlo_ls(
+            valuation_time,
+            risk_factors.clone(),
+            vec![],
+            // Fifty exercise dates, with the rule fitted on independent paths so it is not overfit
+            MonteCarloParams {
+                steps: 50,
+                repetitions: 4000,
+                longstaff_schwartz: LongstaffSchwartzParams {
+                    out_of_sample: true,
+                    ..Default::default()
+                },
+                ..monte_carlo_params()
+            },
+        )?
+        .value;
     let expected = 3.28;
     assert!(
         is_close(ls_valuation, expected, 0.1),
@@ -61,3 +133,1201 @@ fn direct_mcls_test() -> PricerResult<()> {
     );
     Ok(())
 }
+
+fn with_historic_return(risk_factors: &mut RiskFactors, historic_return: f64) {
+    risk_factors
+        .discount_factors
+        .push(DiscountFactor::HistoricReturn(HistoricReturn::new(
+            "AAPL".into(),
+            historic_return,
+        )));
+}
+
+#[test]
+fn historic_return_does_not_move_the_risk_neutral_price() -> PricerResult<()> {
+    let (call, valuation_time, mut risk_factors) = get_test_call();
+    let black_scholes_valuation =
+        call.value_black_scholes(valuation_time, risk_factors.clone(), vec![])?;
+    with_historic_return(&mut risk_factors, 0.4);
+    let valuation = call.value_monte_carlo_with_measures(
+        valuation_time,
+        risk_factors,
+        vec![],
+        monte_carlo_params(),
+    )?;
+    assert!(
+        is_close(black_scholes_valuation, valuation.value, 0.15),
+        "Monte Carlo valuation ({}) differs from Black-Scholes ({}) by more than 15%",
+        valuation.value,
+        black_scholes_valuation
+    );
+    assert!(valuation.real_world_expected_payoff > valuation.value);
+    assert!(valuation.expected_pnl > 0.0);
+    Ok(())
+}
+
+#[test]
+fn put_loses_under_a_real_world_drift_above_the_risk_free_rate() -> PricerResult<()> {
+    let (put, valuation_time, mut risk_factors) = get_test_put();
+    with_historic_return(&mut risk_factors, 0.4);
+    let valuation = put.value_monte_carlo_with_measures(
+        valuation_time,
+        risk_factors,
+        vec![],
+        monte_carlo_params(),
+    )?;
+    assert!(valuation.expected_pnl < 0.0);
+    Ok(())
+}
+
+#[test]
+fn real_world_paths_drift_at_the_historic_return() -> PricerResult<()> {
+    let (call, valuation_time, _) = get_test_call();
+    let risk_factors = MonteCarlo::get_monte_carlo_risk_factors(
+        &call,
+        42.0,
+        0.2,
+        rfr_discount("US Treasury 3M".into(), 0.05),
+        0.3,
+    );
+    let delta_t = get_duration_in_years(valuation_time, call.expiry());
+    let mean_terminal = |measure| -> PricerResult<f64> {
+        let paths = MonteCarlo::generate_monte_carlo_paths(
+            &call,
+            valuation_time,
+            risk_factors.clone(),
+            MonteCarloParams {
+                steps: 50,
+                repetitions: 20000,
+                seed: 7,
+                ..Default::default()
+            },
+            measure,
+        )?;
+        Ok(paths.iter().flat_map(|path| path.last()).sum::<f64>() / paths.len() as f64)
+    };
+    let real_world = mean_terminal(Measure::RealWorld)?;
+    let risk_neutral = mean_terminal(Measure::RiskNeutral)?;
+    assert!(is_close(real_world, 42.0 * (0.3 * delta_t).exp(), 0.01));
+    assert!(is_close(risk_neutral, 42.0 * (0.05 * delta_t).exp(), 0.01));
+    Ok(())
+}
+
+#[test]
+fn real_world_valuation_requires_a_historic_return() {
+    let (call, valuation_time, risk_factors) = get_test_call();
+    assert!(call
+        .value_monte_carlo_with_measures(valuation_time, risk_factors, vec![], monte_carlo_params())
+        .is_err());
+}
+
+fn terminal_prices_on_threads(threads: usize, seed: u64) -> Vec<f64> {
+    let (call, valuation_time, risk_factors) = get_test_call();
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(threads)
+        .build()
+        .unwrap();
+    let paths = pool
+        .install(|| {
+            MonteCarlo::generate_monte_carlo_paths(
+                &call,
+                valuation_time,
+                risk_factors,
+                MonteCarloParams {
+                    steps: 20,
+                    repetitions: 500,
+                    seed,
+                    ..Default::default()
+                },
+                Measure::RiskNeutral,
+            )
+        })
+        .unwrap();
+    paths.iter().flat_map(|path| path.last()).copied().collect()
+}
+
+#[test]
+fn seeded_paths_are_identical_across_thread_counts() {
+    let single_threaded = terminal_prices_on_threads(1, 11);
+    assert_eq!(single_threaded, terminal_prices_on_threads(4, 11));
+    assert_eq!(single_threaded, terminal_prices_on_threads(7, 11));
+    assert_ne!(single_threaded, terminal_prices_on_threads(4, 12));
+}
+
+#[test]
+fn seeded_valuations_repeat_exactly() -> PricerResult<()> {
+    let (put, valuation_time, risk_factors) = get_test_ls_put();
+    let value = || {
+        put.value_monte_carlo_ls(
+            valuation_time,
+            risk_factors.clone(),
+            vec![],
+            MonteCarloParams {
+                steps: 50,
+                ..monte_carlo_params()
+            },
+        )
+    };
+    assert_eq!(value()?.value.to_bits(), value()?.value.to_bits());
+    Ok(())
+}
+
+// Standard deviation of the call valuation over a handful of seeds
+fn spread_over_seeds(variance_reduction: VarianceReduction) -> PricerResult<f64> {
+    spread_of_dividend_call_over_seeds(MonteCarloParams {
+        steps: 20,
+        repetitions: 400,
+        variance_reduction,
+        ..Default::default()
+    })
+}
+
+fn spread_of_dividend_call_over_seeds(parameters: MonteCarloParams) -> PricerResult<f64> {
+    let (call, valuation_time, mut risk_factors) = get_test_call();
+    risk_factors.dividend_sensitivities =
+        vec![get_test_dividend_schedule(DividendPayment::Cash(1.0))];
+    let valuations = (0..16)
+        .map(|seed| {
+            call.value_monte_carlo(
+                valuation_time,
+                risk_factors.clone(),
+                vec![],
+                MonteCarloParams {
+                    seed,
+                    variance_reduction: parameters.variance_reduction.clone(),
+                    sampling: parameters.sampling.clone(),
+                    path_construction: parameters.path_construction.clone(),
+                    longstaff_schwartz: parameters.longstaff_schwartz.clone(),
+                    target: parameters.target.clone(),
+                    ..parameters
+                },
+            )
+            .map(|estimate| estimate.value)
+        })
+        .collect::<PricerResult<Vec<f64>>>()?;
+    let mean = valuations.iter().sum::<f64>() / valuations.len() as f64;
+    let variance =
+        valuations.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / (valuations.len() - 1) as f64;
+    Ok(variance.sqrt())
+}
+
+#[test]
+fn variance_reduction_narrows_the_spread_of_valuations() -> PricerResult<()> {
+    let plain = spread_over_seeds(VarianceReduction::default())?;
+    for variance_reduction in [
+        VarianceReduction {
+            antithetic: true,
+            ..Default::default()
+        },
+        VarianceReduction {
+            moment_matching: true,
+            ..Default::default()
+        },
+        VarianceReduction {
+            control_variate: true,
+            ..Default::default()
+        },
+    ] {
+        let reduced = spread_over_seeds(variance_reduction.clone())?;
+        assert!(
+            reduced < 0.75 * plain,
+            "{:?} spread {} against {} with plain sampling",
+            variance_reduction,
+            reduced,
+            plain
+        );
+    }
+    Ok(())
+}
+
+#[test]
+fn control_variate_is_exact_without_discrete_dividends() -> PricerResult<()> {
+    let (put, valuation_time, risk_factors) = get_test_put();
+    let black_scholes_valuation =
+        put.value_black_scholes(valuation_time, risk_factors.clone(), vec![])?;
+    let monte_carlo_valuation = put
+        .value_monte_carlo(
+            valuation_time,
+            risk_factors,
+            vec![],
+            MonteCarloParams {
+                steps: 10,
+                repetitions: 100,
+                variance_reduction: VarianceReduction {
+                    control_variate: true,
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+        )?
+        .value;
+    assert!(is_close(
+        black_scholes_valuation,
+        monte_carlo_valuation,
+        1e-9
+    ));
+    Ok(())
+}
+
+#[test]
+fn antithetic_paths_mirror_each_other() -> PricerResult<()> {
+    let (call, valuation_time, risk_factors) = get_test_call();
+    let paths = MonteCarlo::generate_monte_carlo_paths(
+        &call,
+        valuation_time,
+        risk_factors,
+        MonteCarloParams {
+            steps: 5,
+            repetitions: 4,
+            variance_reduction: VarianceReduction {
+                antithetic: true,
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+        Measure::RiskNeutral,
+    )?;
+    let delta_t = get_duration_in_years(valuation_time, call.expiry());
+    let drift = (0.05 - 0.5 * 0.2f64.powi(2)) * delta_t;
+    for pair in paths.chunks(2) {
+        let log_returns = (pair[0][4] / 42.0).ln() + (pair[1][4] / 42.0).ln();
+        assert!((log_returns - 2.0 * drift).abs() < 1e-12);
+    }
+    Ok(())
+}
+
+#[test]
+fn scrambled_sobol_points_stratify_each_dimension() {
+    let points = 256;
+    let sobol = Sobol::new(40, 3);
+    let coordinates: Vec<Vec<f64>> = (0..points).map(|i| sobol.point(i).collect()).collect();
+    for dimension in 0..40 {
+        let mut occupied = vec![false; points];
+        for point in &coordinates {
+            let cell = (point[dimension] * points as f64) as usize;
+            assert!(
+                !occupied[cell],
+                "two points share a cell in dimension {}",
+                dimension
+            );
+            occupied[cell] = true;
+        }
+    }
+    // The first two dimensions form a (0,2)-sequence, so every 16 x 16 square holds one point
+    let mut occupied = vec![false; points];
+    for point in &coordinates {
+        let cell = (point[0] * 16.0) as usize * 16 + (point[1] * 16.0) as usize;
+        assert!(!occupied[cell]);
+        occupied[cell] = true;
+    }
+}
+
+#[test]
+fn sobol_scrambling_depends_on_the_seed() {
+    let first: Vec<f64> = Sobol::new(5, 1).point(7).collect();
+    assert_eq!(first, Sobol::new(5, 1).point(7).collect::<Vec<f64>>());
+    assert_ne!(first, Sobol::new(5, 2).point(7).collect::<Vec<f64>>());
+}
+
+#[test]
+fn brownian_bridge_is_an_orthogonal_map_fixing_the_terminal_value() {
+    for steps in [1, 2, 7, 16, 33] {
+        let draws: Vec<f64> = (0..steps)
+            .map(|i| ((i * 37 % 11) as f64 - 5.0) / 3.0)
+            .collect();
+        let increments = BrownianBridge::new(steps).increments(&draws);
+        let terminal: f64 = increments.iter().sum();
+        assert!((terminal - (steps as f64).sqrt() * draws[0]).abs() < 1e-12);
+        let squared = |values: &[f64]| values.iter().map(|v| v.powi(2)).sum::<f64>();
+        assert!((squared(&increments) - squared(&draws)).abs() < 1e-10);
+    }
+}
+
+#[test]
+fn sobol_with_brownian_bridge_beats_pseudo_random_sampling() -> PricerResult<()> {
+    let pseudo_random = spread_of_dividend_call_over_seeds(MonteCarloParams {
+        steps: 32,
+        repetitions: 512,
+        ..Default::default()
+    })?;
+    let quasi_random = spread_of_dividend_call_over_seeds(MonteCarloParams {
+        steps: 32,
+        repetitions: 512,
+        sampling: Sampling::Sobol,
+        path_construction: PathConstruction::BrownianBridge,
+        ..Default::default()
+    })?;
+    assert!(
+        quasi_random < 0.5 * pseudo_random,
+        "Sobol spread {} against {} pseudo-random",
+        quasi_random,
+        pseudo_random
+    );
+    Ok(())
+}
+
+#[test]
+fn longstaff_schwartz_runs_on_sobol_paths() -> PricerResult<()> {
+    let (put, valuation_time, risk_factors) = get_test_ls_put();
+    let ls_valuation = put
+        .value_monte_carlo_ls(
+            valuation_time,
+            risk_factors,
+            vec![],
+            MonteCarloParams {
+                steps: 50,
+                repetitions: 4096,
+                sampling: Sampling::Sobol,
+                path_construction: PathConstruction::BrownianBridge,
+                ..Default::default()
+            },
+        )?
+        .value;
+    assert!(
+        is_close(ls_valuation, 3.28, 0.1),
+        "Monte-Carlo Longstaff-Schwartz on Sobol paths ({}) differs from expected (3.28)",
+        ls_valuation
+    );
+    Ok(())
+}
+
+#[test]
+fn standard_error_matches_the_spread_over_seeds() -> PricerResult<()> {
+    let (call, valuation_time, mut risk_factors) = get_test_call();
+    risk_factors.dividend_sensitivities =
+        vec![get_test_dividend_schedule(DividendPayment::Cash(1.0))];
+    let parameters = || MonteCarloParams {
+        steps: 20,
+        repetitions: 400,
+        ..Default::default()
+    };
+    let estimate = call.value_monte_carlo(valuation_time, risk_factors, vec![], parameters())?;
+    let spread = spread_of_dividend_call_over_seeds(parameters())?;
+    assert!(
+        (0.6..1.6).contains(&(estimate.standard_error / spread)),
+        "standard error {} against a spread of {} over seeds",
+        estimate.standard_error,
+        spread
+    );
+    assert_eq!(estimate.paths, 400);
+    assert!(estimate.runtime.as_nanos() > 0);
+    Ok(())
+}
+
+#[test]
+fn confidence_interval_brackets_black_scholes() -> PricerResult<()> {
+    let (put, valuation_time, risk_factors) = get_test_put();
+    let black_scholes_valuation =
+        put.value_black_scholes(valuation_time, risk_factors.clone(), vec![])?;
+    let estimate = put.value_monte_carlo(
+        valuation_time,
+        risk_factors,
+        vec![],
+        MonteCarloParams {
+            steps: 50,
+            repetitions: 20000,
+            ..Default::default()
+        },
+    )?;
+    let (lower, upper) = estimate.confidence_interval;
+    assert!(
+        lower < black_scholes_valuation && black_scholes_valuation < upper,
+        "Black-Scholes ({}) outside the confidence interval ({}, {})",
+        black_scholes_valuation,
+        lower,
+        upper
+    );
+    assert!(((lower + upper) / 2.0 - estimate.value).abs() < 1e-12);
+    Ok(())
+}
+
+#[test]
+fn standard_error_reflects_variance_reduction() -> PricerResult<()> {
+    let (call, valuation_time, mut risk_factors) = get_test_call();
+    risk_factors.dividend_sensitivities =
+        vec![get_test_dividend_schedule(DividendPayment::Cash(1.0))];
+    let standard_error = |variance_reduction| -> PricerResult<f64> {
+        call.value_monte_carlo(
+            valuation_time,
+            risk_factors.clone(),
+            vec![],
+            MonteCarloParams {
+                steps: 20,
+                repetitions: 2000,
+                variance_reduction,
+                ..Default::default()
+            },
+        )
+        .map(|estimate| estimate.standard_error)
+    };
+    let plain = standard_error(VarianceReduction::default())?;
+    let antithetic = standard_error(VarianceReduction {
+        antithetic: true,
+        ..Default::default()
+    })?;
+    let control_variate = standard_error(VarianceReduction {
+        control_variate: true,
+        ..Default::default()
+    })?;
+    assert!(antithetic < plain);
+    assert!(control_variate < 0.2 * plain);
+    Ok(())
+}
+
+#[test]
+fn european_valuation_only_simulates_observed_dates() -> PricerResult<()> {
+    let (call, valuation_time, mut risk_factors) = get_test_call();
+    risk_factors.dividend_sensitivities =
+        vec![get_test_dividend_schedule(DividendPayment::Cash(1.0))];
+    let value_with_steps = |steps| {
+        call.value_monte_carlo(
+            valuation_time,
+            risk_factors.clone(),
+            vec![],
+            MonteCarloParams {
+                steps,
+                repetitions: 1000,
+                ..Default::default()
+            },
+        )
+        .map(|estimate| estimate.value)
+    };
+    assert_eq!(
+        value_with_steps(1)?.to_bits(),
+        value_with_steps(10000)?.to_bits()
+    );
+    Ok(())
+}
+
+#[test]
+fn streamed_valuation_is_identical_across_thread_counts() {
+    let (put, valuation_time, risk_factors) = get_test_put();
+    let value_on_threads = |threads| {
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(threads)
+            .build()
+            .unwrap();
+        pool.install(|| {
+            put.value_monte_carlo(
+                valuation_time,
+                risk_factors.clone(),
+                vec![],
+                MonteCarloParams {
+                    repetitions: 10_000,
+                    variance_reduction: VarianceReduction {
+                        antithetic: true,
+                        moment_matching: true,
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                },
+            )
+        })
+        .unwrap()
+    };
+    let single_threaded = value_on_threads(1);
+    let multi_threaded = value_on_threads(5);
+    assert_eq!(
+        single_threaded.value.to_bits(),
+        multi_threaded.value.to_bits()
+    );
+    assert_eq!(
+        single_threaded.standard_error.to_bits(),
+        multi_threaded.standard_error.to_bits()
+    );
+}
+
+#[test]
+fn many_paths_converge_on_black_scholes() -> PricerResult<()> {
+    let (call, valuation_time, risk_factors) = get_test_call();
+    let black_scholes_valuation =
+        call.value_black_scholes(valuation_time, risk_factors.clone(), vec![])?;
+    let estimate = call.value_monte_carlo(
+        valuation_time,
+        risk_factors,
+        vec![],
+        MonteCarloParams {
+            repetitions: 40_000,
+            ..Default::default()
+        },
+    )?;
+    assert!(estimate.standard_error < 0.03);
+    assert!(
+        (estimate.value - black_scholes_valuation).abs() < 4.0 * estimate.standard_error,
+        "Monte Carlo valuation ({} ± {}) differs from Black-Scholes ({})",
+        estimate.value,
+        estimate.standard_error,
+        black_scholes_valuation
+    );
+    Ok(())
+}
+
+fn aad_parameters() -> MonteCarloParams {
+    MonteCarloParams {
+        repetitions: 20_000,
+        seed: 3,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn aad_greeks_match_black_scholes_greeks() -> PricerResult<()> {
+    let (call, valuation_time, risk_factors) = get_test_call();
+    let greeks = call.greeks_monte_carlo(
+        valuation_time,
+        risk_factors.clone(),
+        vec![],
+        aad_parameters(),
+    )?;
+    let expected = [
+        (
+            "delta",
+            greeks.delta,
+            call.delta(valuation_time, risk_factors.clone())?,
+        ),
+        (
+            "vega",
+            greeks.vega,
+            call.vega(valuation_time, risk_factors.clone())?,
+        ),
+        (
+            "rho",
+            greeks.rho,
+            call.rho(valuation_time, risk_factors.clone())?,
+        ),
+        (
+            "theta",
+            greeks.theta,
+            call.theta(valuation_time, risk_factors)?,
+        ),
+    ];
+    for (name, adjoint, analytical) in expected {
+        assert!(
+            is_close(adjoint, analytical, 0.05),
+            "Adjoint {} ({}) differs from Black-Scholes ({})",
+            name,
+            adjoint,
+            analytical
+        );
+    }
+    Ok(())
+}
+
+#[test]
+fn aad_greeks_come_from_the_pricing_paths() -> PricerResult<()> {
+    let (put, valuation_time, mut risk_factors) = get_test_put();
+    risk_factors.dividend_sensitivities =
+        vec![get_test_dividend_schedule(DividendPayment::Cash(1.0))];
+    let greeks = put.greeks_monte_carlo(
+        valuation_time,
+        risk_factors.clone(),
+        vec![],
+        monte_carlo_params(),
+    )?;
+    let estimate =
+        put.value_monte_carlo(valuation_time, risk_factors, vec![], monte_carlo_params())?;
+    assert!((greeks.value - estimate.value).abs() < 1e-9);
+    Ok(())
+}
+
+#[test]
+fn aad_greeks_match_bump_and_reprice_with_dividends() -> PricerResult<()> {
+    let (call, valuation_time, mut risk_factors) = get_test_call();
+    risk_factors.dividend_sensitivities =
+        vec![get_test_dividend_schedule(DividendPayment::Cash(1.0))];
+    let reprice = |scenario| -> PricerResult<f64> {
+        Ok(call
+            .value_monte_carlo(
+                valuation_time,
+                risk_factors.clone(),
+                scenario,
+                monte_carlo_params(),
+            )?
+            .value)
+    };
+    let bump = 0.01;
+    let price_bump = |direction| price_shock(Symbol::from("AAPL"), absolute_shock(bump, direction));
+    let delta = (reprice(vec![price_bump(ShockDirection::Up)])?
+        - reprice(vec![price_bump(ShockDirection::Down)])?)
+        / (2.0 * bump);
+    let day = time_shock(absolute_time_shock(Duration::days(1), ShockDirection::Down));
+    let theta = reprice(vec![day])? - reprice(vec![])?;
+    let greeks = call.greeks_monte_carlo(
+        valuation_time,
+        risk_factors.clone(),
+        vec![],
+        monte_carlo_params(),
+    )?;
+    assert!(
+        is_close(greeks.delta, delta, 0.01),
+        "Adjoint delta ({}) differs from bump and reprice on the same paths ({})",
+        greeks.delta,
+        delta
+    );
+    assert!(
+        is_close(greeks.theta, theta, 0.05),
+        "Adjoint theta ({}) differs from a day's decay on the same paths ({})",
+        greeks.theta,
+        theta
+    );
+    Ok(())
+}
+
+#[test]
+fn longstaff_schwartz_aad_greeks() -> PricerResult<()> {
+    let (put, valuation_time, risk_factors) = get_test_ls_put();
+    let parameters = || MonteCarloParams {
+        steps: 50,
+        repetitions: 4000,
+        ..Default::default()
+    };
+    let greeks =
+        put.greeks_monte_carlo_ls(valuation_time, risk_factors.clone(), vec![], parameters())?;
+    let estimate = put.value_monte_carlo_ls(valuation_time, risk_factors, vec![], parameters())?;
+    assert!((greeks.value - estimate.value).abs() < 1e-9);
+    assert!(
+        -1.0 < greeks.delta && greeks.delta < 0.0,
+        "Longstaff-Schwartz put delta ({}) outside (-1, 0)",
+        greeks.delta
+    );
+    assert!(greeks.vega > 0.0);
+    assert!(greeks.rho < 0.0);
+    Ok(())
+}
+
+#[test]
+fn pathwise_and_likelihood_ratio_greeks_match_black_scholes() -> PricerResult<()> {
+    let (call, valuation_time, risk_factors) = get_test_call();
+    for estimator in [GreekEstimator::Pathwise, GreekEstimator::LikelihoodRatio] {
+        let estimates = call.greek_estimates_monte_carlo(
+            valuation_time,
+            risk_factors.clone(),
+            vec![],
+            aad_parameters(),
+            estimator.clone(),
+        )?;
+        let expected = [
+            (
+                "delta",
+                estimates.delta,
+                call.delta(valuation_time, risk_factors.clone())?,
+            ),
+            (
+                "vega",
+                estimates.vega,
+                call.vega(valuation_time, risk_factors.clone())?,
+            ),
+            (
+                "rho",
+                estimates.rho,
+                call.rho(valuation_time, risk_factors.clone())?,
+            ),
+            (
+                "theta",
+                estimates.theta,
+                call.theta(valuation_time, risk_factors.clone())?,
+            ),
+        ];
+        for (name, estimate, analytical) in expected {
+            assert!(
+                (estimate.mean - analytical).abs() < 4.0 * estimate.standard_error,
+                "{:?} {} ({} ± {}) differs from Black-Scholes ({})",
+                estimator,
+                name,
+                estimate.mean,
+                estimate.standard_error,
+                analytical
+            );
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn greek_estimates_share_the_pricing_paths() -> PricerResult<()> {
+    let (put, valuation_time, mut risk_factors) = get_test_put();
+    risk_factors.dividend_sensitivities =
+        vec![get_test_dividend_schedule(DividendPayment::Cash(1.0))];
+    let estimates = put.greek_estimates_monte_carlo(
+        valuation_time,
+        risk_factors.clone(),
+        vec![],
+        monte_carlo_params(),
+        GreekEstimator::Pathwise,
+    )?;
+    let estimate =
+        put.value_monte_carlo(valuation_time, risk_factors, vec![], monte_carlo_params())?;
+    assert!((estimates.value.mean - estimate.value).abs() < 1e-9);
+    assert!((estimates.value.standard_error - estimate.standard_error).abs() < 1e-9);
+    Ok(())
+}
+
+#[test]
+fn likelihood_ratio_delta_is_noisier_than_pathwise() -> PricerResult<()> {
+    let (call, valuation_time, risk_factors) = get_test_call();
+    let delta_error = |estimator| -> PricerResult<f64> {
+        Ok(call
+            .greek_estimates_monte_carlo(
+                valuation_time,
+                risk_factors.clone(),
+                vec![],
+                monte_carlo_params(),
+                estimator,
+            )?
+            .delta
+            .standard_error)
+    };
+    assert!(delta_error(GreekEstimator::LikelihoodRatio)? > delta_error(GreekEstimator::Pathwise)?);
+    Ok(())
+}
+
+#[test]
+fn likelihood_ratio_digital_delta_matches_closed_form() -> PricerResult<()> {
+    let (call, valuation_time, risk_factors) = get_test_call();
+    let digital = DigitalPayoff {
+        is_call: true,
+        strike: call.strike(),
+    };
+    let estimates = greek_estimates_monte_carlo(
+        &digital,
+        call.expiry(),
+        valuation_time,
+        risk_factors,
+        vec![],
+        aad_parameters(),
+        GreekEstimator::LikelihoodRatio,
+    )?;
+    let (price, volatility, rate) = (42.0, 0.2, 0.05);
+    let time = get_duration_in_years(valuation_time, call.expiry());
+    let d2 = ((price / call.strike()).ln() + (rate - 0.5 * volatility * volatility) * time)
+        / (volatility * time.sqrt());
+    let density = (-0.5 * d2 * d2).exp() / (2.0 * std::f64::consts::PI).sqrt();
+    let delta = (-rate * time).exp() * density / (price * volatility * time.sqrt());
+    assert!(
+        (estimates.delta.mean - delta).abs() < 4.0 * estimates.delta.standard_error,
+        "Likelihood ratio digital delta ({} ± {}) differs from closed form ({})",
+        estimates.delta.mean,
+        estimates.delta.standard_error,
+        delta
+    );
+    Ok(())
+}
+
+#[test]
+fn pathwise_greeks_reject_discontinuous_payoffs() {
+    let (call, valuation_time, risk_factors) = get_test_call();
+    let digital = DigitalPayoff {
+        is_call: true,
+        strike: call.strike(),
+    };
+    assert!(greek_estimates_monte_carlo(
+        &digital,
+        call.expiry(),
+        valuation_time,
+        risk_factors,
+        vec![],
+        monte_carlo_params(),
+        GreekEstimator::Pathwise,
+    )
+    .is_err_and(|error| error.code == 15));
+}
+
+#[test]
+fn knock_in_and_knock_out_greeks_add_up_to_the_vanilla() -> PricerResult<()> {
+    let (call, valuation_time, risk_factors) = get_test_call();
+    let estimates = |barrier| {
+        let payoff = BarrierPayoff {
+            is_call: true,
+            strike: call.strike(),
+            barrier,
+            monitoring_dates: 20,
+        };
+        greek_estimates_monte_carlo(
+            &payoff,
+            call.expiry(),
+            valuation_time,
+            risk_factors.clone(),
+            vec![],
+            monte_carlo_params(),
+            GreekEstimator::LikelihoodRatio,
+        )
+    };
+    let knock_in = estimates(Barrier::UpAndIn(48.0))?;
+    let knock_out = estimates(Barrier::UpAndOut(48.0))?;
+    let vanilla = estimates(Barrier::UpAndOut(f64::INFINITY))?;
+    let greeks = |estimates: &MonteCarloGreekEstimates| {
+        [
+            estimates.value.mean,
+            estimates.delta.mean,
+            estimates.vega.mean,
+            estimates.rho.mean,
+            estimates.theta.mean,
+        ]
+    };
+    for ((knock_in, knock_out), vanilla) in greeks(&knock_in)
+        .iter()
+        .zip(greeks(&knock_out))
+        .zip(greeks(&vanilla))
+    {
+        assert!((knock_in + knock_out - vanilla).abs() < 1e-9);
+    }
+    assert!(knock_out.value.mean < vanilla.value.mean);
+    Ok(())
+}
+
+#[test]
+fn down_barrier_put_greeks_add_up_to_the_vanilla() -> PricerResult<()> {
+    let (put, valuation_time, risk_factors) = get_test_put();
+    let estimates = |barrier| {
+        let payoff = crate::BarrierPayoff {
+            is_call: false,
+            strike: put.strike(),
+            barrier,
+            monitoring_dates: 20,
+        };
+        crate::greek_estimates_monte_carlo(
+            &payoff,
+            put.expiry(),
+            valuation_time,
+            risk_factors.clone(),
+            vec![],
+            monte_carlo_params(),
+            crate::GreekEstimator::LikelihoodRatio,
+        )
+    };
+    let knock_in = estimates(crate::Barrier::DownAndIn(36.0))?;
+    let knock_out = estimates(crate::Barrier::DownAndOut(36.0))?;
+    let vanilla = estimates(crate::Barrier::DownAndOut(0.0))?;
+    let greeks = |estimates: &crate::MonteCarloGreekEstimates| {
+        [
+            estimates.value.mean,
+            estimates.delta.mean,
+            estimates.vega.mean,
+            estimates.rho.mean,
+            estimates.theta.mean,
+        ]
+    };
+    for ((knock_in, knock_out), vanilla) in greeks(&knock_in)
+        .iter()
+        .zip(greeks(&knock_out))
+        .zip(greeks(&vanilla))
+    {
+        assert!((knock_in + knock_out - vanilla).abs() < 1e-9);
+    }
+    assert!(knock_in.value.mean > 0.0);
+    assert!(knock_out.value.mean < vanilla.value.mean);
+    // Knocking in needs the price to fall, so the knock-in put gains as it does
+    assert!(knock_in.delta.mean < 0.0);
+    Ok(())
+}
+
+#[test]
+fn scenarios_are_valued_on_one_path_set() -> PricerResult<()> {
+    let (call, valuation_time, mut risk_factors) = get_test_call();
+    risk_factors.dividend_sensitivities =
+        vec![get_test_dividend_schedule(DividendPayment::Cash(1.0))];
+    let price_bump = || {
+        price_shock(
+            Symbol::from("AAPL"),
+            absolute_shock(1.0, ShockDirection::Up),
+        )
+    };
+    let estimates = call.value_monte_carlo_scenarios(
+        valuation_time,
+        risk_factors.clone(),
+        &[vec![], vec![price_bump()]],
+        monte_carlo_params(),
+    )?;
+    let base = call.value_monte_carlo(
+        valuation_time,
+        risk_factors.clone(),
+        vec![],
+        monte_carlo_params(),
+    )?;
+    let bumped = call.value_monte_carlo(
+        valuation_time,
+        risk_factors,
+        vec![price_bump()],
+        monte_carlo_params(),
+    )?;
+    assert_eq!(estimates[0].value.to_bits(), base.value.to_bits());
+    assert_eq!(estimates[1].value.to_bits(), bumped.value.to_bits());
+    Ok(())
+}
+
+#[test]
+fn common_random_numbers_steady_bump_and_reprice() -> PricerResult<()> {
+    let (call, valuation_time, risk_factors) = get_test_call();
+    let deltas = (0..8)
+        .map(|seed| {
+            let estimates = call.value_monte_carlo_scenarios(
+                valuation_time,
+                risk_factors.clone(),
+                &[
+                    vec![],
+                    vec![price_shock(
+                        Symbol::from("AAPL"),
+                        absolute_shock(0.1, ShockDirection::Up),
+                    )],
+                ],
+                MonteCarloParams {
+                    seed,
+                    ..monte_carlo_params()
+                },
+            )?;
+            Ok((estimates[1].value - estimates[0].value) / 0.1)
+        })
+        .collect::<PricerResult<Vec<f64>>>()?;
+    let spread = deltas.iter().cloned().fold(f64::MIN, f64::max)
+        - deltas.iter().cloned().fold(f64::MAX, f64::min);
+    assert!(
+        spread < 0.1,
+        "Bump and reprice deltas on common paths spread by {} over seeds",
+        spread
+    );
+    Ok(())
+}
+
+fn american_tree_put() -> PricerResult<f64> {
+    let (put, valuation_time, risk_factors) = get_test_ls_put();
+    put.value_tree(
+        valuation_time,
+        risk_factors,
+        vec![],
+        &TreeParams {
+            exercise_style: ExerciseStyle::American,
+            ..Default::default()
+        },
+    )
+}
+
+fn longstaff_schwartz_put(parameters: LongstaffSchwartzParams) -> PricerResult<MonteCarloEstimate> {
+    let (put, valuation_time, risk_factors) = get_test_ls_put();
+    put.value_monte_carlo_ls(
+        valuation_time,
+        risk_factors,
+        vec![],
+        MonteCarloParams {
+            steps: 50,
+            repetitions: 10000,
+            longstaff_schwartz: parameters,
+            ..Default::default()
+        },
+    )
+}
+
+#[test]
+fn longstaff_schwartz_bases_converge_on_the_american_tree() -> PricerResult<()> {
+    let tree = american_tree_put()?;
+    for basis in [
+        RegressionBasis::Monomial,
+        RegressionBasis::Laguerre,
+        RegressionBasis::Hermite,
+    ] {
+        let estimate = longstaff_schwartz_put(LongstaffSchwartzParams {
+            basis: basis.clone(),
+            degree: 3,
+            ..Default::default()
+        })?;
+        assert!(
+            (estimate.value - tree).abs() < 3.0 * estimate.standard_error,
+            "{:?} basis ({} ± {}) differs from the American tree ({})",
+            basis,
+            estimate.value,
+            estimate.standard_error,
+            tree
+        );
+    }
+    Ok(())
+}
+
+#[test]
+fn priceable_longstaff_schwartz_put_is_near_the_american_tree() -> PricerResult<()> {
+    let tree = american_tree_put()?;
+    let (put, valuation_time, risk_factors) = get_test_ls_put();
+    let value = Priceable::LongstaffSchwartz(&put).value(valuation_time, risk_factors, vec![])?;
+    assert!(
+        is_close(value, tree, 0.03),
+        "Longstaff-Schwartz ({}) differs from the American tree ({}) by more than 3%",
+        value,
+        tree
+    );
+    Ok(())
+}
+
+#[test]
+fn longstaff_schwartz_call_is_not_implemented() {
+    let (call, valuation_time, risk_factors) = get_test_call();
+    assert!(Priceable::LongstaffSchwartz(&call)
+        .value(valuation_time, risk_factors, vec![])
+        .is_err_and(|error| error.code == 999));
+}
+
+#[test]
+fn hermite_and_monomial_bases_span_the_same_continuation_values() -> PricerResult<()> {
+    let value = |basis| {
+        longstaff_schwartz_put(LongstaffSchwartzParams {
+            basis,
+            degree: 3,
+            ..Default::default()
+        })
+        .map(|estimate| estimate.value)
+    };
+    let monomial = value(RegressionBasis::Monomial)?;
+    let hermite = value(RegressionBasis::Hermite)?;
+    assert!((monomial - hermite).abs() < 1e-9);
+    Ok(())
+}
+
+#[test]
+fn out_of_sample_exercise_removes_the_foresight_bias() -> PricerResult<()> {
+    let tree = american_tree_put()?;
+    let value = |out_of_sample| {
+        longstaff_schwartz_put(LongstaffSchwartzParams {
+            out_of_sample,
+            ..Default::default()
+        })
+    };
+    let in_sample = value(false)?;
+    let out_of_sample = value(true)?;
+    // The independent rule is suboptimal on the pricing paths, so it can only lower the value
+    assert!(out_of_sample.value < in_sample.value);
+    assert!((out_of_sample.value - tree).abs() < 3.0 * out_of_sample.standard_error);
+    Ok(())
+}
+
+#[test]
+fn heavy_ridge_exercises_as_soon_as_the_put_is_in_the_money() -> PricerResult<()> {
+    let fitted = longstaff_schwartz_put(Default::default())?;
+    let shrunk = longstaff_schwartz_put(LongstaffSchwartzParams {
+        ridge: 1e12,
+        ..Default::default()
+    })?;
+    assert!(shrunk.value < fitted.value);
+    Ok(())
+}
+
+fn adaptive_call(
+    repetitions: usize,
+    tolerance: Tolerance,
+    time_budget: std::time::Duration,
+) -> PricerResult<MonteCarloEstimate> {
+    let (call, valuation_time, risk_factors) = get_test_call();
+    call.value_monte_carlo(
+        valuation_time,
+        risk_factors,
+        vec![],
+        MonteCarloParams {
+            repetitions,
+            seed: 3,
+            target: Some(TargetAccuracy {
+                tolerance,
+                time_budget,
+                batch_paths: 1000,
+            }),
+            ..Default::default()
+        },
+    )
+}
+
+#[test]
+fn adaptive_valuation_stops_once_the_target_is_met() -> PricerResult<()> {
+    let estimate = adaptive_call(
+        1_000_000,
+        Tolerance::StandardError(0.05),
+        std::time::Duration::from_secs(60),
+    )?;
+    assert_eq!(estimate.target_met, Some(true));
+    assert!(estimate.standard_error <= 0.05);
+    assert!(estimate.paths < 1_000_000);
+    assert_eq!(estimate.paths % 1000, 0);
+    // One batch fewer would have missed the target
+    let (call, valuation_time, risk_factors) = get_test_call();
+    let previous_batch = call.value_monte_carlo(
+        valuation_time,
+        risk_factors,
+        vec![],
+        MonteCarloParams {
+            repetitions: estimate.paths - 1000,
+            seed: 3,
+            ..Default::default()
+        },
+    )?;
+    assert!(previous_batch.standard_error > 0.05);
+    Ok(())
+}
+
+#[test]
+fn relative_tolerance_scales_with_the_value() -> PricerResult<()> {
+    let estimate = adaptive_call(
+        1_000_000,
+        Tolerance::Relative(0.01),
+        std::time::Duration::from_secs(60),
+    )?;
+    assert_eq!(estimate.target_met, Some(true));
+    assert!(estimate.standard_error <= 0.01 * estimate.value);
+    Ok(())
+}
+
+#[test]
+fn adaptive_valuation_reports_a_missed_target() -> PricerResult<()> {
+    let capped = adaptive_call(
+        5000,
+        Tolerance::StandardError(1e-9),
+        std::time::Duration::from_secs(60),
+    )?;
+    assert_eq!(capped.target_met, Some(false));
+    assert_eq!(capped.paths, 5000);
+    let out_of_time = adaptive_call(
+        1_000_000,
+        Tolerance::StandardError(1e-9),
+        std::time::Duration::ZERO,
+    )?;
+    assert_eq!(out_of_time.target_met, Some(false));
+    assert_eq!(out_of_time.paths, 1000);
+    Ok(())
+}
+
+#[test]
+fn stopping_early_values_the_leading_paths_of_a_longer_run() -> PricerResult<()> {
+    let adaptive = adaptive_call(
+        1_000_000,
+        Tolerance::StandardError(0.05),
+        std::time::Duration::from_secs(60),
+    )?;
+    let (call, valuation_time, risk_factors) = get_test_call();
+    let fixed = call.value_monte_carlo(
+        valuation_time,
+        risk_factors,
+        vec![],
+        MonteCarloParams {
+            repetitions: adaptive.paths,
+            seed: 3,
+            ..Default::default()
+        },
+    )?;
+    assert_eq!(fixed.target_met, None);
+    assert!((adaptive.value - fixed.value).abs() < 1e-12);
+    assert!((adaptive.standard_error - fixed.standard_error).abs() < 1e-12);
+    Ok(())
+}
+
+#[test]
+fn moment_matching_cannot_stop_at_a_target() {
+    let (call, valuation_time, risk_factors) = get_test_call();
+    let valuation = call.value_monte_carlo(
+        valuation_time,
+        risk_factors,
+        vec![],
+        MonteCarloParams {
+            repetitions: 1_000_000,
+            variance_reduction: VarianceReduction {
+                moment_matching: true,
+                ..Default::default()
+            },
+            target: Some(TargetAccuracy::default()),
+            ..Default::default()
+        },
+    );
+    assert!(valuation.is_err());
+}
@@ -0,0 +1,28 @@
+use super::MonteCarloInputs;
+
+use crate::black_scholes::black_scholes_price;
+use crate::result::PricerResult;
+
+// Sources of equations:
+//  - Glasserman (2003), Monte Carlo methods in financial engineering, sections 4.1 and 4.5
+
+// Undiscounted expected vanilla payoff at expiry when the underlying grows at `carry` with no
+// discrete dividends, the known mean of the control variate
+pub fn vanilla_expected_payoff(
+    is_call: bool,
+    strike: f64,
+    inputs: &MonteCarloInputs,
+    carry: f64,
+) -> PricerResult<f64> {
+    let rate = inputs.discount_rate();
+    black_scholes_price(
+        is_call,
+        inputs.price(),
+        strike,
+        inputs.delta_t,
+        rate,
+        rate - carry,
+        inputs.volatility(),
+    )
+    .map(|price| price * (rate * inputs.delta_t).exp())
+}
@@ -64,6 +64,12 @@ impl Put {
     }
 }
 
+#[derive(Clone, Debug)]
+pub enum ExerciseStyle {
+    European,
+    American,
+}
+
 pub trait FinancialOption {
     fn symbol(&self) -> &Symbol;
     fn strike(&self) -> f64;
@@ -0,0 +1,36 @@
+use super::IdentifiableRiskFactor;
+
+use crate::shock::{ApplyShock, BorrowShock};
+use crate::symbol::Symbol;
+
+// Annualised fee for borrowing the stock, it reduces the forward in the same way as a dividend
+// yield since a short seller pays it to the lender
+#[derive(Clone)]
+pub struct BorrowRate {
+    symbol: Symbol,
+    rate: f64,
+}
+
+impl BorrowRate {
+    pub fn new(symbol: Symbol, rate: f64) -> BorrowRate {
+        BorrowRate { symbol, rate }
+    }
+    pub fn rate(&self) -> f64 {
+        self.rate
+    }
+}
+
+impl IdentifiableRiskFactor for BorrowRate {
+    fn id(&self) -> &Symbol {
+        &self.symbol
+    }
+}
+
+impl ApplyShock<BorrowRate> for BorrowShock {
+    fn apply(&self, applicant: &mut BorrowRate) {
+        if applicant.id() != self.risk_factor() {
+            return;
+        }
+        self.apply(&mut applicant.rate)
+    }
+}
@@ -1,16 +1,19 @@
-use crate::shock::{ApplyShock, InterestRateShock};
+use crate::result::{PricerError, PricerResult};
+use crate::shock::{ApplyShock, InterestRateShock, KeyRateShock};
 use crate::symbol::Symbol;
 
-use statrs::statistics::Statistics;
+use chrono::{DateTime, Duration, Utc};
 
+use super::time_series::TimeSeries;
+use super::yield_curve::YieldCurve;
 use super::IdentifiableRiskFactor;
 
-const BUSINESS_DAYS_IN_YEAR: usize = 252;
-
+// Realised return of an asset or index from its dated levels
 #[derive(Clone)]
 pub struct HistoricReturnSeries {
     symbol: Symbol,
-    daily_yields: Vec<f64>,
+    levels: TimeSeries<f64>,
+    look_back: Option<Duration>,
 }
 
 #[derive(Clone)]
@@ -40,19 +43,63 @@ impl HistoricReturn {
     }
 }
 
+fn invalid_return_series_err(symbol: &Symbol) -> PricerError {
+    PricerError::new(
+        format!(
+            "Historic returns for {} need two positive levels at distinct times",
+            symbol
+        ),
+        19,
+    )
+}
+
+impl HistoricReturnSeries {
+    pub fn new(symbol: Symbol, levels: TimeSeries<f64>) -> PricerResult<HistoricReturnSeries> {
+        if levels.len() < 2 || levels.values().iter().any(|level| *level <= 0.0) {
+            return Err(invalid_return_series_err(&symbol));
+        }
+        Ok(HistoricReturnSeries {
+            symbol,
+            levels,
+            look_back: None,
+        })
+    }
+
+    // Only measure the return over `look_back` before the valuation time
+    pub fn with_look_back(self, look_back: Duration) -> HistoricReturnSeries {
+        HistoricReturnSeries {
+            look_back: Some(look_back),
+            ..self
+        }
+    }
+
+    pub fn as_of(&self, valuation_time: DateTime<Utc>) -> PricerResult<HistoricReturnSeries> {
+        let levels = self.levels.as_of(valuation_time, self.look_back);
+        if levels.len() < 2 {
+            return Err(invalid_return_series_err(&self.symbol));
+        }
+        Ok(HistoricReturnSeries {
+            levels,
+            ..self.clone()
+        })
+    }
+}
+
 pub trait DiscountRf {
     fn rate(&self) -> f64;
+    // Continuously compounded rate to a maturity `delta_t` years away, flat unless overridden
+    fn zero_rate(&self, _delta_t: f64) -> f64 {
+        self.rate()
+    }
     fn discount_factor(&self, delta_t: f64) -> f64 {
-        (-self.rate() * delta_t).exp()
+        (-self.zero_rate(delta_t) * delta_t).exp()
     }
 }
 
+// Continuously compounded over the calendar time spanned, so gaps in the levels do not bias it
 impl DiscountRf for HistoricReturnSeries {
     fn rate(&self) -> f64 {
-        self.daily_yields
-            .windows(252)
-            .map(|window| window.iter().sum::<f64>())
-            .mean()
+        self.levels.annualised_growth().unwrap_or(0.0)
     }
 }
 
@@ -91,6 +138,7 @@ pub enum DiscountFactor {
     HistoricReturnSeries(HistoricReturnSeries),
     HistoricReturn(HistoricReturn),
     RiskFreeRate(InterestRate),
+    YieldCurve(YieldCurve),
 }
 
 impl DiscountRf for DiscountFactor {
@@ -99,6 +147,24 @@ impl DiscountRf for DiscountFactor {
             DiscountFactor::RiskFreeRate(rfr) => rfr.rate(),
             DiscountFactor::HistoricReturn(hr) => hr.rate(),
             DiscountFactor::HistoricReturnSeries(hr) => hr.rate(),
+            DiscountFactor::YieldCurve(curve) => curve.rate(),
+        }
+    }
+    fn zero_rate(&self, delta_t: f64) -> f64 {
+        match &self {
+            DiscountFactor::YieldCurve(curve) => curve.zero_rate(delta_t),
+            _ => self.rate(),
+        }
+    }
+}
+
+impl DiscountFactor {
+    pub fn as_of(&self, valuation_time: DateTime<Utc>) -> PricerResult<DiscountFactor> {
+        match self {
+            DiscountFactor::HistoricReturnSeries(hr) => hr
+                .as_of(valuation_time)
+                .map(DiscountFactor::HistoricReturnSeries),
+            _ => Ok(self.clone()),
         }
     }
 }
@@ -109,6 +175,7 @@ impl IdentifiableRiskFactor for DiscountFactor {
             DiscountFactor::RiskFreeRate(rfr) => rfr.id(),
             DiscountFactor::HistoricReturn(hr) => hr.id(),
             DiscountFactor::HistoricReturnSeries(hr) => hr.id(),
+            DiscountFactor::YieldCurve(curve) => curve.id(),
         }
     }
 }
@@ -117,6 +184,7 @@ impl ApplyShock<DiscountFactor> for InterestRateShock {
     fn apply(&self, applicant: &mut DiscountFactor) {
         match applicant {
             DiscountFactor::RiskFreeRate(rfr) => self.apply(&mut rfr.rate),
+            DiscountFactor::YieldCurve(curve) => self.apply(curve),
             // This is weird and not sure how it would interact?
             DiscountFactor::HistoricReturn(_) => {}
             DiscountFactor::HistoricReturnSeries(_) => {}
@@ -127,3 +195,12 @@ impl ApplyShock<DiscountFactor> for InterestRateShock {
 pub fn rfr_discount(symbol: Symbol, rate: f64) -> DiscountFactor {
     DiscountFactor::RiskFreeRate(InterestRate { symbol, rate })
 }
+
+// Flat rates have no term structure to bucket, only curves take key rate shocks
+impl ApplyShock<DiscountFactor> for KeyRateShock {
+    fn apply(&self, applicant: &mut DiscountFactor) {
+        if let DiscountFactor::YieldCurve(curve) = applicant {
+            self.apply(curve)
+        }
+    }
+}
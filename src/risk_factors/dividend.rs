@@ -1,5 +1,6 @@
 use super::IdentifiableRiskFactor;
 
+use crate::shock::{ApplyShock, DividendShock};
 use crate::{symbol::Symbol, utils::date::get_duration_in_years};
 
 use chrono::{DateTime, Utc};
@@ -19,12 +20,67 @@ impl AnnualisedDividendRate {
     }
 }
 
+#[derive(Clone, Debug)]
+pub enum DividendPayment {
+    // Fixed cash amount per share
+    Cash(f64),
+    // Fraction of the share price at the ex-date
+    Proportional(f64),
+}
+
+#[derive(Clone)]
+pub struct ScheduledDividend {
+    pub ex_date: DateTime<Utc>,
+    pub payment: DividendPayment,
+}
+
+#[derive(Clone)]
+pub struct DividendSchedule {
+    symbol: Symbol,
+    dividends: Vec<ScheduledDividend>,
+}
+
+impl DividendSchedule {
+    pub fn new(symbol: Symbol, mut dividends: Vec<ScheduledDividend>) -> DividendSchedule {
+        dividends.sort_by_key(|dividend| dividend.ex_date);
+        DividendSchedule { symbol, dividends }
+    }
+}
+
+// A dividend still to go ex, `time` is in years from the valuation time
+#[derive(Clone, Debug)]
+pub struct OutstandingDividend {
+    pub time: f64,
+    pub payment: DividendPayment,
+}
+
+pub fn present_value_of_cash(dividends: &[OutstandingDividend], discount_rate: f64) -> f64 {
+    dividends
+        .iter()
+        .map(|dividend| match dividend.payment {
+            DividendPayment::Cash(amount) => amount * (-discount_rate * dividend.time).exp(),
+            DividendPayment::Proportional(_) => 0.0,
+        })
+        .sum()
+}
+
+pub fn proportional_factor(dividends: &[OutstandingDividend]) -> f64 {
+    dividends
+        .iter()
+        .map(|dividend| match dividend.payment {
+            DividendPayment::Cash(_) => 1.0,
+            DividendPayment::Proportional(fraction) => 1.0 - fraction,
+        })
+        .product()
+}
+
 pub trait DividendRf {
     fn discount(
         &self,
         initial_value: f64,
         begin_date: DateTime<Utc>,
         end_date: DateTime<Utc>,
+        discount_rate: f64,
     ) -> f64;
 }
 
@@ -34,22 +90,78 @@ impl DividendRf for AnnualisedDividendRate {
         initial_value: f64,
         begin_date: DateTime<Utc>,
         end_date: DateTime<Utc>,
+        _discount_rate: f64,
     ) -> f64 {
         let delta_t = get_duration_in_years(begin_date, end_date);
         initial_value * (-self.rate * delta_t).exp()
     }
 }
 
+// Escrowed dividend model, the value net of the present value of cash dividends and of the
+// proportional dividends going ex in the period
+impl DividendRf for DividendSchedule {
+    fn discount(
+        &self,
+        initial_value: f64,
+        begin_date: DateTime<Utc>,
+        end_date: DateTime<Utc>,
+        discount_rate: f64,
+    ) -> f64 {
+        let delta_t = get_duration_in_years(begin_date, end_date);
+        let outstanding = self.outstanding(end_date, delta_t);
+        (initial_value - present_value_of_cash(&outstanding, discount_rate))
+            * proportional_factor(&outstanding)
+    }
+}
+
+impl DividendSchedule {
+    // Dividends going ex after the valuation time and on or before the expiry. Ex-dates are held
+    // relative to the expiry so that time shocks, which shorten `delta_t`, roll the valuation
+    // time forward past any dividends that have gone ex
+    pub fn outstanding(&self, expiry: DateTime<Utc>, delta_t: f64) -> Vec<OutstandingDividend> {
+        self.dividends
+            .iter()
+            .filter(|dividend| dividend.ex_date <= expiry)
+            .map(|dividend| OutstandingDividend {
+                time: delta_t - get_duration_in_years(dividend.ex_date, expiry),
+                payment: dividend.payment.clone(),
+            })
+            .filter(|dividend| dividend.time > 0.0)
+            .collect()
+    }
+}
+
 impl IdentifiableRiskFactor for AnnualisedDividendRate {
     fn id(&self) -> &Symbol {
         &self.symbol
     }
 }
 
+impl IdentifiableRiskFactor for DividendSchedule {
+    fn id(&self) -> &Symbol {
+        &self.symbol
+    }
+}
+
 #[derive(Clone)]
 pub enum Dividend {
     AnnualisedRate(AnnualisedDividendRate),
-    Schedule,
+    Schedule(DividendSchedule),
+}
+
+impl Dividend {
+    pub fn annualised_rate(&self) -> f64 {
+        match &self {
+            Dividend::AnnualisedRate(adr) => adr.rate(),
+            Dividend::Schedule(_) => 0.0,
+        }
+    }
+    pub fn outstanding(&self, expiry: DateTime<Utc>, delta_t: f64) -> Vec<OutstandingDividend> {
+        match &self {
+            Dividend::AnnualisedRate(_) => vec![],
+            Dividend::Schedule(schedule) => schedule.outstanding(expiry, delta_t),
+        }
+    }
 }
 
 impl DividendRf for Dividend {
@@ -58,10 +170,15 @@ impl DividendRf for Dividend {
         initial_value: f64,
         begin_date: DateTime<Utc>,
         end_date: DateTime<Utc>,
+        discount_rate: f64,
     ) -> f64 {
         match &self {
-            Dividend::AnnualisedRate(adr) => adr.discount(initial_value, begin_date, end_date),
-            Dividend::Schedule => panic!("Dividend schedules are not implement yet"),
+            Dividend::AnnualisedRate(adr) => {
+                adr.discount(initial_value, begin_date, end_date, discount_rate)
+            }
+            Dividend::Schedule(schedule) => {
+                schedule.discount(initial_value, begin_date, end_date, discount_rate)
+            }
         }
     }
 }
@@ -70,7 +187,27 @@ impl IdentifiableRiskFactor for Dividend {
     fn id(&self) -> &Symbol {
         match &self {
             Dividend::AnnualisedRate(adr) => adr.id(),
-            Dividend::Schedule => panic!("Dividend schedules are not implement yet"),
+            Dividend::Schedule(schedule) => schedule.id(),
+        }
+    }
+}
+
+impl ApplyShock<Dividend> for DividendShock {
+    fn apply(&self, applicant: &mut Dividend) {
+        if applicant.id() != self.risk_factor() {
+            return;
+        }
+        match applicant {
+            Dividend::AnnualisedRate(adr) => self.apply(&mut adr.rate),
+            Dividend::Schedule(schedule) => {
+                schedule
+                    .dividends
+                    .iter_mut()
+                    .for_each(|dividend| match &mut dividend.payment {
+                        DividendPayment::Cash(amount) => self.apply(amount),
+                        DividendPayment::Proportional(fraction) => self.apply(fraction),
+                    })
+            }
         }
     }
 }
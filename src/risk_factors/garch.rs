@@ -0,0 +1,200 @@
+use super::time_series::ObservationFrequency;
+use super::volatility::VolatilityRf;
+use super::IdentifiableRiskFactor;
+
+use crate::result::{PricerError, PricerResult};
+use crate::shock::{ApplyShock, VolatilityShock};
+use crate::symbol::Symbol;
+use crate::utils::optimise::nelder_mead;
+
+use statrs::statistics::Statistics;
+
+// Sources of equations:
+//  - Bollerslev (1986), Generalized autoregressive conditional heteroskedasticity
+//  - Glosten, Jagannathan & Runkle (1993), On the relation between the expected value and the
+//    volatility of the nominal excess return on stocks
+
+#[derive(Clone, Debug)]
+pub enum GarchModel {
+    Garch,
+    // Negative returns carry an extra `leverage` loading onto the next variance
+    GjrGarch,
+}
+
+// Per period variance dynamics,
+//   variance_t = omega + (alpha + leverage * 1{r_t-1 < 0}) * r_t-1^2 + beta * variance_t-1
+#[derive(Clone, Debug)]
+pub struct GarchParameters {
+    pub omega: f64,
+    pub alpha: f64,
+    pub beta: f64,
+    pub leverage: f64,
+}
+
+impl GarchParameters {
+    // Expected decay of a variance shock per period, assuming symmetric returns
+    pub fn persistence(&self) -> f64 {
+        self.alpha + self.beta + 0.5 * self.leverage
+    }
+    pub fn long_run_variance(&self) -> f64 {
+        self.omega / (1.0 - self.persistence())
+    }
+    fn is_admissible(&self) -> bool {
+        self.omega > 0.0
+            && self.alpha >= 0.0
+            && self.beta >= 0.0
+            && self.leverage >= 0.0
+            && self.persistence() < 1.0
+    }
+    fn next_variance(&self, variance: f64, log_return: f64) -> f64 {
+        let leverage = if log_return < 0.0 { self.leverage } else { 0.0 };
+        self.omega + (self.alpha + leverage) * log_return.powi(2) + self.beta * variance
+    }
+}
+
+fn invalid_fit_err(reason: &str) -> PricerError {
+    PricerError::new(format!("Unable to fit GARCH model, {}", reason), 11)
+}
+
+const MAX_ITERATIONS: usize = 5000;
+const TOLERANCE: f64 = 1e-10;
+
+// Gaussian negative log likelihood up to a constant, the variance recursion starts from the
+// sample variance. Returns the variance forecast for the period after the last return
+fn negative_log_likelihood(
+    parameters: &GarchParameters,
+    returns: &[f64],
+    sample_variance: f64,
+) -> (f64, f64) {
+    returns.iter().fold(
+        (0.0, sample_variance),
+        |(likelihood, variance), log_return| {
+            (
+                likelihood + variance.ln() + log_return.powi(2) / variance,
+                parameters.next_variance(variance, *log_return),
+            )
+        },
+    )
+}
+
+// Maximum likelihood fit over demeaned log returns
+pub fn fit_garch(returns: &[f64], model: &GarchModel) -> PricerResult<(GarchParameters, f64)> {
+    if returns.len() < 10 {
+        return Err(invalid_fit_err("at least 10 returns are required"));
+    }
+    let mean = returns.mean();
+    let returns: Vec<f64> = returns.iter().map(|r| r - mean).collect();
+    let sample_variance = returns.iter().map(|r| r.powi(2)).mean();
+    if sample_variance <= 0.0 {
+        return Err(invalid_fit_err("the returns have no variance"));
+    }
+    // omega is searched as a multiple of the sample variance to keep the simplex well scaled
+    let to_parameters = |point: &[f64]| GarchParameters {
+        omega: point[0] * sample_variance,
+        alpha: point[1],
+        beta: point[2],
+        leverage: match model {
+            GarchModel::Garch => 0.0,
+            GarchModel::GjrGarch => point[3],
+        },
+    };
+    let objective = |point: &[f64]| {
+        let parameters = to_parameters(point);
+        if !parameters.is_admissible() {
+            return f64::INFINITY;
+        }
+        negative_log_likelihood(&parameters, &returns, sample_variance).0
+    };
+    let (start, steps) = match model {
+        GarchModel::Garch => (vec![0.05, 0.05, 0.9], vec![0.02, 0.02, 0.03]),
+        GarchModel::GjrGarch => (vec![0.05, 0.03, 0.9, 0.04], vec![0.02, 0.02, 0.03, 0.02]),
+    };
+    let minimum = nelder_mead(objective, &start, &steps, TOLERANCE, MAX_ITERATIONS);
+    if !minimum.converged {
+        return Err(invalid_fit_err(&format!(
+            "the likelihood maximisation did not converge in {} iterations",
+            minimum.iterations
+        )));
+    }
+    let parameters = to_parameters(&minimum.point);
+    let (_, next_variance) = negative_log_likelihood(&parameters, &returns, sample_variance);
+    Ok((parameters, next_variance))
+}
+
+// Forecast volatility term structure from a fitted GARCH model. The volatility to a horizon is
+// the root mean of the expected per period variances out to it, annualised
+#[derive(Clone)]
+pub struct GarchVolatility {
+    symbol: Symbol,
+    parameters: GarchParameters,
+    // Annualised volatilities for the next period and in the long run
+    spot_volatility: f64,
+    long_run_volatility: f64,
+    periods_per_year: f64,
+}
+
+impl GarchVolatility {
+    pub fn fit(
+        symbol: Symbol,
+        closes: &[f64],
+        frequency: ObservationFrequency,
+        model: GarchModel,
+    ) -> PricerResult<GarchVolatility> {
+        if closes.iter().any(|close| *close <= 0.0) {
+            return Err(invalid_fit_err("prices must be positive"));
+        }
+        let returns: Vec<f64> = closes
+            .windows(2)
+            .map(|pair| (pair[1] / pair[0]).ln())
+            .collect();
+        let (parameters, next_variance) = fit_garch(&returns, &model)?;
+        let periods_per_year = frequency.periods_per_year();
+        Ok(GarchVolatility {
+            symbol,
+            spot_volatility: (next_variance * periods_per_year).sqrt(),
+            long_run_volatility: (parameters.long_run_variance() * periods_per_year).sqrt(),
+            parameters,
+            periods_per_year,
+        })
+    }
+
+    pub fn parameters(&self) -> &GarchParameters {
+        &self.parameters
+    }
+    pub fn long_run_volatility(&self) -> f64 {
+        self.long_run_volatility
+    }
+}
+
+impl VolatilityRf for GarchVolatility {
+    // The volatility over the next observation period
+    fn volatility(&self) -> f64 {
+        self.spot_volatility
+    }
+    fn volatility_to(&self, delta_t: f64) -> f64 {
+        let periods = delta_t * self.periods_per_year;
+        let persistence = self.parameters.persistence();
+        if periods <= 1.0 || persistence <= 0.0 {
+            return self.spot_volatility;
+        }
+        let long_run_variance = self.long_run_volatility.powi(2);
+        let excess_variance = self.spot_volatility.powi(2) - long_run_variance;
+        let decayed = (1.0 - persistence.powf(periods)) / ((1.0 - persistence) * periods);
+        (long_run_variance + excess_variance * decayed).sqrt()
+    }
+}
+
+impl IdentifiableRiskFactor for GarchVolatility {
+    fn id(&self) -> &Symbol {
+        &self.symbol
+    }
+}
+
+// Shifts both ends of the term structure, so an absolute shock moves every horizon by about the
+// same amount
+impl ApplyShock<GarchVolatility> for VolatilityShock {
+    fn apply(&self, applicant: &mut GarchVolatility) {
+        self.apply(&mut applicant.spot_volatility);
+        self.apply(&mut applicant.long_run_volatility);
+    }
+}
@@ -1,15 +1,26 @@
+pub mod borrow;
 pub mod discount;
 pub mod dividend;
+pub mod garch;
 pub mod price;
+pub mod time_series;
 pub mod volatility;
+pub mod yield_curve;
 
+#[cfg(test)]
+mod test;
+
+use crate::result::PricerResult;
 use crate::symbol::Symbol;
 
+use borrow::BorrowRate;
 use discount::DiscountFactor;
 use dividend::Dividend;
 use price::Price;
 use volatility::Volatility;
 
+use chrono::{DateTime, Utc};
+
 pub trait IdentifiableRiskFactor {
     fn id(&self) -> &Symbol;
 }
@@ -20,4 +31,30 @@ pub struct RiskFactors {
     pub volatility_sensitivities: Vec<Volatility>,
     pub discount_factors: Vec<DiscountFactor>,
     pub dividend_sensitivities: Vec<Dividend>,
+    pub borrow_sensitivities: Vec<BorrowRate>,
+}
+
+impl RiskFactors {
+    // Restricts historic risk factors to what was observed by the valuation time
+    pub fn as_of(&self, valuation_time: DateTime<Utc>) -> PricerResult<RiskFactors> {
+        Ok(RiskFactors {
+            price_sensitivities: self
+                .price_sensitivities
+                .iter()
+                .map(|price| price.as_of(valuation_time))
+                .collect::<PricerResult<_>>()?,
+            volatility_sensitivities: self
+                .volatility_sensitivities
+                .iter()
+                .map(|volatility| volatility.as_of(valuation_time))
+                .collect::<PricerResult<_>>()?,
+            discount_factors: self
+                .discount_factors
+                .iter()
+                .map(|discount| discount.as_of(valuation_time))
+                .collect::<PricerResult<_>>()?,
+            dividend_sensitivities: self.dividend_sensitivities.clone(),
+            borrow_sensitivities: self.borrow_sensitivities.clone(),
+        })
+    }
 }
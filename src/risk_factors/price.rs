@@ -1,9 +1,11 @@
+use super::time_series::TimeSeries;
 use super::IdentifiableRiskFactor;
 
+use crate::result::{PricerError, PricerResult};
 use crate::shock::{ApplyShock, PriceShock};
 use crate::symbol::Symbol;
 
-use statrs::statistics::Statistics;
+use chrono::{DateTime, Utc};
 
 #[derive(Clone)]
 pub struct PriceTick {
@@ -17,18 +19,32 @@ impl PriceTick {
     }
 }
 
+// Spot is the latest observation
 #[derive(Clone)]
 pub struct HistoricPrices {
     symbol: Symbol,
-    historic_daily_average: Vec<f64>,
+    prices: TimeSeries<f64>,
+}
+
+fn no_prices_err(symbol: &Symbol) -> PricerError {
+    PricerError::new(format!("No historic prices observed for {}", symbol), 12)
 }
 
 impl HistoricPrices {
-    fn new(symbol: Symbol, historic_daily_average: Vec<f64>) -> HistoricPrices {
-        HistoricPrices {
-            symbol,
-            historic_daily_average,
+    pub fn new(symbol: Symbol, prices: TimeSeries<f64>) -> PricerResult<HistoricPrices> {
+        if prices.is_empty() {
+            return Err(no_prices_err(&symbol));
         }
+        Ok(HistoricPrices { symbol, prices })
+    }
+
+    pub fn prices(&self) -> &TimeSeries<f64> {
+        &self.prices
+    }
+
+    // Drops observations after the valuation time, so spot is the last price known at it
+    pub fn as_of(&self, valuation_time: DateTime<Utc>) -> PricerResult<HistoricPrices> {
+        HistoricPrices::new(self.symbol.clone(), self.prices.as_of(valuation_time, None))
     }
 }
 
@@ -56,7 +72,7 @@ impl PriceRf for PriceTick {
 
 impl PriceRf for HistoricPrices {
     fn price(&self) -> f64 {
-        self.historic_daily_average.iter().mean()
+        self.prices.last().map_or(f64::NAN, |(_, price)| *price)
     }
 }
 
@@ -75,6 +91,15 @@ impl PriceRf for Price {
     }
 }
 
+impl Price {
+    pub fn as_of(&self, valuation_time: DateTime<Utc>) -> PricerResult<Price> {
+        match self {
+            Price::HistoricPrices(hp) => hp.as_of(valuation_time).map(Price::HistoricPrices),
+            _ => Ok(self.clone()),
+        }
+    }
+}
+
 impl IdentifiableRiskFactor for Price {
     fn id(&self) -> &Symbol {
         match &self {
@@ -91,8 +116,12 @@ impl ApplyShock<Price> for PriceShock {
         }
         match applicant {
             Price::PriceTick(pt) => self.apply(&mut pt.price),
-            // Uncertain how to model shocking historical price data
-            Price::HistoricPrices(_) => {}
+            // Only spot moves, the history before it stands
+            Price::HistoricPrices(hp) => {
+                if let Some(price) = hp.prices.last_value_mut() {
+                    self.apply(price)
+                }
+            }
         }
     }
 }
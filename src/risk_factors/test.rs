@@ -0,0 +1,617 @@
+use super::discount::{DiscountFactor, DiscountRf, HistoricReturnSeries};
+use super::garch::{fit_garch, GarchModel, GarchParameters, GarchVolatility};
+use super::price::{HistoricPrices, Price, PriceRf};
+use super::time_series::{GapHandling, Observation, ObservationFrequency, ReturnKind, TimeSeries};
+use super::volatility::{
+    HistoricVolatility, ImpliedVolatility, PriceBar, Volatility, VolatilityEstimator, VolatilityRf,
+};
+use super::yield_curve::{CurveInstrument, CurveInterpolation, YieldCurve};
+
+use crate::black_scholes::BlackScholes;
+use crate::greeks::FiniteDifferenceGreeks;
+use crate::option::FinancialOption;
+use crate::result::PricerResult;
+use crate::risk_factors::discount::rfr_discount;
+use crate::symbol::Symbol;
+use crate::utils::date::get_duration_in_years;
+use crate::utils::test_utils::{get_test_call, is_close};
+use crate::Priceable;
+
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use statrs::distribution::Normal;
+
+fn curve_instruments() -> Vec<CurveInstrument> {
+    vec![
+        CurveInstrument::Deposit {
+            maturity: 0.25,
+            rate: 0.040,
+        },
+        CurveInstrument::ForwardRateAgreement {
+            start: 0.25,
+            end: 0.5,
+            rate: 0.043,
+        },
+        CurveInstrument::Future {
+            start: 0.5,
+            end: 0.75,
+            price: 95.5,
+        },
+        CurveInstrument::Swap {
+            maturity: 2.0,
+            rate: 0.047,
+            frequency: 2,
+        },
+        CurveInstrument::Swap {
+            maturity: 5.0,
+            rate: 0.045,
+            frequency: 2,
+        },
+    ]
+}
+
+fn interpolations() -> [CurveInterpolation; 3] {
+    [
+        CurveInterpolation::LinearZero,
+        CurveInterpolation::LogLinearDiscount,
+        CurveInterpolation::MonotoneConvex,
+    ]
+}
+
+#[test]
+fn bootstrapped_curve_reprices_instruments() -> PricerResult<()> {
+    for interpolation in interpolations() {
+        let curve = YieldCurve::bootstrap("USD".into(), curve_instruments(), interpolation)?;
+        let deposit = 1.0 / (1.0 + 0.040 * 0.25);
+        assert!((curve.discount_factor(0.25) - deposit).abs() < 1e-10);
+        let fra = curve.discount_factor(0.25) / (1.0 + 0.043 * 0.25);
+        assert!((curve.discount_factor(0.5) - fra).abs() < 1e-10);
+        let future = curve.discount_factor(0.5) / (1.0 + 0.045 * 0.25);
+        assert!((curve.discount_factor(0.75) - future).abs() < 1e-10);
+        let annuity: f64 = (1..=10)
+            .map(|payment| 0.5 * curve.discount_factor(payment as f64 * 0.5))
+            .sum();
+        let par_rate = (1.0 - curve.discount_factor(5.0)) / annuity;
+        assert!(
+            (par_rate - 0.045).abs() < 1e-10,
+            "Five year swap reprices to {}",
+            par_rate
+        );
+    }
+    Ok(())
+}
+
+#[test]
+fn linear_zero_interpolates_zero_rates() -> PricerResult<()> {
+    let curve = YieldCurve::new(
+        "USD".into(),
+        vec![1.0, 2.0],
+        vec![0.02, 0.04],
+        CurveInterpolation::LinearZero,
+    )?;
+    assert!((curve.zero_rate(1.5) - 0.03).abs() < 1e-12);
+    assert!((curve.zero_rate(0.5) - 0.02).abs() < 1e-12);
+    assert!((curve.zero_rate(3.0) - 0.04).abs() < 1e-12);
+    Ok(())
+}
+
+#[test]
+fn log_linear_discount_holds_forwards_flat_between_pillars() -> PricerResult<()> {
+    let curve = YieldCurve::new(
+        "USD".into(),
+        vec![1.0, 2.0],
+        vec![0.02, 0.04],
+        CurveInterpolation::LogLinearDiscount,
+    )?;
+    let forward = 0.06;
+    assert!((curve.forward_rate(1.1, 1.3) - forward).abs() < 1e-12);
+    assert!((curve.forward_rate(1.6, 1.9) - forward).abs() < 1e-12);
+    assert!((curve.forward_rate(2.5, 3.0) - forward).abs() < 1e-12);
+    Ok(())
+}
+
+#[test]
+fn monotone_convex_preserves_discrete_forwards() -> PricerResult<()> {
+    let curve = YieldCurve::new(
+        "USD".into(),
+        vec![0.5, 1.0, 2.0, 5.0],
+        vec![0.03, 0.035, 0.04, 0.038],
+        CurveInterpolation::MonotoneConvex,
+    )?;
+    for (pillar, zero_rate) in curve.pillars().iter().zip(curve.zero_rates()) {
+        assert!((curve.zero_rate(*pillar) - zero_rate).abs() < 1e-12);
+    }
+    // Instantaneous forwards are continuous across a pillar
+    let (below, above) = (
+        curve.forward_rate(0.999, 1.0),
+        curve.forward_rate(1.0, 1.001),
+    );
+    assert!((below - above).abs() < 1e-4, "{} vs {}", below, above);
+    Ok(())
+}
+
+#[test]
+fn unordered_or_missing_pillars_are_rejected() {
+    assert!(YieldCurve::new(
+        "USD".into(),
+        vec![1.0, 1.0],
+        vec![0.02, 0.03],
+        CurveInterpolation::LinearZero
+    )
+    .is_err());
+    assert!(YieldCurve::new("USD".into(), vec![], vec![], CurveInterpolation::LinearZero).is_err());
+}
+
+#[test]
+fn black_scholes_uses_curve_rate_at_expiry() -> PricerResult<()> {
+    let (call, valuation_time, mut risk_factors) = get_test_call();
+    let curve = YieldCurve::new(
+        Symbol::from("US Treasury 3M"),
+        vec![0.1, 0.5, 1.0],
+        vec![0.02, 0.04, 0.06],
+        CurveInterpolation::LinearZero,
+    )?;
+    let delta_t = get_duration_in_years(valuation_time, call.expiry());
+    let rate_at_expiry = curve.zero_rate(delta_t);
+    risk_factors.discount_factors = vec![DiscountFactor::YieldCurve(curve)];
+    let curve_value = call.value_black_scholes(valuation_time, risk_factors.clone(), vec![])?;
+
+    risk_factors.discount_factors = vec![rfr_discount("US Treasury 3M".into(), rate_at_expiry)];
+    let flat_value = call.value_black_scholes(valuation_time, risk_factors, vec![])?;
+    assert!(
+        is_close(curve_value, flat_value, 1e-9),
+        "Curve valuation ({}) differs from flat valuation at the expiry rate ({})",
+        curve_value,
+        flat_value
+    );
+    Ok(())
+}
+
+// Monday 2nd January 2023
+fn series_start() -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(2023, 1, 2, 16, 0, 0).unwrap()
+}
+
+// Observations `spacing` apart from the series start
+fn dated<T: Observation>(values: Vec<T>, spacing: Duration) -> TimeSeries<T> {
+    let observations = values
+        .into_iter()
+        .enumerate()
+        .map(|(i, value)| (series_start() + spacing * i as i32, value))
+        .collect();
+    TimeSeries::new(observations).unwrap()
+}
+
+// Daily bars of a driftless geometric Brownian motion observed at `steps` points intraday
+fn simulated_bars(volatility: f64, days: usize, steps: usize) -> Vec<PriceBar> {
+    let mut rng = StdRng::seed_from_u64(7);
+    let gaussian = Normal::new(0.0, 1.0).unwrap();
+    let step_volatility = volatility / (252.0 * steps as f64).sqrt();
+    let mut price = 100.0;
+    (0..days)
+        .map(|_| {
+            let open = price;
+            let (mut high, mut low) = (price, price);
+            for _ in 0..steps {
+                let sample: f64 = rng.sample(gaussian);
+                price *= (step_volatility * sample - 0.5 * step_volatility.powi(2)).exp();
+                high = high.max(price);
+                low = low.min(price);
+            }
+            PriceBar {
+                open,
+                high,
+                low,
+                close: price,
+            }
+        })
+        .collect()
+}
+
+#[test]
+fn historic_estimators_recover_simulated_volatility() -> PricerResult<()> {
+    let bars = simulated_bars(0.25, 1000, 200);
+    let estimators = [
+        VolatilityEstimator::CloseToClose,
+        VolatilityEstimator::Parkinson,
+        VolatilityEstimator::GarmanKlass,
+        VolatilityEstimator::RogersSatchell,
+        VolatilityEstimator::YangZhang,
+    ];
+    for estimator in estimators {
+        let historic = HistoricVolatility::new(
+            "AAPL".into(),
+            dated(bars.clone(), Duration::days(1)),
+            ObservationFrequency::Daily,
+            estimator.clone(),
+        )?;
+        assert!(
+            is_close(historic.volatility(), 0.25, 0.1),
+            "{:?} estimated volatility {}",
+            estimator,
+            historic.volatility()
+        );
+    }
+    Ok(())
+}
+
+#[test]
+fn close_to_close_volatility_is_annualised() -> PricerResult<()> {
+    // Alternating one percent moves up and down, sampled weekly
+    let closes: Vec<f64> = (0..=52)
+        .map(|week| 100.0 * if week % 2 == 0 { 1.0 } else { 0.01f64.exp() })
+        .collect();
+    let historic = HistoricVolatility::from_closes(
+        "AAPL".into(),
+        dated(closes, Duration::weeks(1)),
+        ObservationFrequency::Weekly,
+        VolatilityEstimator::CloseToClose,
+    )?;
+    let expected = (0.0001 * 52.0 / 51.0 * 52.0f64).sqrt();
+    assert!((historic.volatility() - expected).abs() < 1e-12);
+    Ok(())
+}
+
+#[test]
+fn ewma_of_constant_moves_is_the_move_size() -> PricerResult<()> {
+    let closes: Vec<f64> = (0..100)
+        .map(|day| 100.0 * (0.02 * day as f64).exp())
+        .collect();
+    let historic = HistoricVolatility::from_closes(
+        "AAPL".into(),
+        dated(closes, Duration::days(1)),
+        ObservationFrequency::Daily,
+        VolatilityEstimator::Ewma { decay: 0.94 },
+    )?;
+    assert!((historic.volatility() - 0.02 * 252f64.sqrt()).abs() < 1e-12);
+    Ok(())
+}
+
+#[test]
+fn short_or_non_positive_histories_are_rejected() {
+    let estimator = || VolatilityEstimator::CloseToClose;
+    assert!(HistoricVolatility::from_closes(
+        "AAPL".into(),
+        dated(vec![100.0, 101.0], Duration::days(1)),
+        ObservationFrequency::Daily,
+        estimator()
+    )
+    .is_err());
+    assert!(HistoricVolatility::from_closes(
+        "AAPL".into(),
+        dated(vec![100.0, 0.0, 101.0], Duration::days(1)),
+        ObservationFrequency::Daily,
+        estimator()
+    )
+    .is_err());
+}
+
+// Log returns drawn from the variance recursion of `parameters`, started at the long run variance
+fn simulated_garch_returns(parameters: &GarchParameters, periods: usize) -> Vec<f64> {
+    let mut rng = StdRng::seed_from_u64(11);
+    let gaussian = Normal::new(0.0, 1.0).unwrap();
+    let mut variance = parameters.long_run_variance();
+    (0..periods)
+        .map(|_| {
+            let sample: f64 = rng.sample(gaussian);
+            let log_return = variance.sqrt() * sample;
+            let leverage = if log_return < 0.0 {
+                parameters.leverage
+            } else {
+                0.0
+            };
+            variance = parameters.omega
+                + (parameters.alpha + leverage) * log_return.powi(2)
+                + parameters.beta * variance;
+            log_return
+        })
+        .collect()
+}
+
+#[test]
+fn garch_fit_recovers_simulated_parameters() -> PricerResult<()> {
+    let true_parameters = GarchParameters {
+        omega: 2e-6,
+        alpha: 0.08,
+        beta: 0.9,
+        leverage: 0.0,
+    };
+    let returns = simulated_garch_returns(&true_parameters, 5000);
+    let (fitted, _) = fit_garch(&returns, &GarchModel::Garch)?;
+    assert!((fitted.alpha - 0.08).abs() < 0.03, "alpha {}", fitted.alpha);
+    assert!((fitted.beta - 0.9).abs() < 0.04, "beta {}", fitted.beta);
+    assert_eq!(fitted.leverage, 0.0);
+    assert!(fitted.persistence() < 1.0);
+    Ok(())
+}
+
+#[test]
+fn gjr_garch_fit_finds_leverage() -> PricerResult<()> {
+    let true_parameters = GarchParameters {
+        omega: 2e-6,
+        alpha: 0.02,
+        beta: 0.88,
+        leverage: 0.12,
+    };
+    let returns = simulated_garch_returns(&true_parameters, 5000);
+    let (fitted, _) = fit_garch(&returns, &GarchModel::GjrGarch)?;
+    assert!(fitted.leverage > 0.05, "leverage {}", fitted.leverage);
+    assert!(fitted.leverage > fitted.alpha);
+    Ok(())
+}
+
+#[test]
+fn garch_term_structure_reverts_to_long_run_volatility() -> PricerResult<()> {
+    let true_parameters = GarchParameters {
+        omega: 2e-6,
+        alpha: 0.08,
+        beta: 0.9,
+        leverage: 0.0,
+    };
+    let returns = simulated_garch_returns(&true_parameters, 2000);
+    let closes: Vec<f64> = std::iter::once(100.0)
+        .chain(returns.iter().scan(100.0, |price, log_return| {
+            *price *= log_return.exp();
+            Some(*price)
+        }))
+        .collect();
+    let garch = GarchVolatility::fit(
+        "AAPL".into(),
+        &closes,
+        ObservationFrequency::Daily,
+        GarchModel::Garch,
+    )?;
+    assert_eq!(garch.volatility_to(1.0 / 252.0), garch.volatility());
+    let spot_gap = (garch.volatility() - garch.long_run_volatility()).abs();
+    let one_year_gap = (garch.volatility_to(1.0) - garch.long_run_volatility()).abs();
+    let far_gap = (garch.volatility_to(50.0) - garch.long_run_volatility()).abs();
+    assert!(one_year_gap <= spot_gap);
+    assert!(far_gap < 0.1 * spot_gap.max(1e-4), "far gap {}", far_gap);
+    Ok(())
+}
+
+#[test]
+fn black_scholes_uses_garch_volatility_to_expiry() -> PricerResult<()> {
+    let (call, valuation_time, mut risk_factors) = get_test_call();
+    let returns = simulated_garch_returns(
+        &GarchParameters {
+            omega: 2e-6,
+            alpha: 0.08,
+            beta: 0.9,
+            leverage: 0.0,
+        },
+        1000,
+    );
+    let closes: Vec<f64> = std::iter::once(42.0)
+        .chain(returns.iter().scan(42.0, |price, log_return| {
+            *price *= log_return.exp();
+            Some(*price)
+        }))
+        .collect();
+    let garch = GarchVolatility::fit(
+        "AAPL".into(),
+        &closes,
+        ObservationFrequency::Daily,
+        GarchModel::Garch,
+    )?;
+    let delta_t = get_duration_in_years(valuation_time, call.expiry());
+    let volatility_to_expiry = garch.volatility_to(delta_t);
+    risk_factors.volatility_sensitivities = vec![Volatility::GarchVolatility(garch)];
+    let garch_value = call.value_black_scholes(valuation_time, risk_factors.clone(), vec![])?;
+
+    risk_factors.volatility_sensitivities = vec![Volatility::ImpliedVolatility(
+        ImpliedVolatility::new("AAPL".into(), volatility_to_expiry),
+    )];
+    let flat_value = call.value_black_scholes(valuation_time, risk_factors, vec![])?;
+    assert!(
+        is_close(garch_value, flat_value, 1e-9),
+        "GARCH valuation ({}) differs from flat valuation at the expiry volatility ({})",
+        garch_value,
+        flat_value
+    );
+    Ok(())
+}
+
+#[test]
+fn time_series_is_ordered_with_unique_timestamps() {
+    let start = series_start();
+    let series = TimeSeries::new(vec![
+        (start + Duration::days(2), 3.0),
+        (start, 1.0),
+        (start + Duration::days(1), 2.0),
+    ])
+    .unwrap();
+    assert_eq!(series.values(), &[1.0, 2.0, 3.0]);
+    assert_eq!(series.last(), Some((start + Duration::days(2), &3.0)));
+    assert!(TimeSeries::new(vec![(start, 1.0), (start, 2.0)]).is_err_and(|error| error.code == 18));
+}
+
+#[test]
+fn historic_price_is_the_last_observation_at_valuation() -> PricerResult<()> {
+    let prices = HistoricPrices::new(
+        "AAPL".into(),
+        dated(vec![40.0, 44.0, 42.0, 50.0], Duration::days(1)),
+    )?;
+    assert_eq!(prices.price(), 50.0);
+    let valuation_time = series_start() + Duration::days(2) + Duration::hours(1);
+    assert_eq!(prices.as_of(valuation_time)?.price(), 42.0);
+    assert!(prices
+        .as_of(series_start() - Duration::days(1))
+        .is_err_and(|error| error.code == 12));
+    Ok(())
+}
+
+#[test]
+fn volatility_shocks_move_the_historic_estimate() -> PricerResult<()> {
+    let (call, valuation_time, mut risk_factors) = get_test_call();
+    let closes: Vec<f64> = (0..60)
+        .map(|day| {
+            if day % 2 == 0 {
+                40.0
+            } else {
+                40.0 * 0.01f64.exp()
+            }
+        })
+        .collect();
+    let historic = HistoricVolatility::from_closes(
+        "AAPL".into(),
+        dated(closes, Duration::days(1)),
+        ObservationFrequency::Daily,
+        VolatilityEstimator::CloseToClose,
+    )?;
+    let volatility = historic.as_of(valuation_time)?.volatility();
+    let priceable = Priceable::BlackScholes(&call);
+    risk_factors.volatility_sensitivities = vec![Volatility::Historic(historic)];
+    let historic_vega = priceable.vega_fd(valuation_time, risk_factors.clone())?;
+    risk_factors.volatility_sensitivities = vec![Volatility::ImpliedVolatility(
+        ImpliedVolatility::new("AAPL".into(), volatility),
+    )];
+    let implied_vega = priceable.vega_fd(valuation_time, risk_factors)?;
+    assert!(historic_vega > 0.0);
+    assert!(is_close(historic_vega, implied_vega, 1e-9));
+    Ok(())
+}
+
+#[test]
+fn black_scholes_prices_off_the_last_historic_price() -> PricerResult<()> {
+    let (call, valuation_time, mut risk_factors) = get_test_call();
+    let observations = vec![
+        (valuation_time - Duration::days(2), 40.0),
+        (valuation_time - Duration::days(1), 42.0),
+        (valuation_time + Duration::days(1), 60.0),
+    ];
+    let prices = HistoricPrices::new("AAPL".into(), TimeSeries::new(observations)?)?;
+    let tick_value = call.value_black_scholes(valuation_time, risk_factors.clone(), vec![])?;
+    risk_factors.price_sensitivities = vec![Price::HistoricPrices(prices)];
+    let historic_value = call.value_black_scholes(valuation_time, risk_factors, vec![])?;
+    assert!(is_close(historic_value, tick_value, 1e-12));
+    Ok(())
+}
+
+#[test]
+fn log_and_simple_returns() {
+    let series = dated(vec![100.0, 110.0, 99.0], Duration::days(1));
+    let simple = series.returns(&ReturnKind::Simple);
+    let log = series.returns(&ReturnKind::Log);
+    assert_eq!(simple.times(), &series.times()[1..]);
+    assert!(is_close(simple.values()[0], 0.1, 1e-12));
+    assert!(is_close(simple.values()[1], -0.1, 1e-12));
+    assert!(is_close(log.values()[0], 1.1f64.ln(), 1e-12));
+    assert!(is_close(log.values()[1], 0.9f64.ln(), 1e-12));
+}
+
+#[test]
+fn weekly_resampling_keeps_the_last_close_and_the_weeks_range() {
+    // Two weeks of weekday closes
+    let closes: Vec<(DateTime<Utc>, f64)> = [0, 1, 2, 3, 4, 7, 8, 9, 10, 11]
+        .iter()
+        .map(|day| (series_start() + Duration::days(*day), 100.0 + *day as f64))
+        .collect();
+    let weekly = TimeSeries::new(closes.clone())
+        .unwrap()
+        .resample(&ObservationFrequency::Weekly, &GapHandling::Skip);
+    assert_eq!(weekly.values(), &[104.0, 111.0]);
+    assert_eq!(weekly.times(), &[closes[4].0, closes[9].0]);
+
+    let bars = TimeSeries::new(
+        closes
+            .iter()
+            .map(|(time, close)| {
+                let bar = PriceBar {
+                    open: close - 0.5,
+                    high: close + 1.0,
+                    low: close - 1.0,
+                    close: *close,
+                };
+                (*time, bar)
+            })
+            .collect(),
+    )
+    .unwrap()
+    .resample(&ObservationFrequency::Weekly, &GapHandling::Skip);
+    let first_week = &bars.values()[0];
+    assert_eq!(
+        (
+            first_week.open,
+            first_week.high,
+            first_week.low,
+            first_week.close
+        ),
+        (99.5, 105.0, 99.0, 104.0)
+    );
+}
+
+#[test]
+fn forward_fill_covers_missing_weekdays_only() {
+    // Thursday, then the following Tuesday
+    let series = TimeSeries::new(vec![
+        (series_start() + Duration::days(3), 1.0),
+        (series_start() + Duration::days(8), 2.0),
+    ])
+    .unwrap();
+    let skipped = series.resample(&ObservationFrequency::Daily, &GapHandling::Skip);
+    assert_eq!(skipped.len(), 2);
+    let filled = series.resample(&ObservationFrequency::Daily, &GapHandling::ForwardFill);
+    assert_eq!(filled.values(), &[1.0, 1.0, 1.0, 2.0]);
+    assert_eq!(
+        filled.times()[1..3],
+        [
+            series_start() + Duration::days(4),
+            series_start() + Duration::days(7)
+        ]
+    );
+}
+
+#[test]
+fn look_back_window_limits_the_historic_volatility() -> PricerResult<()> {
+    // A calm year followed by a volatile quarter, alternating moves make the estimate exact
+    let calm = (0..250).map(|day| if day % 2 == 0 { 0.0 } else { 0.005 });
+    let volatile = (0..60).map(|day| if day % 2 == 0 { 0.0 } else { 0.03 });
+    let closes: Vec<f64> = calm
+        .chain(volatile)
+        .map(|move_size| 100.0 * f64::exp(move_size))
+        .collect();
+    let series = dated(closes, Duration::days(1));
+    let valuation_time = series.last().unwrap().0;
+    let full = HistoricVolatility::from_closes(
+        "AAPL".into(),
+        series.clone(),
+        ObservationFrequency::Daily,
+        VolatilityEstimator::CloseToClose,
+    )?;
+    let recent = full.clone().with_look_back(Duration::days(30));
+    let recent_volatility = recent.as_of(valuation_time)?.volatility();
+    assert!(recent_volatility > full.as_of(valuation_time)?.volatility());
+    assert!(is_close(
+        recent_volatility,
+        0.03 * (252.0 * 30.0 / 29.0f64).sqrt(),
+        1e-9
+    ));
+
+    // Only two observations fall inside a two day window
+    assert!(full
+        .with_look_back(Duration::days(2))
+        .as_of(valuation_time)
+        .is_err());
+    Ok(())
+}
+
+#[test]
+fn historic_return_series_annualises_growth_to_valuation() -> PricerResult<()> {
+    let levels = dated(
+        vec![100.0, 100.0 * 0.05f64.exp(), 100.0 * 0.2f64.exp()],
+        Duration::days(365),
+    );
+    let series = HistoricReturnSeries::new("SPX".into(), levels)?;
+    assert!(is_close(series.rate(), 0.1, 1e-12));
+    let one_year_in = series_start() + Duration::days(400);
+    assert!(is_close(series.as_of(one_year_in)?.rate(), 0.05, 1e-12));
+    assert!(series
+        .as_of(series_start())
+        .is_err_and(|error| error.code == 19));
+    let discount = DiscountFactor::HistoricReturnSeries(series.with_look_back(Duration::days(400)));
+    let last_year = discount.as_of(series_start() + Duration::days(730))?;
+    assert!(is_close(last_year.rate(), 0.15, 1e-12));
+    Ok(())
+}
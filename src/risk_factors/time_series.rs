@@ -0,0 +1,216 @@
+use crate::result::{PricerError, PricerResult};
+use crate::utils::date::get_duration_in_years;
+
+use chrono::{DateTime, Datelike, Duration, Months, Utc, Weekday};
+
+fn invalid_series_err(reason: &str) -> PricerError {
+    PricerError::new(format!("Invalid time series, {}", reason), 18)
+}
+
+#[derive(Clone, Debug)]
+pub enum ObservationFrequency {
+    Daily,
+    Weekly,
+    Monthly,
+}
+
+impl ObservationFrequency {
+    pub fn periods_per_year(&self) -> f64 {
+        match self {
+            ObservationFrequency::Daily => 252.0,
+            ObservationFrequency::Weekly => 52.0,
+            ObservationFrequency::Monthly => 12.0,
+        }
+    }
+
+    // Index of the period containing `time`, consecutive periods differ by one
+    fn period(&self, time: DateTime<Utc>) -> i64 {
+        let date = time.date_naive();
+        match self {
+            ObservationFrequency::Daily => date.num_days_from_ce() as i64,
+            ObservationFrequency::Weekly => {
+                (date.num_days_from_ce() - date.weekday().num_days_from_monday() as i32) as i64 / 7
+            }
+            ObservationFrequency::Monthly => date.year() as i64 * 12 + date.month0() as i64,
+        }
+    }
+
+    fn shift(&self, time: DateTime<Utc>, periods: i64) -> DateTime<Utc> {
+        match self {
+            ObservationFrequency::Daily => time + Duration::days(periods),
+            ObservationFrequency::Weekly => time + Duration::weeks(periods),
+            ObservationFrequency::Monthly => time
+                .checked_add_months(Months::new(periods as u32))
+                .unwrap_or(time),
+        }
+    }
+
+    // Weekends are not trading days, so a daily series has no gap over them
+    fn is_trading_period(&self, time: DateTime<Utc>) -> bool {
+        match self {
+            ObservationFrequency::Daily => !matches!(time.weekday(), Weekday::Sat | Weekday::Sun),
+            _ => true,
+        }
+    }
+}
+
+// How resampling treats periods with no observation
+#[derive(Clone, Debug)]
+pub enum GapHandling {
+    // Leave the period out, the next return then spans the gap
+    Skip,
+    // Repeat the last observation, so the gap shows no movement
+    ForwardFill,
+}
+
+#[derive(Clone, Debug)]
+pub enum ReturnKind {
+    Log,
+    Simple,
+}
+
+// Values that a time series can resample
+pub trait Observation: Clone {
+    // Combines the observations within one period, in time order
+    fn aggregate(period: &[Self]) -> Self;
+    // Stands in for a period with no observation, following this one
+    fn carried_forward(&self) -> Self;
+}
+
+impl Observation for f64 {
+    fn aggregate(period: &[f64]) -> f64 {
+        period[period.len() - 1]
+    }
+    fn carried_forward(&self) -> f64 {
+        *self
+    }
+}
+
+// Observations ordered in time with at most one per timestamp
+#[derive(Clone, Debug)]
+pub struct TimeSeries<T> {
+    times: Vec<DateTime<Utc>>,
+    values: Vec<T>,
+}
+
+impl<T: Observation> TimeSeries<T> {
+    pub fn new(mut observations: Vec<(DateTime<Utc>, T)>) -> PricerResult<TimeSeries<T>> {
+        observations.sort_by_key(|(time, _)| *time);
+        if observations.windows(2).any(|pair| pair[0].0 == pair[1].0) {
+            return Err(invalid_series_err("timestamps must be unique"));
+        }
+        let (times, values) = observations.into_iter().unzip();
+        Ok(TimeSeries { times, values })
+    }
+
+    pub fn times(&self) -> &[DateTime<Utc>] {
+        &self.times
+    }
+    pub fn values(&self) -> &[T] {
+        &self.values
+    }
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+    pub fn first(&self) -> Option<(DateTime<Utc>, &T)> {
+        self.times.first().copied().zip(self.values.first())
+    }
+    pub fn last(&self) -> Option<(DateTime<Utc>, &T)> {
+        self.times.last().copied().zip(self.values.last())
+    }
+
+    pub fn last_value_mut(&mut self) -> Option<&mut T> {
+        self.values.last_mut()
+    }
+
+    pub fn map<U, F: Fn(&T) -> U>(&self, f: F) -> TimeSeries<U> {
+        TimeSeries {
+            times: self.times.clone(),
+            values: self.values.iter().map(f).collect(),
+        }
+    }
+
+    // Observations at or before the valuation time, and within `look_back` of it when given,
+    // so later data never leaks into a valuation
+    pub fn as_of(
+        &self,
+        valuation_time: DateTime<Utc>,
+        look_back: Option<Duration>,
+    ) -> TimeSeries<T> {
+        let start = look_back.map(|look_back| valuation_time - look_back);
+        let (times, values) = self
+            .times
+            .iter()
+            .zip(&self.values)
+            .filter(|(time, _)| {
+                **time <= valuation_time && start.is_none_or(|start| **time > start)
+            })
+            .map(|(time, value)| (*time, value.clone()))
+            .unzip();
+        TimeSeries { times, values }
+    }
+
+    // One observation per period, stamped with the time of the last observation in it
+    pub fn resample(&self, frequency: &ObservationFrequency, gaps: &GapHandling) -> TimeSeries<T> {
+        let mut resampled: TimeSeries<T> = TimeSeries {
+            times: vec![],
+            values: vec![],
+        };
+        let mut start = 0;
+        while start < self.len() {
+            let period = frequency.period(self.times[start]);
+            let end = start
+                + self.times[start..]
+                    .iter()
+                    .take_while(|time| frequency.period(**time) == period)
+                    .count();
+            if let (GapHandling::ForwardFill, Some((last_time, last_value))) =
+                (gaps, resampled.last())
+            {
+                let (last_time, last_value) = (last_time, last_value.carried_forward());
+                let missing = period - frequency.period(last_time);
+                for shift in 1..missing {
+                    let time = frequency.shift(last_time, shift);
+                    if frequency.is_trading_period(time) {
+                        resampled.times.push(time);
+                        resampled.values.push(last_value.clone());
+                    }
+                }
+            }
+            resampled.times.push(self.times[end - 1]);
+            resampled
+                .values
+                .push(T::aggregate(&self.values[start..end]));
+            start = end;
+        }
+        resampled
+    }
+}
+
+impl TimeSeries<f64> {
+    // Returns between consecutive observations, stamped at the end of each interval
+    pub fn returns(&self, kind: &ReturnKind) -> TimeSeries<f64> {
+        let values = self
+            .values
+            .windows(2)
+            .map(|pair| match kind {
+                ReturnKind::Log => (pair[1] / pair[0]).ln(),
+                ReturnKind::Simple => pair[1] / pair[0] - 1.0,
+            })
+            .collect();
+        TimeSeries {
+            times: self.times.iter().skip(1).copied().collect(),
+            values,
+        }
+    }
+
+    // Continuously compounded growth per year between the first and last observations
+    pub fn annualised_growth(&self) -> Option<f64> {
+        let ((start, first), (end, last)) = self.first().zip(self.last())?;
+        let years = get_duration_in_years(start, end);
+        (years > 0.0).then(|| (last / first).ln() / years)
+    }
+}
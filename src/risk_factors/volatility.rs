@@ -1,10 +1,22 @@
+use super::garch::GarchVolatility;
+use super::time_series::{GapHandling, Observation, ObservationFrequency, TimeSeries};
 use super::IdentifiableRiskFactor;
 
+use crate::result::{PricerError, PricerResult};
 use crate::shock::{ApplyShock, VolatilityShock};
 use crate::symbol::Symbol;
 
+use chrono::{DateTime, Duration, Utc};
 use statrs::statistics::Statistics;
 
+// Sources of equations:
+//  - Parkinson (1980), The extreme value method for estimating the variance of the rate of return
+//  - Garman & Klass (1980), On the estimation of security price volatilities from historical data
+//  - Rogers & Satchell (1991), Estimating variance from high, low and closing prices
+//  - Yang & Zhang (2000), Drift-independent volatility estimation based on high, low, open and
+//    close prices
+//  - J.P. Morgan (1996), RiskMetrics technical document
+
 #[derive(Clone)]
 pub struct ImpliedVolatility {
     symbol: Symbol,
@@ -17,10 +29,197 @@ impl ImpliedVolatility {
     }
 }
 
+// Open, high, low and close prices over one observation period
+#[derive(Clone, Debug)]
+pub struct PriceBar {
+    pub open: f64,
+    pub high: f64,
+    pub low: f64,
+    pub close: f64,
+}
+
+impl PriceBar {
+    fn log_high_low(&self) -> f64 {
+        (self.high / self.low).ln()
+    }
+    fn log_close_open(&self) -> f64 {
+        (self.close / self.open).ln()
+    }
+    fn rogers_satchell(&self) -> f64 {
+        (self.high / self.close).ln() * (self.high / self.open).ln()
+            + (self.low / self.close).ln() * (self.low / self.open).ln()
+    }
+}
+
+impl Observation for PriceBar {
+    fn aggregate(period: &[PriceBar]) -> PriceBar {
+        PriceBar {
+            open: period[0].open,
+            high: period.iter().map(|bar| bar.high).fold(f64::MIN, f64::max),
+            low: period.iter().map(|bar| bar.low).fold(f64::MAX, f64::min),
+            close: period[period.len() - 1].close,
+        }
+    }
+    fn carried_forward(&self) -> PriceBar {
+        PriceBar {
+            open: self.close,
+            high: self.close,
+            low: self.close,
+            close: self.close,
+        }
+    }
+}
+
+// The range based estimators need genuine open, high, low and close prices, with closes alone
+// they see no intraperiod movement
+#[derive(Clone, Debug)]
+pub enum VolatilityEstimator {
+    CloseToClose,
+    Parkinson,
+    GarmanKlass,
+    RogersSatchell,
+    YangZhang,
+    // Exponentially weighted squared returns, RiskMetrics uses a decay of 0.94 for daily data
+    Ewma { decay: f64 },
+}
+
+// Bars are resampled to the observation frequency, skipping periods without any
 #[derive(Clone)]
 pub struct HistoricVolatility {
     symbol: Symbol,
-    price_time_series: Vec<f64>,
+    bars: TimeSeries<PriceBar>,
+    frequency: ObservationFrequency,
+    estimator: VolatilityEstimator,
+    look_back: Option<Duration>,
+    // Where a shock has moved the estimate to
+    shocked_volatility: Option<f64>,
+}
+
+fn invalid_history_err(reason: &str) -> PricerError {
+    PricerError::new(
+        format!("Unable to estimate historic volatility, {}", reason),
+        10,
+    )
+}
+
+fn ensure_enough_observations(bars: &TimeSeries<PriceBar>) -> PricerResult<()> {
+    if bars.len() < 3 {
+        return Err(invalid_history_err("at least 3 observations are required"));
+    }
+    Ok(())
+}
+
+impl HistoricVolatility {
+    pub fn new(
+        symbol: Symbol,
+        bars: TimeSeries<PriceBar>,
+        frequency: ObservationFrequency,
+        estimator: VolatilityEstimator,
+    ) -> PricerResult<HistoricVolatility> {
+        let positive = |bar: &PriceBar| {
+            [bar.open, bar.high, bar.low, bar.close]
+                .iter()
+                .all(|price| *price > 0.0)
+        };
+        if !bars.values().iter().all(positive) {
+            return Err(invalid_history_err("prices must be positive"));
+        }
+        if let VolatilityEstimator::Ewma { decay } = estimator {
+            if !(0.0..1.0).contains(&decay) {
+                return Err(invalid_history_err("the EWMA decay must lie in [0, 1)"));
+            }
+        }
+        let bars = bars.resample(&frequency, &GapHandling::Skip);
+        ensure_enough_observations(&bars)?;
+        Ok(HistoricVolatility {
+            symbol,
+            bars,
+            frequency,
+            estimator,
+            look_back: None,
+            shocked_volatility: None,
+        })
+    }
+
+    pub fn from_closes(
+        symbol: Symbol,
+        closes: TimeSeries<f64>,
+        frequency: ObservationFrequency,
+        estimator: VolatilityEstimator,
+    ) -> PricerResult<HistoricVolatility> {
+        let bars = closes.map(|close| PriceBar {
+            open: *close,
+            high: *close,
+            low: *close,
+            close: *close,
+        });
+        HistoricVolatility::new(symbol, bars, frequency, estimator)
+    }
+
+    // Only estimate from observations within `look_back` of the valuation time
+    pub fn with_look_back(self, look_back: Duration) -> HistoricVolatility {
+        HistoricVolatility {
+            look_back: Some(look_back),
+            ..self
+        }
+    }
+
+    pub fn as_of(&self, valuation_time: DateTime<Utc>) -> PricerResult<HistoricVolatility> {
+        let bars = self.bars.as_of(valuation_time, self.look_back);
+        ensure_enough_observations(&bars)?;
+        Ok(HistoricVolatility {
+            bars,
+            ..self.clone()
+        })
+    }
+
+    fn log_returns(&self) -> Vec<f64> {
+        self.bars
+            .values()
+            .windows(2)
+            .map(|pair| (pair[1].close / pair[0].close).ln())
+            .collect()
+    }
+
+    // Variance of returns over one observation period
+    fn period_variance(&self) -> f64 {
+        let bars = self.bars.values();
+        match self.estimator {
+            VolatilityEstimator::CloseToClose => self.log_returns().variance(),
+            VolatilityEstimator::Parkinson => {
+                bars.iter().map(|bar| bar.log_high_low().powi(2)).mean() / (4.0 * 2f64.ln())
+            }
+            VolatilityEstimator::GarmanKlass => bars
+                .iter()
+                .map(|bar| {
+                    0.5 * bar.log_high_low().powi(2)
+                        - (2.0 * 2f64.ln() - 1.0) * bar.log_close_open().powi(2)
+                })
+                .mean(),
+            VolatilityEstimator::RogersSatchell => {
+                bars.iter().map(PriceBar::rogers_satchell).mean()
+            }
+            VolatilityEstimator::YangZhang => {
+                let periods = bars.len() - 1;
+                let overnight = bars
+                    .windows(2)
+                    .map(|pair| (pair[1].open / pair[0].close).ln())
+                    .variance();
+                let open_to_close = bars[1..].iter().map(PriceBar::log_close_open).variance();
+                let rogers_satchell = bars[1..].iter().map(PriceBar::rogers_satchell).mean();
+                let k = 0.34 / (1.34 + (periods + 1) as f64 / (periods - 1) as f64);
+                overnight + k * open_to_close + (1.0 - k) * rogers_satchell
+            }
+            VolatilityEstimator::Ewma { decay } => {
+                let returns = self.log_returns();
+                returns[1..]
+                    .iter()
+                    .fold(returns[0].powi(2), |variance, log_return| {
+                        decay * variance + (1.0 - decay) * log_return.powi(2)
+                    })
+            }
+        }
+    }
 }
 
 impl IdentifiableRiskFactor for ImpliedVolatility {
@@ -37,8 +236,12 @@ impl IdentifiableRiskFactor for HistoricVolatility {
 
 pub trait VolatilityRf {
     fn volatility(&self) -> f64;
+    // Annualised volatility over the next `delta_t` years, flat unless overridden
+    fn volatility_to(&self, _delta_t: f64) -> f64 {
+        self.volatility()
+    }
     fn scaled_to_time(&self, delta_t: f64) -> f64 {
-        self.volatility() * delta_t.sqrt()
+        self.volatility_to(delta_t) * delta_t.sqrt()
     }
 }
 
@@ -48,23 +251,42 @@ impl VolatilityRf for ImpliedVolatility {
     }
 }
 
+// Annualised by the number of observation periods in a year
 impl VolatilityRf for HistoricVolatility {
     fn volatility(&self) -> f64 {
-        self.price_time_series.iter().std_dev()
+        self.shocked_volatility
+            .unwrap_or_else(|| (self.period_variance() * self.frequency.periods_per_year()).sqrt())
     }
 }
 
 #[derive(Clone)]
 pub enum Volatility {
     ImpliedVolatility(ImpliedVolatility),
-    HistoricVolatility(HistoricVolatility),
+    Historic(HistoricVolatility),
+    GarchVolatility(GarchVolatility),
 }
 
 impl VolatilityRf for Volatility {
     fn volatility(&self) -> f64 {
         match &self {
             Volatility::ImpliedVolatility(iv) => iv.volatility(),
-            Volatility::HistoricVolatility(hv) => hv.volatility(),
+            Volatility::Historic(hv) => hv.volatility(),
+            Volatility::GarchVolatility(gv) => gv.volatility(),
+        }
+    }
+    fn volatility_to(&self, delta_t: f64) -> f64 {
+        match &self {
+            Volatility::GarchVolatility(gv) => gv.volatility_to(delta_t),
+            _ => self.volatility(),
+        }
+    }
+}
+
+impl Volatility {
+    pub fn as_of(&self, valuation_time: DateTime<Utc>) -> PricerResult<Volatility> {
+        match self {
+            Volatility::Historic(hv) => hv.as_of(valuation_time).map(Volatility::Historic),
+            _ => Ok(self.clone()),
         }
     }
 }
@@ -73,7 +295,8 @@ impl IdentifiableRiskFactor for Volatility {
     fn id(&self) -> &Symbol {
         match &self {
             Volatility::ImpliedVolatility(iv) => iv.id(),
-            Volatility::HistoricVolatility(hv) => hv.id(),
+            Volatility::Historic(hv) => hv.id(),
+            Volatility::GarchVolatility(gv) => gv.id(),
         }
     }
 }
@@ -82,8 +305,16 @@ impl ApplyShock<Volatility> for VolatilityShock {
     fn apply(&self, applicant: &mut Volatility) {
         match applicant {
             Volatility::ImpliedVolatility(iv) => self.apply(&mut iv.volatility),
-            // This is also weird...
-            Volatility::HistoricVolatility(_) => {}
+            Volatility::Historic(hv) => self.apply(hv),
+            Volatility::GarchVolatility(gv) => self.apply(gv),
         }
     }
 }
+
+impl ApplyShock<HistoricVolatility> for VolatilityShock {
+    fn apply(&self, applicant: &mut HistoricVolatility) {
+        let mut volatility = applicant.volatility();
+        self.apply(&mut volatility);
+        applicant.shocked_volatility = Some(volatility);
+    }
+}
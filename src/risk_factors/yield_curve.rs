@@ -0,0 +1,368 @@
+use crate::result::{PricerError, PricerResult};
+use crate::shock::{ApplyShock, InterestRateShock, KeyRateShock};
+use crate::symbol::Symbol;
+
+use super::discount::DiscountRf;
+use super::IdentifiableRiskFactor;
+
+// Sources of equations:
+//  - Hagan & West (2006), Interpolation methods for curve construction
+//  - Hagan & West (2008), Methods for constructing a yield curve
+
+// Market quotes the curve is bootstrapped from, times are in years from the curve date
+#[derive(Clone, Debug)]
+pub enum CurveInstrument {
+    // Simply compounded deposit rate
+    Deposit {
+        maturity: f64,
+        rate: f64,
+    },
+    // Simply compounded forward rate between `start` and `end`
+    ForwardRateAgreement {
+        start: f64,
+        end: f64,
+        rate: f64,
+    },
+    // Futures are quoted as 100 minus the rate in percent, no convexity adjustment is applied
+    Future {
+        start: f64,
+        end: f64,
+        price: f64,
+    },
+    // Par swap rate paying the fixed leg `frequency` times a year
+    Swap {
+        maturity: f64,
+        rate: f64,
+        frequency: usize,
+    },
+}
+
+impl CurveInstrument {
+    fn pillar(&self) -> f64 {
+        match self {
+            CurveInstrument::Deposit { maturity, .. } => *maturity,
+            CurveInstrument::ForwardRateAgreement { end, .. } => *end,
+            CurveInstrument::Future { end, .. } => *end,
+            CurveInstrument::Swap { maturity, .. } => *maturity,
+        }
+    }
+
+    // Difference between the curve's discount factor at the pillar and the one the quote implies
+    fn residual(&self, curve: &YieldCurve) -> f64 {
+        match self {
+            CurveInstrument::Deposit { maturity, rate } => {
+                curve.discount_factor(*maturity) - 1.0 / (1.0 + rate * maturity)
+            }
+            CurveInstrument::ForwardRateAgreement { start, end, rate } => {
+                forward_residual(curve, *start, *end, *rate)
+            }
+            CurveInstrument::Future { start, end, price } => {
+                forward_residual(curve, *start, *end, (100.0 - price) / 100.0)
+            }
+            CurveInstrument::Swap {
+                maturity,
+                rate,
+                frequency,
+            } => {
+                let accrual = 1.0 / *frequency as f64;
+                let payments = (maturity * *frequency as f64).round() as usize;
+                let annuity: f64 = (0..payments)
+                    .map(|payment| maturity - payment as f64 * accrual)
+                    .map(|time| {
+                        let period = accrual.min(time);
+                        period * curve.discount_factor(time)
+                    })
+                    .sum();
+                curve.discount_factor(*maturity) - (1.0 - rate * annuity)
+            }
+        }
+    }
+}
+
+fn forward_residual(curve: &YieldCurve, start: f64, end: f64, rate: f64) -> f64 {
+    curve.discount_factor(end) - curve.discount_factor(start) / (1.0 + rate * (end - start))
+}
+
+#[derive(Clone, Debug)]
+pub enum CurveInterpolation {
+    LinearZero,
+    LogLinearDiscount,
+    MonotoneConvex,
+}
+
+#[derive(Clone)]
+pub struct YieldCurve {
+    symbol: Symbol,
+    interpolation: CurveInterpolation,
+    // Continuously compounded zero rates at each pillar, pillars are in years and increasing
+    pillars: Vec<f64>,
+    zero_rates: Vec<f64>,
+}
+
+fn invalid_curve_err(reason: &str) -> PricerError {
+    PricerError::new(format!("Unable to build yield curve, {}", reason), 9)
+}
+
+const MAX_ITERATIONS: usize = 100;
+const TOLERANCE: f64 = 1e-12;
+
+impl YieldCurve {
+    pub fn new(
+        symbol: Symbol,
+        pillars: Vec<f64>,
+        zero_rates: Vec<f64>,
+        interpolation: CurveInterpolation,
+    ) -> PricerResult<YieldCurve> {
+        if pillars.is_empty() || pillars.len() != zero_rates.len() {
+            return Err(invalid_curve_err(
+                "a zero rate is required for each of at least one pillar",
+            ));
+        }
+        if pillars[0] <= 0.0 || pillars.windows(2).any(|pair| pair[1] <= pair[0]) {
+            return Err(invalid_curve_err("pillars must be positive and increasing"));
+        }
+        Ok(YieldCurve {
+            symbol,
+            interpolation,
+            pillars,
+            zero_rates,
+        })
+    }
+
+    // Solves for the zero rate at each instrument's pillar in turn. Monotone convex interpolation
+    // is not local, so the curve is swept again until no pillar moves
+    pub fn bootstrap(
+        symbol: Symbol,
+        mut instruments: Vec<CurveInstrument>,
+        interpolation: CurveInterpolation,
+    ) -> PricerResult<YieldCurve> {
+        instruments.sort_by(|lhs, rhs| lhs.pillar().total_cmp(&rhs.pillar()));
+        let pillars: Vec<f64> = instruments.iter().map(CurveInstrument::pillar).collect();
+        let mut curve =
+            YieldCurve::new(symbol, pillars, vec![0.0; instruments.len()], interpolation)?;
+        for sweep in 0..MAX_ITERATIONS {
+            let mut largest_move: f64 = 0.0;
+            for (index, instrument) in instruments.iter().enumerate() {
+                let previous = curve.zero_rates[index];
+                let solved = curve.solve_pillar(index, instrument, sweep == 0)?;
+                largest_move = largest_move.max((solved - previous).abs());
+            }
+            if largest_move < TOLERANCE {
+                return Ok(curve);
+            }
+        }
+        Err(invalid_curve_err("bootstrap did not converge"))
+    }
+
+    // Secant search on the zero rate at `index`. On the first sweep later pillars are not yet
+    // known, so they are held at the rate being solved for
+    fn solve_pillar(
+        &mut self,
+        index: usize,
+        instrument: &CurveInstrument,
+        first_sweep: bool,
+    ) -> PricerResult<f64> {
+        let start = if index > 0 && first_sweep {
+            self.zero_rates[index - 1]
+        } else {
+            self.zero_rates[index]
+        };
+        let mut residual_at = |rate: f64| {
+            self.zero_rates[index] = rate;
+            if first_sweep {
+                self.zero_rates[index + 1..].fill(rate);
+            }
+            instrument.residual(self)
+        };
+        let (mut lower, mut upper) = (start, start + 0.01);
+        let (mut lower_residual, mut upper_residual) = (residual_at(lower), residual_at(upper));
+        for _ in 0..MAX_ITERATIONS {
+            if upper_residual.abs() < TOLERANCE {
+                return Ok(upper);
+            }
+            let slope = (upper_residual - lower_residual) / (upper - lower);
+            if slope == 0.0 || !slope.is_finite() {
+                break;
+            }
+            (lower, lower_residual) = (upper, upper_residual);
+            upper -= upper_residual / slope;
+            upper_residual = residual_at(upper);
+        }
+        Err(invalid_curve_err(&format!(
+            "no zero rate reprices the instrument {:?}",
+            instrument
+        )))
+    }
+
+    pub fn pillars(&self) -> &[f64] {
+        &self.pillars
+    }
+    pub fn zero_rates(&self) -> &[f64] {
+        &self.zero_rates
+    }
+
+    // Continuously compounded zero rate to `t`. Linear zero interpolation holds the zero rate flat
+    // beyond the last pillar, the others hold the forward flat
+    pub fn zero_rate(&self, t: f64) -> f64 {
+        if t <= 0.0 {
+            return self.zero_rates[0];
+        }
+        let last = self.pillars.len() - 1;
+        if t >= self.pillars[last] {
+            let forward = match self.interpolation {
+                CurveInterpolation::LinearZero => return self.zero_rates[last],
+                CurveInterpolation::LogLinearDiscount => self.discrete_forward(last),
+                CurveInterpolation::MonotoneConvex => self.instantaneous_forwards()[last + 1],
+            };
+            let accrued = self.zero_rates[last] * self.pillars[last];
+            return (accrued + forward * (t - self.pillars[last])) / t;
+        }
+        // Interval ending at pillar `index`, the first interval starts at time zero
+        let index = self.pillars.partition_point(|pillar| *pillar < t);
+        let (start, start_accrued) = match index {
+            0 => (0.0, 0.0),
+            _ => (
+                self.pillars[index - 1],
+                self.zero_rates[index - 1] * self.pillars[index - 1],
+            ),
+        };
+        let end = self.pillars[index];
+        let x = (t - start) / (end - start);
+        match self.interpolation {
+            CurveInterpolation::LinearZero if index == 0 => self.zero_rates[0],
+            CurveInterpolation::LinearZero => {
+                self.zero_rates[index - 1]
+                    + x * (self.zero_rates[index] - self.zero_rates[index - 1])
+            }
+            CurveInterpolation::LogLinearDiscount => {
+                let end_accrued = self.zero_rates[index] * end;
+                (start_accrued + x * (end_accrued - start_accrued)) / t
+            }
+            CurveInterpolation::MonotoneConvex => {
+                let forwards = self.instantaneous_forwards();
+                let discrete = self.discrete_forward(index);
+                let integral = monotone_convex_integral(
+                    forwards[index] - discrete,
+                    forwards[index + 1] - discrete,
+                    x,
+                );
+                (start_accrued + (end - start) * (discrete * x + integral)) / t
+            }
+        }
+    }
+
+    pub fn discount_factor(&self, t: f64) -> f64 {
+        (-self.zero_rate(t) * t).exp()
+    }
+
+    // Continuously compounded forward rate between `start` and `end`
+    pub fn forward_rate(&self, start: f64, end: f64) -> f64 {
+        (self.zero_rate(end) * end - self.zero_rate(start) * start) / (end - start)
+    }
+
+    // Forward between pillar `index - 1` and `index`, with an implicit pillar at time zero
+    fn discrete_forward(&self, index: usize) -> f64 {
+        if index == 0 {
+            return self.zero_rates[0];
+        }
+        let (start, end) = (self.pillars[index - 1], self.pillars[index]);
+        (self.zero_rates[index] * end - self.zero_rates[index - 1] * start) / (end - start)
+    }
+
+    // Instantaneous forwards at time zero followed by each pillar
+    fn instantaneous_forwards(&self) -> Vec<f64> {
+        let times: Vec<f64> = std::iter::once(0.0)
+            .chain(self.pillars.iter().cloned())
+            .collect();
+        let discrete: Vec<f64> = (0..self.pillars.len())
+            .map(|index| self.discrete_forward(index))
+            .collect();
+        let n = discrete.len();
+        let mut forwards = vec![0.0; n + 1];
+        for i in 1..n {
+            forwards[i] = ((times[i] - times[i - 1]) * discrete[i]
+                + (times[i + 1] - times[i]) * discrete[i - 1])
+                / (times[i + 1] - times[i - 1]);
+        }
+        if n == 1 {
+            forwards[0] = discrete[0];
+            forwards[1] = discrete[0];
+        } else {
+            forwards[0] = discrete[0] - 0.5 * (forwards[1] - discrete[0]);
+            forwards[n] = discrete[n - 1] - 0.5 * (forwards[n - 1] - discrete[n - 1]);
+        }
+        forwards
+    }
+}
+
+// Integral over [0, x] of the Hagan-West correction g to the discrete forward, where g takes the
+// values g0 and g1 at either end of the interval
+fn monotone_convex_integral(g0: f64, g1: f64, x: f64) -> f64 {
+    if g0 == 0.0 && g1 == 0.0 {
+        return 0.0;
+    }
+    let quadratic = (g0 < 0.0 && -0.5 * g0 <= g1 && g1 <= -2.0 * g0)
+        || (g0 > 0.0 && -0.5 * g0 >= g1 && g1 >= -2.0 * g0);
+    if quadratic {
+        return g0 * (x - 2.0 * x.powi(2) + x.powi(3)) + g1 * (x.powi(3) - x.powi(2));
+    }
+    if (g0 < 0.0 && g1 > -2.0 * g0) || (g0 > 0.0 && g1 < -2.0 * g0) {
+        let eta = (g1 + 2.0 * g0) / (g1 - g0);
+        if x <= eta {
+            return g0 * x;
+        }
+        return g0 * x + (g1 - g0) * (x - eta).powi(3) / (1.0 - eta).powi(2) / 3.0;
+    }
+    if (g0 > 0.0 && 0.0 > g1 && g1 > -0.5 * g0) || (g0 < 0.0 && 0.0 < g1 && g1 < -0.5 * g0) {
+        let eta = 3.0 * g1 / (g1 - g0);
+        if x < eta {
+            return g1 * x + (g0 - g1) * (eta - (eta - x).powi(3) / eta.powi(2)) / 3.0;
+        }
+        return g1 * x + (g0 - g1) * eta / 3.0;
+    }
+    let eta = g1 / (g1 + g0);
+    let a = -g0 * g1 / (g0 + g1);
+    if x <= eta {
+        return a * x + (g0 - a) * (eta - (eta - x).powi(3) / eta.powi(2)) / 3.0;
+    }
+    a * x + (g0 - a) * eta / 3.0 + (g1 - a) * (x - eta).powi(3) / (1.0 - eta).powi(2) / 3.0
+}
+
+impl DiscountRf for YieldCurve {
+    // The short rate
+    fn rate(&self) -> f64 {
+        self.zero_rates[0]
+    }
+    fn zero_rate(&self, delta_t: f64) -> f64 {
+        YieldCurve::zero_rate(self, delta_t)
+    }
+}
+
+impl IdentifiableRiskFactor for YieldCurve {
+    fn id(&self) -> &Symbol {
+        &self.symbol
+    }
+}
+
+// A parallel shift of every zero rate
+impl ApplyShock<YieldCurve> for InterestRateShock {
+    fn apply(&self, applicant: &mut YieldCurve) {
+        applicant
+            .zero_rates
+            .iter_mut()
+            .for_each(|rate| self.apply(rate));
+    }
+}
+
+impl ApplyShock<YieldCurve> for KeyRateShock {
+    fn apply(&self, applicant: &mut YieldCurve) {
+        if applicant.id() != self.risk_factor() {
+            return;
+        }
+        applicant
+            .pillars
+            .iter()
+            .zip(applicant.zero_rates.iter_mut())
+            .for_each(|(pillar, rate)| *rate = self.apply_weighted(*pillar, *rate));
+    }
+}
@@ -49,6 +49,28 @@ pub struct InterestRateShock {
     size: ShockSize,
 }
 
+pub struct BorrowShock {
+    risk_factor_id: Symbol,
+    size: ShockSize,
+}
+
+impl BorrowShock {
+    pub fn risk_factor(&self) -> &Symbol {
+        &self.risk_factor_id
+    }
+}
+
+pub struct DividendShock {
+    risk_factor_id: Symbol,
+    size: ShockSize,
+}
+
+impl DividendShock {
+    pub fn risk_factor(&self) -> &Symbol {
+        &self.risk_factor_id
+    }
+}
+
 impl FloatShock for PriceShock {
     fn apply_float(&self, base: f64) -> f64 {
         self.size.apply_float(base)
@@ -59,11 +81,55 @@ impl FloatShock for VolatilityShock {
         self.size.apply_float(base)
     }
 }
+// Shifts a curve's zero rates around one key rate tenor. The shift is triangular, falling from
+// the full size at `key_rates[bucket]` to nothing at the neighbouring key rates, and held flat
+// beyond the first and last key rates, so the shifts across all buckets sum to a parallel shift
+pub struct KeyRateShock {
+    risk_factor_id: Symbol,
+    key_rates: Vec<f64>,
+    bucket: usize,
+    size: ShockSize,
+}
+
+impl KeyRateShock {
+    pub fn risk_factor(&self) -> &Symbol {
+        &self.risk_factor_id
+    }
+    pub fn tenor(&self) -> f64 {
+        self.key_rates[self.bucket]
+    }
+    pub fn weight(&self, t: f64) -> f64 {
+        let tenor = self.tenor();
+        let neighbour = if t < tenor {
+            self.bucket.checked_sub(1).map(|i| self.key_rates[i])
+        } else {
+            self.key_rates.get(self.bucket + 1).cloned()
+        };
+        match neighbour {
+            None => 1.0,
+            Some(neighbour) => (1.0 - (t - tenor) / (neighbour - tenor)).max(0.0),
+        }
+    }
+    pub fn apply_weighted(&self, t: f64, base: f64) -> f64 {
+        base + self.weight(t) * (self.size.apply_float(base) - base)
+    }
+}
+
 impl FloatShock for InterestRateShock {
     fn apply_float(&self, base: f64) -> f64 {
         self.size.apply_float(base)
     }
 }
+impl FloatShock for BorrowShock {
+    fn apply_float(&self, base: f64) -> f64 {
+        self.size.apply_float(base)
+    }
+}
+impl FloatShock for DividendShock {
+    fn apply_float(&self, base: f64) -> f64 {
+        self.size.apply_float(base)
+    }
+}
 impl FloatShock for TimeShock {
     fn apply_float(&self, base: f64) -> f64 {
         self.size.apply_float(base)
@@ -84,6 +150,9 @@ pub enum Shock {
     VolatilityShock(VolatilityShock),
     TimeShock(TimeShock),
     InterestRateShock(InterestRateShock),
+    KeyRateShock(KeyRateShock),
+    DividendShock(DividendShock),
+    BorrowShock(BorrowShock),
 }
 
 pub const fn absolute_shock(size: f64, direction: ShockDirection) -> ShockSize {
@@ -114,6 +183,31 @@ pub const fn interest_rate_shock(risk_factor_id: Symbol, size: ShockSize) -> Sho
         size,
     })
 }
+pub const fn key_rate_shock(
+    risk_factor_id: Symbol,
+    key_rates: Vec<f64>,
+    bucket: usize,
+    size: ShockSize,
+) -> Shock {
+    Shock::KeyRateShock(KeyRateShock {
+        risk_factor_id,
+        key_rates,
+        bucket,
+        size,
+    })
+}
+pub const fn dividend_shock(risk_factor_id: Symbol, size: ShockSize) -> Shock {
+    Shock::DividendShock(DividendShock {
+        risk_factor_id,
+        size,
+    })
+}
+pub const fn borrow_shock(risk_factor_id: Symbol, size: ShockSize) -> Shock {
+    Shock::BorrowShock(BorrowShock {
+        risk_factor_id,
+        size,
+    })
+}
 pub const fn time_shock(size: TimeShockSize) -> Shock {
     Shock::TimeShock(TimeShock { size })
 }
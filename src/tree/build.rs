@@ -1,4 +1,5 @@
-use crate::tree::node::{Node, Position};
+use crate::tree::lattice::{DividendAdjustments, LatticeInputs, LatticeParameters, LatticeScheme};
+use crate::tree::node::Position;
 use crate::tree::Tree;
 
 use crate::result::PricerResult;
@@ -9,23 +10,6 @@ use chrono::DateTime;
 #[cfg(test)]
 use chrono::TimeZone;
 
-use std::collections::HashMap;
-
-fn new_tree(price: f64, datetime: DateTime<Utc>) -> Tree {
-    Tree {
-        head: Node {
-            price,
-            datetime,
-            pos: Position {
-                num_ups: 0,
-                num_downs: 0,
-            },
-        },
-        nodes: HashMap::new(),
-        valuation_cache: HashMap::new(),
-    }
-}
-
 pub fn get_next_layer(tree_positions: Vec<Position>) -> Vec<Position> {
     let mut next_layer: Vec<Position> = tree_positions
         .into_iter()
@@ -50,57 +34,61 @@ pub fn get_next_layer(tree_positions: Vec<Position>) -> Vec<Position> {
     next_layer
 }
 
-fn get_node_value(underlying_price: f64, volatility: f64, position: &Position) -> f64 {
-    let up_multi = f64::powf(1.0 + volatility, position.num_ups as f64);
-    let down_multi = f64::powf(1.0 - volatility, position.num_downs as f64);
+pub fn get_node_value(
+    underlying_price: f64,
+    parameters: &LatticeParameters,
+    position: &Position,
+) -> f64 {
+    let up_multi = f64::powi(parameters.up, position.num_ups as i32);
+    let down_multi = f64::powi(parameters.down, position.num_downs as i32);
     underlying_price * up_multi * down_multi
 }
 
-fn get_node(price: f64, datetime: DateTime<Utc>, volatility: f64, position: &Position) -> Node {
-    let price = get_node_value(price, volatility, position);
-    Node {
-        price,
-        datetime,
-        pos: position.clone(),
-    }
-}
-
 pub fn construct_tree(
-    underlying_price: f64,
-    volatility: f64,
+    inputs: &LatticeInputs,
+    scheme: &LatticeScheme,
     start: DateTime<Utc>,
     end: DateTime<Utc>,
     num_steps: i32,
 ) -> PricerResult<Tree> {
-    let date_range = date_utils::get_datetime_range(start, end, num_steps);
-    let mut tree = new_tree(underlying_price, start);
-    let mut current_layer = vec![Position {
-        num_ups: 0,
-        num_downs: 0,
-    }];
-    for datetime in date_range {
-        let nodes: Vec<Node> = current_layer
-            .iter()
-            .map(|position: &Position| get_node(underlying_price, datetime, volatility, position))
-            .collect();
-        tree.nodes
-            .extend(current_layer.clone().into_iter().zip(nodes.into_iter()));
-        current_layer = get_next_layer(current_layer);
-    }
-    Ok(tree)
+    let delta_t = date_utils::get_duration_in_years(start, end);
+    let parameters = scheme.parameters(inputs, delta_t, num_steps as usize)?;
+    let num_steps = num_steps as usize;
+    let dividend_adjustments = DividendAdjustments::new(inputs, parameters.dt, num_steps);
+    let terminal_prices = (0..=num_steps)
+        .map(|num_ups| Position {
+            num_ups,
+            num_downs: num_steps - num_ups,
+        })
+        .map(|position| get_node_value(dividend_adjustments.escrowed_price, &parameters, &position))
+        .collect();
+    Ok(Tree {
+        num_steps,
+        parameters,
+        risk_free_rate: inputs.risk_free_rate,
+        dividend_adjustments,
+        terminal_prices,
+    })
 }
 
 #[test]
 fn one_year_tree_one_step() {
-    let underlying_price: f64 = 100.0;
-    let volatility: f64 = 0.05;
+    let inputs = LatticeInputs {
+        underlying_price: 100.0,
+        volatility: 0.05,
+        risk_free_rate: 0.0,
+        dividend_yield: 0.0,
+        dividends: vec![],
+    };
     let begin_date = Utc.timestamp_millis_opt(1688917143000).unwrap();
     let end_date = Utc.timestamp_millis_opt(1720539543000).unwrap();
     let num_steps = 1;
+    // 366 days, as the period spans a leap day
+    let expected_up = 100.0 * (0.05 * (366.0f64 / 365.0).sqrt()).exp();
 
     let tree = construct_tree(
-        underlying_price,
-        volatility,
+        &inputs,
+        &LatticeScheme::CoxRossRubinstein,
         begin_date,
         end_date,
         num_steps,
@@ -110,44 +98,9 @@ fn one_year_tree_one_step() {
     {
         assert!(tree.is_ok());
         tree.map(|tree: Tree| {
-            let node = tree.head;
-            assert!(node.price == 100.0);
-            assert!(node.datetime == begin_date);
-            assert!(
-                node.pos
-                    == Position {
-                        num_ups: 0,
-                        num_downs: 0
-                    }
-            );
-            assert!(tree.nodes.len() == 3);
-
-            let (up_pos, down_pos) = node.pos.get_branches();
-            assert!(tree.nodes.get(&up_pos).is_some());
-            tree.nodes.get(&up_pos).map(|node: &Node| {
-                assert!(node.price == 105.00);
-                assert!(node.datetime == end_date);
-                assert!(
-                    node.pos
-                        == Position {
-                            num_ups: 1,
-                            num_downs: 0
-                        }
-                );
-            });
-            assert!(tree.nodes.get(&down_pos).is_some());
-            tree.nodes.get(&down_pos).map(|node: &Node| {
-                assert!(node.price > 94.999);
-                assert!(node.price < 95.001);
-                assert!(node.datetime == end_date);
-                assert!(
-                    node.pos
-                        == Position {
-                            num_ups: 0,
-                            num_downs: 1
-                        }
-                );
-            });
+            assert!(tree.terminal_prices.len() == 2);
+            assert!((tree.terminal_prices[1] - expected_up).abs() < 1e-9);
+            assert!((tree.terminal_prices[0] * expected_up - 100.0 * 100.0).abs() < 1e-9);
         });
     }
 }
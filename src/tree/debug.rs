@@ -1,26 +1,9 @@
 use crate::tree::build::get_next_layer;
-use crate::tree::node::{Node, Position};
+use crate::tree::node::Position;
 use crate::tree::Tree;
 
 use log::info;
 
-fn get_up_node(node: &Node) -> Position {
-    Position {
-        num_ups: node.pos.num_ups + 1,
-        num_downs: node.pos.num_downs,
-    }
-}
-
-fn get_tree_depth(tree: &Tree) -> usize {
-    let mut count = 0;
-    let mut it = &tree.head;
-    while tree.nodes.get(&get_up_node(it)).is_some() {
-        it = tree.nodes.get(&get_up_node(it)).unwrap();
-        count += 1;
-    }
-    return count + 1;
-}
-
 fn print_layer(tree: &Tree, layer: &Vec<Position>, expected_width: &usize) {
     let mut out = String::from("[");
     let num_in_layer = layer.len();
@@ -34,13 +17,11 @@ fn print_layer(tree: &Tree, layer: &Vec<Position>, expected_width: &usize) {
     }
     i = 0;
     for p in layer {
-        tree.nodes.get(p).map(|n: &Node| {
-            out += &format!("{:.3}", n.price);
-            if i < layer.len() - 1 {
-                out += "   ";
-            }
-            i += 1;
-        });
+        out += &format!("{:.3}", tree.node_price(p));
+        if i < layer.len() - 1 {
+            out += "   ";
+        }
+        i += 1;
     }
     i = 0;
     while i < padding {
@@ -53,7 +34,7 @@ fn print_layer(tree: &Tree, layer: &Vec<Position>, expected_width: &usize) {
 
 #[allow(dead_code)]
 pub fn print_tree(tree: &Tree) {
-    let tree_depth = get_tree_depth(tree);
+    let tree_depth = tree.num_steps + 1;
     let cumulative_node_width = tree_depth * 5;
     let cumulative_node_spacing_width = (tree_depth - 1) * 3;
     let expected_width = cumulative_node_width + cumulative_node_spacing_width;
@@ -0,0 +1,65 @@
+use super::lattice::LatticeInputs;
+use super::risk_factors::TreeRiskFactors;
+
+use crate::shock::{ApplyShock, Scenario, Shock};
+
+use crate::utils::date::{get_duration_in_years, shift_by_years};
+
+use chrono::{DateTime, Utc};
+
+pub struct TreeInputs {
+    pub valuation_time: DateTime<Utc>,
+    pub delta_t: f64,
+    option_expiry: DateTime<Utc>,
+    risk_factors: TreeRiskFactors,
+}
+
+impl TreeInputs {
+    pub fn gather(
+        expiry: DateTime<Utc>,
+        valuation_time: DateTime<Utc>,
+        risk_factors: TreeRiskFactors,
+    ) -> TreeInputs {
+        let delta_t = get_duration_in_years(valuation_time, expiry);
+        TreeInputs {
+            valuation_time,
+            delta_t,
+            option_expiry: expiry,
+            risk_factors,
+        }
+    }
+
+    // Expiry as seen after any time shocks have been applied
+    pub fn expiry(&self) -> DateTime<Utc> {
+        shift_by_years(self.valuation_time, self.delta_t)
+    }
+    pub fn lattice_inputs(&self) -> LatticeInputs {
+        LatticeInputs {
+            underlying_price: self.risk_factors.price(),
+            volatility: self.risk_factors.volatility(self.delta_t),
+            risk_free_rate: self.risk_factors.discount_rate(self.delta_t),
+            // The borrow fee reduces the forward in the same way as a dividend yield
+            dividend_yield: self.risk_factors.dividend_yield() + self.risk_factors.borrow_rate(),
+            dividends: self
+                .risk_factors
+                .outstanding_dividends(self.option_expiry, self.delta_t),
+        }
+    }
+}
+
+impl ApplyShock<TreeInputs> for Shock {
+    fn apply(&self, applicant: &mut TreeInputs) {
+        match self {
+            Shock::TimeShock(shock) => shock.apply(&mut applicant.delta_t),
+            _ => self.apply(&mut applicant.risk_factors),
+        }
+    }
+}
+
+impl ApplyShock<TreeInputs> for Scenario {
+    fn apply(&self, applicant: &mut TreeInputs) {
+        for shock in self {
+            shock.apply(applicant);
+        }
+    }
+}
@@ -0,0 +1,237 @@
+use crate::result::{PricerError, PricerResult};
+use crate::risk_factors::dividend::{
+    present_value_of_cash, proportional_factor, OutstandingDividend,
+};
+
+// Sources of equations:
+//  - Cox, Ross & Rubinstein (1979), Option pricing: A simplified approach
+//  - Jarrow & Rudd (1983), Option Pricing
+//  - Tian (1993), A modified lattice approach to option pricing
+//  - Leisen & Reimer (1996), Binomial models for option valuation - examining and improving convergence
+//  - Boyle (1988), A lattice framework for option pricing with two state variables
+//  - Kamrad & Ritchken (1991), Multinomial approximating models for options with k state variables
+
+#[derive(Clone, Debug)]
+pub enum LatticeScheme {
+    CoxRossRubinstein,
+    JarrowRudd,
+    Tian,
+    // Centres the lattice on the strike, most accurate with an odd number of steps
+    LeisenReimer { strike: f64 },
+}
+
+#[derive(Clone, Debug)]
+pub struct LatticeInputs {
+    pub underlying_price: f64,
+    pub volatility: f64,
+    pub risk_free_rate: f64,
+    pub dividend_yield: f64,
+    pub dividends: Vec<OutstandingDividend>,
+}
+
+#[derive(Clone, Debug)]
+pub struct LatticeParameters {
+    pub up: f64,
+    pub down: f64,
+    pub probability: f64,
+    pub dt: f64,
+}
+
+impl LatticeParameters {
+    pub fn step_discount(&self, risk_free_rate: f64) -> f64 {
+        (-risk_free_rate * self.dt).exp()
+    }
+}
+
+// Escrowed dividend model, the lattice diffuses the price net of the present value of all cash
+// dividends and a node's price adds back the cash still to be paid and applies the proportional
+// dividends that have gone ex by that step
+#[derive(Clone, Debug)]
+pub struct DividendAdjustments {
+    pub escrowed_price: f64,
+    pub proportional_factors: Vec<f64>,
+    pub cash_present_values: Vec<f64>,
+}
+
+impl DividendAdjustments {
+    pub fn new(inputs: &LatticeInputs, dt: f64, steps: usize) -> DividendAdjustments {
+        let (proportional_factors, cash_present_values) = (0..=steps)
+            .map(|step| {
+                let elapsed = step as f64 * dt;
+                let (paid, outstanding): (Vec<OutstandingDividend>, Vec<OutstandingDividend>) =
+                    inputs
+                        .dividends
+                        .iter()
+                        .cloned()
+                        .partition(|dividend| dividend.time <= elapsed);
+                let remaining: Vec<OutstandingDividend> = outstanding
+                    .into_iter()
+                    .map(|dividend| OutstandingDividend {
+                        time: dividend.time - elapsed,
+                        payment: dividend.payment,
+                    })
+                    .collect();
+                (
+                    proportional_factor(&paid),
+                    present_value_of_cash(&remaining, inputs.risk_free_rate),
+                )
+            })
+            .unzip();
+        DividendAdjustments {
+            escrowed_price: inputs.underlying_price
+                - present_value_of_cash(&inputs.dividends, inputs.risk_free_rate),
+            proportional_factors,
+            cash_present_values,
+        }
+    }
+
+    pub fn price(&self, step: usize, lattice_price: f64) -> f64 {
+        lattice_price * self.proportional_factors[step] + self.cash_present_values[step]
+    }
+
+    // The price the lattice would reach at expiry absent volatility, used to centre schemes
+    fn ex_dividend_price(inputs: &LatticeInputs) -> f64 {
+        (inputs.underlying_price - present_value_of_cash(&inputs.dividends, inputs.risk_free_rate))
+            * proportional_factor(&inputs.dividends)
+    }
+}
+
+// `stretch` scales the node spacing to stretch * volatility * sqrt(dt), sqrt(2) and sqrt(3) are
+// the usual choices
+#[derive(Clone, Debug)]
+pub enum TrinomialScheme {
+    Boyle { stretch: f64 },
+    KamradRitchken { stretch: f64 },
+}
+
+#[derive(Clone, Debug)]
+pub struct TrinomialParameters {
+    pub up: f64,
+    pub down: f64,
+    pub up_probability: f64,
+    pub middle_probability: f64,
+    pub down_probability: f64,
+    pub dt: f64,
+}
+
+impl TrinomialParameters {
+    pub fn step_discount(&self, risk_free_rate: f64) -> f64 {
+        (-risk_free_rate * self.dt).exp()
+    }
+}
+
+fn invalid_lattice_err<S: std::fmt::Debug>(scheme: &S, probability: f64) -> PricerError {
+    PricerError::new(
+        format!(
+            "Lattice scheme {:?} produced risk-neutral probability {}, try increasing the number of steps",
+            scheme, probability
+        ),
+        7,
+    )
+}
+
+// Peizer-Pratt method 2 inversion of the normal distribution
+fn peizer_pratt(z: f64, steps: usize) -> f64 {
+    let n = steps as f64;
+    let scaled = z / (n + 1.0 / 3.0 + 0.1 / (n + 1.0));
+    0.5 + z.signum() * (0.25 - 0.25 * (-scaled.powi(2) * (n + 1.0 / 6.0)).exp()).sqrt()
+}
+
+impl LatticeScheme {
+    pub fn parameters(
+        &self,
+        inputs: &LatticeInputs,
+        delta_t: f64,
+        steps: usize,
+    ) -> PricerResult<LatticeParameters> {
+        let dt = delta_t / steps as f64;
+        let carry = inputs.risk_free_rate - inputs.dividend_yield;
+        let growth = (carry * dt).exp();
+        let variance = inputs.volatility.powi(2);
+        let (up, down, probability) = match self {
+            LatticeScheme::CoxRossRubinstein => {
+                let up = (inputs.volatility * dt.sqrt()).exp();
+                let down = 1.0 / up;
+                (up, down, (growth - down) / (up - down))
+            }
+            LatticeScheme::JarrowRudd => {
+                let drift = (carry - 0.5 * variance) * dt;
+                let diffusion = inputs.volatility * dt.sqrt();
+                ((drift + diffusion).exp(), (drift - diffusion).exp(), 0.5)
+            }
+            LatticeScheme::Tian => {
+                let v = (variance * dt).exp();
+                let root = (v.powi(2) + 2.0 * v - 3.0).sqrt();
+                let up = 0.5 * growth * v * (v + 1.0 + root);
+                let down = 0.5 * growth * v * (v + 1.0 - root);
+                (up, down, (growth - down) / (up - down))
+            }
+            LatticeScheme::LeisenReimer { strike } => {
+                let vol_sqrt_t = inputs.volatility * delta_t.sqrt();
+                let d1 = ((DividendAdjustments::ex_dividend_price(inputs) / strike).ln()
+                    + (carry + 0.5 * variance) * delta_t)
+                    / vol_sqrt_t;
+                let d2 = d1 - vol_sqrt_t;
+                let probability = peizer_pratt(d2, steps);
+                let up = growth * peizer_pratt(d1, steps) / probability;
+                let down = (growth - probability * up) / (1.0 - probability);
+                (up, down, probability)
+            }
+        };
+        if !(0.0..=1.0).contains(&probability) {
+            return Err(invalid_lattice_err(self, probability));
+        }
+        Ok(LatticeParameters {
+            up,
+            down,
+            probability,
+            dt,
+        })
+    }
+}
+
+impl TrinomialScheme {
+    pub fn parameters(
+        &self,
+        inputs: &LatticeInputs,
+        delta_t: f64,
+        steps: usize,
+    ) -> PricerResult<TrinomialParameters> {
+        let dt = delta_t / steps as f64;
+        let carry = inputs.risk_free_rate - inputs.dividend_yield;
+        let variance = inputs.volatility.powi(2);
+        let (up, up_probability, down_probability) = match self {
+            TrinomialScheme::Boyle { stretch } => {
+                let up = (stretch * inputs.volatility * dt.sqrt()).exp();
+                let growth = (carry * dt).exp();
+                let second_moment = growth.powi(2) * (variance * dt).exp();
+                let scale = (up - 1.0) * (up.powi(2) - 1.0);
+                let up_probability = ((second_moment - growth) * up - (growth - 1.0)) / scale;
+                let down_probability =
+                    ((second_moment - growth) * up.powi(2) - (growth - 1.0) * up.powi(3)) / scale;
+                (up, up_probability, down_probability)
+            }
+            TrinomialScheme::KamradRitchken { stretch } => {
+                let up = (stretch * inputs.volatility * dt.sqrt()).exp();
+                let base = 1.0 / (2.0 * stretch.powi(2));
+                let skew =
+                    (carry - 0.5 * variance) * dt.sqrt() / (2.0 * stretch * inputs.volatility);
+                (up, base + skew, base - skew)
+            }
+        };
+        let middle_probability = 1.0 - up_probability - down_probability;
+        for probability in [up_probability, middle_probability, down_probability] {
+            if !(0.0..=1.0).contains(&probability) {
+                return Err(invalid_lattice_err(self, probability));
+            }
+        }
+        Ok(TrinomialParameters {
+            up,
+            down: 1.0 / up,
+            up_probability,
+            middle_probability,
+            down_probability,
+            dt,
+        })
+    }
+}
@@ -1,68 +1,199 @@
 pub mod build;
 pub mod debug;
+pub mod lattice;
 pub mod node;
+pub mod trinomial;
+
+mod inputs;
+mod params;
+mod pricing;
+mod risk_factors;
 
 mod tests;
 
-use node::{Node, Position};
+pub use params::{TreeParams, TreeScheme};
+pub use pricing::BinomialTree;
+
+use lattice::{DividendAdjustments, LatticeParameters};
+use node::Position;
 
-use crate::option::FinancialOption;
-use crate::utils::date as date_utils;
+use crate::option::{ExerciseStyle, FinancialOption};
+use crate::result::{PricerError, PricerResult};
+use crate::utils::date::DAYS_IN_YEAR;
 
 use log::debug;
+use rayon::prelude::*;
+
+pub trait LatticeValuation {
+    fn value<O: FinancialOption + ?Sized>(&self, option: &O, exercise_style: &ExerciseStyle)
+        -> f64;
+    fn value_many<O: FinancialOption + Sync>(
+        &self,
+        options: &[O],
+        exercise_style: &ExerciseStyle,
+    ) -> Vec<f64>
+    where
+        Self: Sync,
+    {
+        options
+            .par_iter()
+            .map(|option| self.value(option, exercise_style))
+            .collect()
+    }
+}
 
-use std::collections::HashMap;
+fn exercise_value<O: FinancialOption + ?Sized>(option: &O, price: f64) -> f64 {
+    0.0f64.max(option.value_if_executed(price))
+}
 
-const EULERS_NUMBER: f64 = std::f64::consts::E;
+fn apply_exercise_style<O: FinancialOption + ?Sized>(
+    option: &O,
+    exercise_style: &ExerciseStyle,
+    price: f64,
+    continuation_value: f64,
+) -> f64 {
+    match exercise_style {
+        ExerciseStyle::European => continuation_value,
+        ExerciseStyle::American => continuation_value.max(exercise_value(option, price)),
+    }
+}
+
+#[derive(Debug)]
+pub struct TreeValuation {
+    pub value: f64,
+    pub delta: f64,
+    pub gamma: f64,
+    pub theta: f64,
+}
 
+// Nodes are never materialised, a layer is a flat array indexed by the number of up moves so
+// the up branch of node j is node j + 1 in the next layer and the down branch is node j
 #[derive(Debug)]
 pub struct Tree {
-    pub head: Node,
-    pub nodes: HashMap<Position, Node>,
-    pub valuation_cache: HashMap<Position, f64>,
+    pub num_steps: usize,
+    pub parameters: LatticeParameters,
+    pub risk_free_rate: f64,
+    pub dividend_adjustments: DividendAdjustments,
+    // Terminal prices of the escrowed lattice, before adding back dividends
+    pub terminal_prices: Vec<f64>,
 }
 
 impl Tree {
-    fn get_child_nodes(&self, node: &Node) -> Option<(Node, Node)> {
-        let (up_pos, down_pos) = node.pos.get_branches();
-        let up_node = self.nodes.get(&up_pos);
-        if up_node.is_none() {
-            return None;
-        }
-        self.nodes
-            .get(&down_pos)
-            .map(|down_node: &Node| (up_node.unwrap().clone(), down_node.clone()))
+    fn node_price(&self, pos: &Position) -> f64 {
+        let lattice_price = build::get_node_value(
+            self.dividend_adjustments.escrowed_price,
+            &self.parameters,
+            pos,
+        );
+        self.dividend_adjustments
+            .price(pos.num_ups + pos.num_downs, lattice_price)
     }
 
-    fn calculate_node<O: FinancialOption>(&mut self, node: &Node, option: &O, rfr: f64) -> f64 {
-        let value = self
-            .get_child_nodes(node)
-            .map(|(up_node, down_node)| -> f64 {
-                let duration = date_utils::get_duration_in_years(node.datetime, up_node.datetime);
-                let u = up_node.price / node.price;
-                let d = down_node.price / node.price;
-                debug!("Found u={}, d={}", u, d);
-                let p = (EULERS_NUMBER.powf(rfr * duration) - d) / (u - d);
-                debug!("Found p={}", p);
-                let up_value = self.value_node(&up_node, option, rfr);
-                let down_value = self.value_node(&down_node, option, rfr);
-                EULERS_NUMBER.powf(-rfr * duration) * ((p * up_value) + ((1.0f64 - p) * down_value))
+    // Backward induction from expiry, returning the option values on layer `stop_step`
+    fn induct_to_step<O: FinancialOption + ?Sized>(
+        &self,
+        option: &O,
+        exercise_style: &ExerciseStyle,
+        stop_step: usize,
+    ) -> Vec<f64> {
+        let p = self.parameters.probability;
+        let discount = self.parameters.step_discount(self.risk_free_rate);
+        let mut prices = self.terminal_prices.clone();
+        let mut values: Vec<f64> = prices
+            .iter()
+            .map(|price| {
+                exercise_value(
+                    option,
+                    self.dividend_adjustments.price(self.num_steps, *price),
+                )
             })
-            .unwrap_or(0.0f64.max(option.value_if_executed(node.price)));
-        self.valuation_cache.insert(node.pos.clone(), value);
+            .collect();
+        for step in (stop_step..self.num_steps).rev() {
+            for j in 0..=step {
+                prices[j] /= self.parameters.down;
+                let continuation_value =
+                    discount * ((p * values[j + 1]) + ((1.0f64 - p) * values[j]));
+                values[j] = apply_exercise_style(
+                    option,
+                    exercise_style,
+                    self.dividend_adjustments.price(step, prices[j]),
+                    continuation_value,
+                );
+            }
+        }
+        values.truncate(stop_step + 1);
         debug!(
-            "Calculated u={}, d={}, v={}",
-            node.pos.num_ups, node.pos.num_downs, value
+            "Inducted {} steps back to layer {}",
+            self.num_steps - stop_step,
+            stop_step
         );
-        value
+        values
     }
-    fn value_node<O: FinancialOption>(&mut self, node: &Node, option: &O, rfr: f64) -> f64 {
-        self.valuation_cache
-            .get(&node.pos)
-            .map(|f| f.clone())
-            .unwrap_or(self.calculate_node(node, option, rfr))
+    fn layer_prices(&self, step: usize) -> Vec<f64> {
+        (0..=step)
+            .map(|num_ups| Position {
+                num_ups,
+                num_downs: step - num_ups,
+            })
+            .map(|pos| self.node_price(&pos))
+            .collect()
     }
-    pub fn value<O: FinancialOption>(&mut self, option: &O, rfr: f64) -> f64 {
-        self.value_node(&self.head.clone(), option, rfr)
+
+    // Greeks are read off the first two layers, theta compares the middle node two steps in with
+    // the head, adjusting for any drift of that node away from the current price
+    pub fn value_with_greeks<O: FinancialOption + ?Sized>(
+        &self,
+        option: &O,
+        exercise_style: &ExerciseStyle,
+    ) -> PricerResult<TreeValuation> {
+        if self.num_steps < 2 {
+            return Err(PricerError::new(
+                "Lattice greeks require a tree with at least 2 steps".into(),
+                8,
+            ));
+        }
+        let p = self.parameters.probability;
+        let discount = self.parameters.step_discount(self.risk_free_rate);
+        let induct = |prices: &[f64], values: &[f64]| -> Vec<f64> {
+            prices
+                .iter()
+                .enumerate()
+                .map(|(j, price)| {
+                    let continuation_value =
+                        discount * ((p * values[j + 1]) + ((1.0f64 - p) * values[j]));
+                    apply_exercise_style(option, exercise_style, *price, continuation_value)
+                })
+                .collect()
+        };
+        let prices_0 = self.layer_prices(0);
+        let (prices_1, prices_2) = (self.layer_prices(1), self.layer_prices(2));
+        let values_2 = self.induct_to_step(option, exercise_style, 2);
+        let values_1 = induct(&prices_1, &values_2);
+        let value = induct(&prices_0, &values_1)[0];
+
+        let delta = (values_1[1] - values_1[0]) / (prices_1[1] - prices_1[0]);
+        let upper_delta = (values_2[2] - values_2[1]) / (prices_2[2] - prices_2[1]);
+        let lower_delta = (values_2[1] - values_2[0]) / (prices_2[1] - prices_2[0]);
+        let gamma = (upper_delta - lower_delta) / (0.5 * (prices_2[2] - prices_2[0]));
+
+        let drift = prices_0[0] - prices_2[1];
+        let middle_value = values_2[1] + delta * drift + 0.5 * gamma * drift.powi(2);
+        let theta = (middle_value - value) / (2.0 * self.parameters.dt);
+        Ok(TreeValuation {
+            value,
+            delta,
+            gamma,
+            theta: theta / DAYS_IN_YEAR as f64,
+        })
+    }
+}
+
+impl LatticeValuation for Tree {
+    fn value<O: FinancialOption + ?Sized>(
+        &self,
+        option: &O,
+        exercise_style: &ExerciseStyle,
+    ) -> f64 {
+        self.induct_to_step(option, exercise_style, 0)[0]
     }
 }
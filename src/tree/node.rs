@@ -1,5 +1,4 @@
-use chrono::prelude::Utc;
-use chrono::DateTime;
+use std::hash::{Hash, Hasher};
 
 #[derive(Clone, Debug, Eq, Hash)]
 pub struct Position {
@@ -7,30 +6,68 @@ pub struct Position {
     pub num_downs: usize,
 }
 
-impl Position {
-    pub fn get_branches(&self) -> (Position, Position) {
+impl PartialEq for Position {
+    fn eq(&self, other: &Self) -> bool {
+        self.num_ups == other.num_ups && self.num_downs == other.num_downs
+    }
+}
+
+// Trinomial nodes recombine, so positions are equal whenever they sit at the same step with the
+// same net number of up moves, e.g. one up and one down lands on the same node as two middles
+#[derive(Clone, Debug, Eq)]
+pub struct TrinomialPosition {
+    pub num_ups: usize,
+    pub num_middles: usize,
+    pub num_downs: usize,
+}
+
+impl TrinomialPosition {
+    pub fn from_layer_index(step: usize, layer_index: usize) -> TrinomialPosition {
+        let displacement = layer_index as i32 - step as i32;
+        let num_moves = displacement.unsigned_abs() as usize;
+        TrinomialPosition {
+            num_ups: if displacement > 0 { num_moves } else { 0 },
+            num_middles: step - num_moves,
+            num_downs: if displacement < 0 { num_moves } else { 0 },
+        }
+    }
+    pub fn get_branches(&self) -> (TrinomialPosition, TrinomialPosition, TrinomialPosition) {
         (
-            Position {
+            TrinomialPosition {
                 num_ups: self.num_ups + 1,
-                num_downs: self.num_downs,
+                ..self.clone()
+            },
+            TrinomialPosition {
+                num_middles: self.num_middles + 1,
+                ..self.clone()
             },
-            Position {
-                num_ups: self.num_ups,
+            TrinomialPosition {
                 num_downs: self.num_downs + 1,
+                ..self.clone()
             },
         )
     }
+    pub fn step(&self) -> usize {
+        self.num_ups + self.num_middles + self.num_downs
+    }
+    pub fn displacement(&self) -> i32 {
+        self.num_ups as i32 - self.num_downs as i32
+    }
+    // Index of the node within its layer, counting from the lowest node
+    pub fn layer_index(&self) -> usize {
+        (self.displacement() + self.step() as i32) as usize
+    }
 }
 
-impl PartialEq for Position {
+impl PartialEq for TrinomialPosition {
     fn eq(&self, other: &Self) -> bool {
-        self.num_ups == other.num_ups && self.num_downs == other.num_downs
+        self.step() == other.step() && self.displacement() == other.displacement()
     }
 }
 
-#[derive(Debug, Clone)]
-pub struct Node {
-    pub price: f64,
-    pub datetime: DateTime<Utc>,
-    pub pos: Position,
+impl Hash for TrinomialPosition {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.step().hash(state);
+        self.displacement().hash(state);
+    }
 }
@@ -0,0 +1,25 @@
+use crate::option::ExerciseStyle;
+use crate::tree::lattice::{LatticeScheme, TrinomialScheme};
+
+// Lattice the tree is built on, two branches per node or three
+#[derive(Clone, Debug)]
+pub enum TreeScheme {
+    Binomial(LatticeScheme),
+    Trinomial(TrinomialScheme),
+}
+
+pub struct TreeParams {
+    pub steps: usize,
+    pub scheme: TreeScheme,
+    pub exercise_style: ExerciseStyle,
+}
+
+impl Default for TreeParams {
+    fn default() -> Self {
+        TreeParams {
+            steps: 1000,
+            scheme: TreeScheme::Binomial(LatticeScheme::CoxRossRubinstein),
+            exercise_style: ExerciseStyle::European,
+        }
+    }
+}
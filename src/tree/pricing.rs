@@ -0,0 +1,165 @@
+use super::build::construct_tree;
+use super::inputs::TreeInputs;
+use super::params::{TreeParams, TreeScheme};
+use super::trinomial::{construct_trinomial_tree, TrinomialTree};
+use super::{LatticeValuation, Tree, TreeValuation};
+
+use crate::option::{Call, ExerciseStyle, FinancialOption, Put};
+use crate::result::{PricerError, PricerResult};
+use crate::risk_factors::RiskFactors;
+use crate::shock::{ApplyShock, Scenario};
+
+use chrono::{DateTime, Utc};
+
+// The tree `TreeParams::scheme` asks for
+enum Lattice {
+    Binomial(Tree),
+    Trinomial(TrinomialTree),
+}
+
+impl Lattice {
+    fn value_with_greeks<O: FinancialOption + ?Sized>(
+        &self,
+        option: &O,
+        exercise_style: &ExerciseStyle,
+    ) -> PricerResult<TreeValuation> {
+        match self {
+            Lattice::Binomial(tree) => tree.value_with_greeks(option, exercise_style),
+            Lattice::Trinomial(tree) => tree.value_with_greeks(option, exercise_style),
+        }
+    }
+}
+
+impl LatticeValuation for Lattice {
+    fn value<O: FinancialOption + ?Sized>(
+        &self,
+        option: &O,
+        exercise_style: &ExerciseStyle,
+    ) -> f64 {
+        match self {
+            Lattice::Binomial(tree) => tree.value(option, exercise_style),
+            Lattice::Trinomial(tree) => tree.value(option, exercise_style),
+        }
+    }
+}
+
+fn mixed_options_err() -> PricerError {
+    PricerError::new(
+        "Options valued off one lattice must share an underlying and expiry".into(),
+        17,
+    )
+}
+
+fn build_tree(inputs: &TreeInputs, parameters: &TreeParams) -> PricerResult<Lattice> {
+    let lattice_inputs = inputs.lattice_inputs();
+    let steps = parameters.steps as i32;
+    match &parameters.scheme {
+        TreeScheme::Binomial(scheme) => construct_tree(
+            &lattice_inputs,
+            scheme,
+            inputs.valuation_time,
+            inputs.expiry(),
+            steps,
+        )
+        .map(Lattice::Binomial),
+        TreeScheme::Trinomial(scheme) => construct_trinomial_tree(
+            &lattice_inputs,
+            scheme,
+            inputs.valuation_time,
+            inputs.expiry(),
+            steps,
+        )
+        .map(Lattice::Trinomial),
+    }
+}
+
+pub trait BinomialTree: FinancialOption {
+    fn value_tree_impl(&self, inputs: TreeInputs, parameters: &TreeParams) -> PricerResult<f64> {
+        build_tree(&inputs, parameters)
+            .map(|tree| tree.value(self, &parameters.exercise_style))
+            .map(|valuation| valuation - self.cost())
+    }
+    fn value_tree_with_greeks_impl(
+        &self,
+        inputs: TreeInputs,
+        parameters: &TreeParams,
+    ) -> PricerResult<TreeValuation> {
+        build_tree(&inputs, parameters)
+            .and_then(|tree| tree.value_with_greeks(self, &parameters.exercise_style))
+            .map(|valuation| TreeValuation {
+                value: valuation.value - self.cost(),
+                ..valuation
+            })
+    }
+    fn gather_tree_inputs(
+        &self,
+        valuation_time: DateTime<Utc>,
+        risk_factors: RiskFactors,
+        shock_scenarios: Scenario,
+    ) -> PricerResult<TreeInputs> {
+        risk_factors
+            .as_of(valuation_time)
+            .and_then(TryInto::try_into)
+            .map(|risk_factors| {
+                let mut inputs = TreeInputs::gather(self.expiry(), valuation_time, risk_factors);
+                shock_scenarios.apply(&mut inputs);
+                inputs
+            })
+    }
+    fn value_tree(
+        &self,
+        valuation_time: DateTime<Utc>,
+        risk_factors: RiskFactors,
+        shock_scenarios: Scenario,
+        parameters: &TreeParams,
+    ) -> PricerResult<f64> {
+        self.gather_tree_inputs(valuation_time, risk_factors, shock_scenarios)
+            .and_then(|inputs| self.value_tree_impl(inputs, parameters))
+    }
+    // Options that differ only in strike or cost are valued off the one lattice built for the
+    // first of them
+    fn value_tree_many(
+        options: &[Self],
+        valuation_time: DateTime<Utc>,
+        risk_factors: RiskFactors,
+        shock_scenarios: Scenario,
+        parameters: &TreeParams,
+    ) -> PricerResult<Vec<f64>>
+    where
+        Self: Sized + Sync,
+    {
+        let Some(first) = options.first() else {
+            return Ok(vec![]);
+        };
+        if options
+            .iter()
+            .any(|option| option.symbol() != first.symbol() || option.expiry() != first.expiry())
+        {
+            return Err(mixed_options_err());
+        }
+        first
+            .gather_tree_inputs(valuation_time, risk_factors, shock_scenarios)
+            .and_then(|inputs| build_tree(&inputs, parameters))
+            .map(|tree| {
+                tree.value_many(options, &parameters.exercise_style)
+                    .into_iter()
+                    .zip(options)
+                    .map(|(valuation, option)| valuation - option.cost())
+                    .collect()
+            })
+    }
+    fn value_tree_with_greeks(
+        &self,
+        valuation_time: DateTime<Utc>,
+        risk_factors: RiskFactors,
+        shock_scenarios: Scenario,
+        parameters: &TreeParams,
+    ) -> PricerResult<TreeValuation> {
+        self.gather_tree_inputs(valuation_time, risk_factors, shock_scenarios)
+            .and_then(|inputs| self.value_tree_with_greeks_impl(inputs, parameters))
+    }
+}
+
+impl BinomialTree for Call {}
+
+impl BinomialTree for Put {}
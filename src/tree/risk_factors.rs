@@ -0,0 +1,115 @@
+use crate::result::{PricerError, PricerResult};
+
+use crate::risk_factors::borrow::BorrowRate;
+use crate::risk_factors::discount::{DiscountFactor, DiscountRf};
+use crate::risk_factors::dividend::{Dividend, OutstandingDividend};
+use crate::risk_factors::price::{Price, PriceRf};
+use crate::risk_factors::volatility::{Volatility, VolatilityRf};
+use crate::risk_factors::RiskFactors;
+
+use crate::shock::{ApplyShock, Shock};
+
+use chrono::{DateTime, Utc};
+
+pub struct TreeRiskFactors {
+    price_risk_factor: Price,
+    volatility_risk_factor: Volatility,
+    discount_factor: DiscountFactor,
+    dividend_factor: Option<Dividend>,
+    borrow_factor: Option<BorrowRate>,
+}
+
+impl TreeRiskFactors {
+    pub fn discount_rate(&self, delta_t: f64) -> f64 {
+        self.discount_factor.zero_rate(delta_t)
+    }
+    pub fn dividend_yield(&self) -> f64 {
+        self.dividend_factor
+            .as_ref()
+            .map(|dividend| dividend.annualised_rate())
+            .unwrap_or(0.0)
+    }
+    pub fn borrow_rate(&self) -> f64 {
+        self.borrow_factor
+            .as_ref()
+            .map(|borrow| borrow.rate())
+            .unwrap_or(0.0)
+    }
+    pub fn outstanding_dividends(
+        &self,
+        expiry: DateTime<Utc>,
+        delta_t: f64,
+    ) -> Vec<OutstandingDividend> {
+        self.dividend_factor
+            .as_ref()
+            .map(|dividend| dividend.outstanding(expiry, delta_t))
+            .unwrap_or_default()
+    }
+    pub fn price(&self) -> f64 {
+        self.price_risk_factor.price()
+    }
+    pub fn volatility(&self, delta_t: f64) -> f64 {
+        self.volatility_risk_factor.volatility_to(delta_t)
+    }
+}
+
+fn too_many_rf_err(how_many: usize) -> PricerError {
+    PricerError::new(
+        format!("Provided {} risk factors, when 1 was expected", how_many),
+        1,
+    )
+}
+fn get_first_and_ensure_one<RF>(mut risk_factors: Vec<RF>) -> PricerResult<RF> {
+    if risk_factors.len() != 1 {
+        return Err(too_many_rf_err(risk_factors.len()));
+    }
+    Ok(risk_factors.remove(0))
+}
+
+fn get_at_most_one<RF>(risk_factors: Vec<RF>) -> PricerResult<Option<RF>> {
+    if risk_factors.is_empty() {
+        return Ok(None);
+    }
+    get_first_and_ensure_one(risk_factors).map(Some)
+}
+
+impl TryFrom<RiskFactors> for TreeRiskFactors {
+    type Error = PricerError;
+    fn try_from(risk_factors: RiskFactors) -> PricerResult<Self> {
+        let price_risk_factor = get_first_and_ensure_one(risk_factors.price_sensitivities)?;
+        let volatility_risk_factor =
+            get_first_and_ensure_one(risk_factors.volatility_sensitivities)?;
+        let discount_factor = get_first_and_ensure_one(risk_factors.discount_factors)?;
+        let dividend_factor = get_at_most_one(risk_factors.dividend_sensitivities)?;
+        let borrow_factor = get_at_most_one(risk_factors.borrow_sensitivities)?;
+        Ok(TreeRiskFactors {
+            price_risk_factor,
+            volatility_risk_factor,
+            discount_factor,
+            dividend_factor,
+            borrow_factor,
+        })
+    }
+}
+
+impl ApplyShock<TreeRiskFactors> for Shock {
+    fn apply(&self, applicant: &mut TreeRiskFactors) {
+        match self {
+            Shock::InterestRateShock(shock) => shock.apply(&mut applicant.discount_factor),
+            Shock::KeyRateShock(shock) => shock.apply(&mut applicant.discount_factor),
+            Shock::PriceShock(shock) => shock.apply(&mut applicant.price_risk_factor),
+            Shock::VolatilityShock(shock) => shock.apply(&mut applicant.volatility_risk_factor),
+            Shock::DividendShock(shock) => {
+                if let Some(dividend) = applicant.dividend_factor.as_mut() {
+                    shock.apply(dividend)
+                }
+            }
+            Shock::BorrowShock(shock) => {
+                if let Some(borrow) = applicant.borrow_factor.as_mut() {
+                    shock.apply(borrow)
+                }
+            }
+            _ => (),
+        }
+    }
+}
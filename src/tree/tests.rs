@@ -1,75 +1,572 @@
 #[cfg(test)]
-use crate::option::{get_call, get_put};
+use crate::black_scholes::{BlackScholes, BlackScholesGreeks};
+#[cfg(test)]
+use crate::greeks::FiniteDifferenceGreeks;
+#[cfg(test)]
+use crate::option::{get_call, get_put, Call, ExerciseStyle, FinancialOption};
+#[cfg(test)]
+use crate::result::PricerResult;
+#[cfg(test)]
+use crate::risk_factors::discount::rfr_discount;
+#[cfg(test)]
+use crate::risk_factors::dividend::DividendPayment;
+#[cfg(test)]
+use crate::shock::{price_shock, relative_percentage_shock, ShockDirection};
 #[cfg(test)]
 use crate::symbol::Symbol;
 #[cfg(test)]
-use crate::tree::{build::construct_tree, Tree};
+use crate::tree::lattice::{LatticeInputs, LatticeScheme, TrinomialScheme};
+#[cfg(test)]
+use crate::tree::trinomial::construct_trinomial_tree;
+#[cfg(test)]
+use crate::tree::{build::construct_tree, BinomialTree, LatticeValuation, Tree};
+#[cfg(test)]
+use crate::tree::{TreeParams, TreeScheme};
+#[cfg(test)]
+use crate::utils::test_utils::{get_test_call, get_test_dividend_schedule, get_test_put, is_close};
+#[cfg(test)]
+use crate::{Priceable, Pricer};
 
 #[cfg(test)]
 use chrono::prelude::Utc;
 #[cfg(test)]
+use chrono::DateTime;
+#[cfg(test)]
 use chrono::Datelike;
 #[cfg(test)]
 use chrono::TimeZone;
 
+#[cfg(test)]
+fn two_year_inputs() -> (LatticeInputs, DateTime<Utc>, DateTime<Utc>) {
+    let inputs = LatticeInputs {
+        underlying_price: 20.0,
+        volatility: 0.2,
+        risk_free_rate: 0.05,
+        dividend_yield: 0.0,
+        dividends: vec![],
+    };
+    let begin_date = Utc.timestamp_millis_opt(1688917143000).unwrap();
+    let end_date = begin_date.with_year(begin_date.year() + 2).unwrap();
+    (inputs, begin_date, end_date)
+}
+
+#[cfg(test)]
+fn value_with_scheme<O: FinancialOption>(option: &O, scheme: LatticeScheme, num_steps: i32) -> f64 {
+    let (inputs, begin_date, end_date) = two_year_inputs();
+    let tree: Tree = construct_tree(&inputs, &scheme, begin_date, end_date, num_steps).unwrap();
+    tree.value(option, &ExerciseStyle::European)
+}
+
+#[cfg(test)]
+fn value_with_trinomial_scheme<O: FinancialOption>(
+    option: &O,
+    scheme: TrinomialScheme,
+    num_steps: i32,
+    exercise_style: ExerciseStyle,
+) -> f64 {
+    let (inputs, begin_date, end_date) = two_year_inputs();
+    construct_trinomial_tree(&inputs, &scheme, begin_date, end_date, num_steps)
+        .unwrap()
+        .value(option, &exercise_style)
+}
+
+#[cfg(test)]
+fn black_scholes_value<O: BlackScholes>(option: &O) -> f64 {
+    let (inputs, begin_date, _) = two_year_inputs();
+    let risk_factors = option.get_black_scholes_risk_factors(
+        inputs.underlying_price,
+        inputs.volatility,
+        inputs.dividend_yield,
+        rfr_discount("US Treasury 3M".into(), inputs.risk_free_rate),
+    );
+    option
+        .value_black_scholes(begin_date, risk_factors, vec![])
+        .unwrap()
+}
+
 #[test]
 fn two_year_basic_put() {
-    let mock_symbol = Symbol::from("AAPL");
-    let underlying_price: f64 = 20.0;
-    let strike = 20.0;
-    let volatility = 0.2;
-    let begin_date = Utc.timestamp_millis_opt(1688917143000).unwrap();
-    let number_of_years = 2;
-    let end_date = begin_date
-        .with_year(begin_date.year() + number_of_years)
-        .unwrap();
-    let put = get_put(mock_symbol, strike, end_date, 0.0);
-    let num_steps = number_of_years;
-    let risk_free_rate = 0.05;
-    #[allow(unused_must_use)]
-    {
-        construct_tree(
-            underlying_price,
-            volatility,
-            begin_date,
-            end_date,
-            num_steps,
-        )
-        .map(|mut tree: Tree| {
-            let option_value = tree.value(&put, risk_free_rate);
-            assert!(option_value > 1.2377);
-            assert!(option_value < 1.2378);
-        });
+    let (_, _, end_date) = two_year_inputs();
+    let put = get_put(Symbol::from("AAPL"), 20.0, end_date, 0.0);
+    let black_scholes = black_scholes_value(&put);
+    let schemes = vec![
+        LatticeScheme::CoxRossRubinstein,
+        LatticeScheme::JarrowRudd,
+        LatticeScheme::Tian,
+    ];
+    for scheme in schemes {
+        let option_value = value_with_scheme(&put, scheme.clone(), 15);
+        assert!(
+            is_close(option_value, black_scholes, 0.03),
+            "{:?} put ({}) differs from Black-Scholes ({}) by more than 3%",
+            scheme,
+            option_value,
+            black_scholes
+        );
     }
 }
 
 #[test]
 fn two_year_basic_call() {
-    let mock_symbol = Symbol::from("AAPL");
-    let underlying_price: f64 = 20.0;
-    let strike = 20.0;
-    let volatility = 0.2;
-    let begin_date = Utc.timestamp_millis_opt(1688917143000).unwrap();
-    let number_of_years = 2;
-    let end_date = begin_date
-        .with_year(begin_date.year() + number_of_years)
-        .unwrap();
-    let call = get_call(mock_symbol, strike, end_date, 0.0);
-    let num_steps = number_of_years;
-    let risk_free_rate = 0.05;
-    #[allow(unused_must_use)]
-    {
-        construct_tree(
-            underlying_price,
-            volatility,
-            begin_date,
-            end_date,
-            num_steps,
-        )
-        .map(|mut tree: Tree| {
-            let option_value = tree.value(&call, risk_free_rate);
-            assert!(option_value > 3.1434);
-            assert!(option_value < 3.1435);
-        });
+    let (_, _, end_date) = two_year_inputs();
+    let call = get_call(Symbol::from("AAPL"), 20.0, end_date, 0.0);
+    let black_scholes = black_scholes_value(&call);
+    let schemes = vec![
+        LatticeScheme::CoxRossRubinstein,
+        LatticeScheme::JarrowRudd,
+        LatticeScheme::Tian,
+    ];
+    for scheme in schemes {
+        let option_value = value_with_scheme(&call, scheme.clone(), 15);
+        assert!(
+            is_close(option_value, black_scholes, 0.03),
+            "{:?} call ({}) differs from Black-Scholes ({}) by more than 3%",
+            scheme,
+            option_value,
+            black_scholes
+        );
+    }
+}
+
+#[test]
+fn leisen_reimer_call_converges_quickly() {
+    let (_, _, end_date) = two_year_inputs();
+    let strike = 22.0;
+    let call = get_call(Symbol::from("AAPL"), strike, end_date, 0.0);
+    let black_scholes = black_scholes_value(&call);
+    let option_value = value_with_scheme(&call, LatticeScheme::LeisenReimer { strike }, 15);
+    assert!(
+        is_close(option_value, black_scholes, 0.001),
+        "Leisen-Reimer call ({}) differs from Black-Scholes ({}) by more than 0.1%",
+        option_value,
+        black_scholes
+    );
+}
+
+#[test]
+fn cox_ross_rubinstein_converges_with_steps() {
+    let (_, _, end_date) = two_year_inputs();
+    let put = get_put(Symbol::from("AAPL"), 20.0, end_date, 0.0);
+    let black_scholes = black_scholes_value(&put);
+    let coarse = value_with_scheme(&put, LatticeScheme::CoxRossRubinstein, 2);
+    let fine = value_with_scheme(&put, LatticeScheme::CoxRossRubinstein, 16);
+    assert!((fine - black_scholes).abs() < (coarse - black_scholes).abs());
+}
+
+#[test]
+fn trinomial_schemes_near_black_scholes() {
+    let (_, _, end_date) = two_year_inputs();
+    let call = get_call(Symbol::from("AAPL"), 20.0, end_date, 0.0);
+    let put = get_put(Symbol::from("AAPL"), 20.0, end_date, 0.0);
+    let call_black_scholes = black_scholes_value(&call);
+    let put_black_scholes = black_scholes_value(&put);
+    let schemes = vec![
+        TrinomialScheme::Boyle {
+            stretch: 2.0f64.sqrt(),
+        },
+        TrinomialScheme::KamradRitchken {
+            stretch: 3.0f64.sqrt(),
+        },
+    ];
+    for scheme in schemes {
+        let call_value =
+            value_with_trinomial_scheme(&call, scheme.clone(), 200, ExerciseStyle::European);
+        let put_value =
+            value_with_trinomial_scheme(&put, scheme.clone(), 200, ExerciseStyle::European);
+        assert!(
+            is_close(call_value, call_black_scholes, 0.005),
+            "{:?} call ({}) differs from Black-Scholes ({}) by more than 0.5%",
+            scheme,
+            call_value,
+            call_black_scholes
+        );
+        assert!(
+            is_close(put_value, put_black_scholes, 0.005),
+            "{:?} put ({}) differs from Black-Scholes ({}) by more than 0.5%",
+            scheme,
+            put_value,
+            put_black_scholes
+        );
+    }
+}
+
+#[test]
+fn american_put_worth_more_than_european_on_lattices() {
+    let (_, _, end_date) = two_year_inputs();
+    let put = get_put(Symbol::from("AAPL"), 20.0, end_date, 0.0);
+    let scheme = TrinomialScheme::Boyle {
+        stretch: 2.0f64.sqrt(),
+    };
+    let european = value_with_trinomial_scheme(&put, scheme.clone(), 100, ExerciseStyle::European);
+    let american = value_with_trinomial_scheme(&put, scheme, 100, ExerciseStyle::American);
+    assert!(american > european);
+
+    let (inputs, begin_date, end_date) = two_year_inputs();
+    let tree = construct_tree(
+        &inputs,
+        &LatticeScheme::CoxRossRubinstein,
+        begin_date,
+        end_date,
+        15,
+    )
+    .unwrap();
+    let binomial_european = tree.value(&put, &ExerciseStyle::European);
+    let binomial_american = tree.value(&put, &ExerciseStyle::American);
+    assert!(binomial_american > binomial_european);
+    assert!(
+        is_close(binomial_american, american, 0.03),
+        "Binomial American put ({}) differs from trinomial ({}) by more than 3%",
+        binomial_american,
+        american
+    );
+}
+
+#[test]
+fn american_call_without_dividends_matches_european_on_trinomial_tree() {
+    let (_, _, end_date) = two_year_inputs();
+    let call = get_call(Symbol::from("AAPL"), 20.0, end_date, 0.0);
+    let scheme = TrinomialScheme::KamradRitchken {
+        stretch: 3.0f64.sqrt(),
+    };
+    let european = value_with_trinomial_scheme(&call, scheme.clone(), 100, ExerciseStyle::European);
+    let american = value_with_trinomial_scheme(&call, scheme, 100, ExerciseStyle::American);
+    assert!((american - european).abs() < 1e-9);
+}
+
+#[test]
+fn five_thousand_step_tree_converges_to_black_scholes() {
+    let (_, _, end_date) = two_year_inputs();
+    let put = get_put(Symbol::from("AAPL"), 20.0, end_date, 0.0);
+    let black_scholes = black_scholes_value(&put);
+    let option_value = value_with_scheme(&put, LatticeScheme::CoxRossRubinstein, 5000);
+    assert!(
+        is_close(option_value, black_scholes, 0.0005),
+        "5000 step tree ({}) differs from Black-Scholes ({}) by more than 0.05%",
+        option_value,
+        black_scholes
+    );
+}
+
+#[test]
+fn many_strikes_valued_off_one_lattice() {
+    let (inputs, begin_date, end_date) = two_year_inputs();
+    let tree = construct_tree(
+        &inputs,
+        &LatticeScheme::CoxRossRubinstein,
+        begin_date,
+        end_date,
+        1001,
+    )
+    .unwrap();
+    let calls: Vec<_> = [16.0, 18.0, 20.0, 22.0, 24.0]
+        .iter()
+        .map(|strike| get_call(Symbol::from("AAPL"), *strike, end_date, 0.0))
+        .collect();
+    let values = tree.value_many(&calls, &ExerciseStyle::European);
+    assert!(values.len() == calls.len());
+    assert!(values.windows(2).all(|pair| pair[0] > pair[1]));
+    for (call, option_value) in calls.iter().zip(values) {
+        let black_scholes = black_scholes_value(call);
+        assert!(
+            is_close(option_value, black_scholes, 0.005),
+            "Tree call ({}) differs from Black-Scholes ({}) by more than 0.5%",
+            option_value,
+            black_scholes
+        );
+    }
+}
+
+#[test]
+fn binomial_tree_values_many_strikes() -> PricerResult<()> {
+    let (call, valuation_time, risk_factors) = get_test_call();
+    let calls: Vec<_> = [36.0, 40.0, 44.0]
+        .iter()
+        .map(|strike| get_call(call.symbol().clone(), *strike, call.expiry(), 0.0))
+        .collect();
+    let parameters = TreeParams::default();
+    let values = Call::value_tree_many(
+        &calls,
+        valuation_time,
+        risk_factors.clone(),
+        vec![],
+        &parameters,
+    )?;
+    for (call, option_value) in calls.iter().zip(values) {
+        let single = call.value_tree(valuation_time, risk_factors.clone(), vec![], &parameters)?;
+        assert!((option_value - single).abs() < 1e-12);
+    }
+    let mixed = [
+        get_call(call.symbol().clone(), 40.0, call.expiry(), 0.0),
+        get_call(call.symbol().clone(), 40.0, valuation_time, 0.0),
+    ];
+    assert!(
+        Call::value_tree_many(&mixed, valuation_time, risk_factors, vec![], &parameters)
+            .is_err_and(|error| error.code == 17)
+    );
+    Ok(())
+}
+
+#[test]
+fn priceable_tree_near_black_scholes() -> PricerResult<()> {
+    let (call, valuation_time, risk_factors) = get_test_call();
+    let black_scholes = call.value_black_scholes(valuation_time, risk_factors.clone(), vec![])?;
+    let priceable = Priceable::Tree(&call, TreeParams::default());
+    let tree_value = priceable.value(valuation_time, risk_factors, vec![])?;
+    assert!(
+        is_close(tree_value, black_scholes, 0.001),
+        "Tree valuation ({}) differs from Black-Scholes ({}) by more than 0.1%",
+        tree_value,
+        black_scholes
+    );
+    Ok(())
+}
+
+#[test]
+fn tree_applies_price_shocks() -> PricerResult<()> {
+    let (call, valuation_time, risk_factors) = get_test_call();
+    let shocked_price = call.value_black_scholes(
+        valuation_time,
+        risk_factors.clone(),
+        vec![price_shock(
+            Symbol::from("AAPL"),
+            relative_percentage_shock(10.0, ShockDirection::Up),
+        )],
+    )?;
+    let shocked_tree = call.value_tree(
+        valuation_time,
+        risk_factors,
+        vec![price_shock(
+            Symbol::from("AAPL"),
+            relative_percentage_shock(10.0, ShockDirection::Up),
+        )],
+        &TreeParams::default(),
+    )?;
+    assert!(
+        is_close(shocked_tree, shocked_price, 0.001),
+        "Shocked tree valuation ({}) differs from shocked Black-Scholes ({}) by more than 0.1%",
+        shocked_tree,
+        shocked_price
+    );
+    Ok(())
+}
+
+#[test]
+fn tree_finite_difference_greeks_near_analytical_greeks() -> PricerResult<()> {
+    let (call, valuation_time, risk_factors) = get_test_call();
+    let priceable = Priceable::Tree(&call, TreeParams::default());
+    let greeks = [
+        (
+            "delta",
+            priceable.delta_fd(valuation_time, risk_factors.clone())?,
+            call.delta(valuation_time, risk_factors.clone())?,
+        ),
+        (
+            "vega",
+            priceable.vega_fd(valuation_time, risk_factors.clone())?,
+            call.vega(valuation_time, risk_factors.clone())?,
+        ),
+        (
+            "rho",
+            priceable.rho_fd(valuation_time, risk_factors.clone())?,
+            call.rho(valuation_time, risk_factors.clone())?,
+        ),
+        (
+            "theta",
+            priceable.theta_fd(valuation_time, risk_factors.clone())?,
+            call.theta(valuation_time, risk_factors)?,
+        ),
+    ];
+    for (name, finite_difference, analytic) in greeks {
+        assert!(
+            is_close(finite_difference, analytic, 0.1),
+            "Tree finite difference {} ({}) differs from analytical {} ({}) by more than 10%",
+            name,
+            finite_difference,
+            name,
+            analytic
+        );
+    }
+    Ok(())
+}
+
+#[test]
+fn american_tree_put_greeks() -> PricerResult<()> {
+    let (put, valuation_time, risk_factors) = get_test_put();
+    let european = Priceable::Tree(&put, TreeParams::default());
+    let american = Priceable::Tree(
+        &put,
+        TreeParams {
+            exercise_style: ExerciseStyle::American,
+            ..Default::default()
+        },
+    );
+    let european_value = european.value(valuation_time, risk_factors.clone(), vec![])?;
+    let american_value = american.value(valuation_time, risk_factors.clone(), vec![])?;
+    assert!(american_value > european_value);
+    let american_delta = american.delta_fd(valuation_time, risk_factors)?;
+    assert!(american_delta < 0.0 && american_delta > -1.0);
+    Ok(())
+}
+
+#[test]
+fn lattice_greeks_near_analytical_greeks() -> PricerResult<()> {
+    let (call, valuation_time, risk_factors) = get_test_call();
+    let lattice = call.value_tree_with_greeks(
+        valuation_time,
+        risk_factors.clone(),
+        vec![],
+        &TreeParams::default(),
+    )?;
+    let value = call.value_tree(
+        valuation_time,
+        risk_factors.clone(),
+        vec![],
+        &TreeParams::default(),
+    )?;
+    assert!((lattice.value - value).abs() < 1e-12);
+    let greeks = [
+        (
+            "delta",
+            lattice.delta,
+            call.delta(valuation_time, risk_factors.clone())?,
+        ),
+        (
+            "gamma",
+            lattice.gamma,
+            call.gamma(valuation_time, risk_factors.clone())?,
+        ),
+        (
+            "theta",
+            lattice.theta,
+            call.theta(valuation_time, risk_factors)?,
+        ),
+    ];
+    for (name, lattice_greek, analytic) in greeks {
+        assert!(
+            is_close(lattice_greek, analytic, 0.01),
+            "Lattice {} ({}) differs from analytical {} ({}) by more than 1%",
+            name,
+            lattice_greek,
+            name,
+            analytic
+        );
+    }
+    Ok(())
+}
+
+#[test]
+fn american_lattice_greeks_near_finite_difference_greeks() -> PricerResult<()> {
+    let (put, valuation_time, risk_factors) = get_test_put();
+    let parameters = TreeParams {
+        exercise_style: ExerciseStyle::American,
+        ..Default::default()
+    };
+    let lattice =
+        put.value_tree_with_greeks(valuation_time, risk_factors.clone(), vec![], &parameters)?;
+    let priceable = Priceable::Tree(&put, parameters);
+    let delta_fd = priceable.delta_fd(valuation_time, risk_factors.clone())?;
+    let theta_fd = priceable.theta_fd(valuation_time, risk_factors)?;
+    // Finite difference greeks are forward differences with a bump of 1, so only loosely agree
+    assert!(
+        is_close(lattice.delta, delta_fd, 0.2),
+        "Lattice delta ({}) differs from finite difference delta ({}) by more than 20%",
+        lattice.delta,
+        delta_fd
+    );
+    assert!(
+        is_close(lattice.theta, theta_fd, 0.1),
+        "Lattice theta ({}) differs from finite difference theta ({}) by more than 10%",
+        lattice.theta,
+        theta_fd
+    );
+    assert!(lattice.gamma > 0.0);
+    Ok(())
+}
+
+#[test]
+fn tree_with_cash_dividend_near_black_scholes() -> PricerResult<()> {
+    let (call, valuation_time, mut risk_factors) = get_test_call();
+    risk_factors.dividend_sensitivities =
+        vec![get_test_dividend_schedule(DividendPayment::Cash(1.0))];
+    let black_scholes = call.value_black_scholes(valuation_time, risk_factors.clone(), vec![])?;
+    let tree_value =
+        call.value_tree(valuation_time, risk_factors, vec![], &TreeParams::default())?;
+    assert!(
+        is_close(tree_value, black_scholes, 0.001),
+        "Tree valuation with a cash dividend ({}) differs from Black-Scholes ({}) by more than 0.1%",
+        tree_value,
+        black_scholes
+    );
+    Ok(())
+}
+
+#[test]
+fn american_call_with_dividend_worth_more_than_european() -> PricerResult<()> {
+    let (call, valuation_time, mut risk_factors) = get_test_call();
+    risk_factors.dividend_sensitivities =
+        vec![get_test_dividend_schedule(DividendPayment::Cash(3.0))];
+    let european = call.value_tree(
+        valuation_time,
+        risk_factors.clone(),
+        vec![],
+        &TreeParams::default(),
+    )?;
+    let american = call.value_tree(
+        valuation_time,
+        risk_factors,
+        vec![],
+        &TreeParams {
+            exercise_style: ExerciseStyle::American,
+            ..Default::default()
+        },
+    )?;
+    assert!(
+        american > european,
+        "American call ({}) should be worth more than European call ({}) ahead of a dividend",
+        american,
+        european
+    );
+    Ok(())
+}
+
+#[test]
+fn trinomial_scheme_reaches_priceable_tree() -> PricerResult<()> {
+    let (put, valuation_time, risk_factors) = get_test_put();
+    let binomial = TreeParams {
+        exercise_style: ExerciseStyle::American,
+        ..Default::default()
+    };
+    let trinomial = TreeParams {
+        steps: 500,
+        scheme: TreeScheme::Trinomial(TrinomialScheme::KamradRitchken {
+            stretch: 3.0f64.sqrt(),
+        }),
+        exercise_style: ExerciseStyle::American,
+    };
+    let binomial_lattice =
+        put.value_tree_with_greeks(valuation_time, risk_factors.clone(), vec![], &binomial)?;
+    let trinomial_lattice =
+        put.value_tree_with_greeks(valuation_time, risk_factors.clone(), vec![], &trinomial)?;
+    let trinomial_value =
+        Priceable::Tree(&put, trinomial).value(valuation_time, risk_factors, vec![])?;
+    assert!((trinomial_value - trinomial_lattice.value).abs() < 1e-12);
+    assert!(
+        is_close(trinomial_value, binomial_lattice.value, 0.002),
+        "Trinomial American put ({}) differs from binomial ({}) by more than 0.2%",
+        trinomial_value,
+        binomial_lattice.value
+    );
+    for (trinomial_greek, binomial_greek) in [
+        (trinomial_lattice.delta, binomial_lattice.delta),
+        (trinomial_lattice.gamma, binomial_lattice.gamma),
+        (trinomial_lattice.theta, binomial_lattice.theta),
+    ] {
+        assert!(
+            is_close(trinomial_greek, binomial_greek, 0.02),
+            "Trinomial lattice greek ({}) differs from binomial ({}) by more than 2%",
+            trinomial_greek,
+            binomial_greek
+        );
     }
+    Ok(())
 }
@@ -0,0 +1,150 @@
+use crate::tree::lattice::{
+    DividendAdjustments, LatticeInputs, TrinomialParameters, TrinomialScheme,
+};
+use crate::tree::node::TrinomialPosition;
+use crate::tree::{apply_exercise_style, exercise_value, LatticeValuation, TreeValuation};
+
+use crate::option::{ExerciseStyle, FinancialOption};
+use crate::result::{PricerError, PricerResult};
+use crate::utils::date as date_utils;
+use crate::utils::date::DAYS_IN_YEAR;
+
+use chrono::prelude::Utc;
+use chrono::DateTime;
+
+#[derive(Debug)]
+pub struct TrinomialTree {
+    pub num_steps: usize,
+    pub parameters: TrinomialParameters,
+    pub risk_free_rate: f64,
+    pub dividend_adjustments: DividendAdjustments,
+}
+
+pub fn get_trinomial_layer(step: usize) -> Vec<TrinomialPosition> {
+    (0..=2 * step)
+        .map(|layer_index| TrinomialPosition::from_layer_index(step, layer_index))
+        .collect()
+}
+
+impl TrinomialTree {
+    pub fn price_at(&self, position: &TrinomialPosition) -> f64 {
+        let displacement = position.displacement();
+        let move_multi = if displacement >= 0 {
+            self.parameters.up.powi(displacement)
+        } else {
+            self.parameters.down.powi(-displacement)
+        };
+        self.dividend_adjustments.price(
+            position.step(),
+            self.dividend_adjustments.escrowed_price * move_multi,
+        )
+    }
+
+    fn layer_prices(&self, step: usize) -> Vec<f64> {
+        get_trinomial_layer(step)
+            .iter()
+            .map(|pos| self.price_at(pos))
+            .collect()
+    }
+
+    // Backward induction from expiry, returning the option values on layer `stop_step` from the
+    // lowest node up
+    fn induct_to_step<O: FinancialOption + ?Sized>(
+        &self,
+        option: &O,
+        exercise_style: &ExerciseStyle,
+        stop_step: usize,
+    ) -> Vec<f64> {
+        let discount = self.parameters.step_discount(self.risk_free_rate);
+        let mut values: Vec<f64> = get_trinomial_layer(self.num_steps)
+            .iter()
+            .map(|pos| exercise_value(option, self.price_at(pos)))
+            .collect();
+        for step in (stop_step..self.num_steps).rev() {
+            values = get_trinomial_layer(step)
+                .iter()
+                .map(|pos| {
+                    let (up, middle, down) = pos.get_branches();
+                    let continuation_value = discount
+                        * (self.parameters.up_probability * values[up.layer_index()]
+                            + self.parameters.middle_probability * values[middle.layer_index()]
+                            + self.parameters.down_probability * values[down.layer_index()]);
+                    apply_exercise_style(
+                        option,
+                        exercise_style,
+                        self.price_at(pos),
+                        continuation_value,
+                    )
+                })
+                .collect();
+        }
+        values
+    }
+
+    // Greeks are read off the three nodes of the first layer, theta compares the middle node with
+    // the head, adjusting for any drift of that node away from the current price
+    pub fn value_with_greeks<O: FinancialOption + ?Sized>(
+        &self,
+        option: &O,
+        exercise_style: &ExerciseStyle,
+    ) -> PricerResult<TreeValuation> {
+        if self.num_steps < 1 {
+            return Err(PricerError::new(
+                "Lattice greeks require a trinomial tree with at least 1 step".into(),
+                8,
+            ));
+        }
+        let prices = self.layer_prices(1);
+        let values = self.induct_to_step(option, exercise_style, 1);
+        let head_price = self.layer_prices(0)[0];
+        let discount = self.parameters.step_discount(self.risk_free_rate);
+        let continuation_value = discount
+            * (self.parameters.up_probability * values[2]
+                + self.parameters.middle_probability * values[1]
+                + self.parameters.down_probability * values[0]);
+        let value = apply_exercise_style(option, exercise_style, head_price, continuation_value);
+
+        let delta = (values[2] - values[0]) / (prices[2] - prices[0]);
+        let upper_delta = (values[2] - values[1]) / (prices[2] - prices[1]);
+        let lower_delta = (values[1] - values[0]) / (prices[1] - prices[0]);
+        let gamma = (upper_delta - lower_delta) / (0.5 * (prices[2] - prices[0]));
+
+        let drift = head_price - prices[1];
+        let middle_value = values[1] + delta * drift + 0.5 * gamma * drift.powi(2);
+        let theta = (middle_value - value) / self.parameters.dt;
+        Ok(TreeValuation {
+            value,
+            delta,
+            gamma,
+            theta: theta / DAYS_IN_YEAR as f64,
+        })
+    }
+}
+
+impl LatticeValuation for TrinomialTree {
+    fn value<O: FinancialOption + ?Sized>(
+        &self,
+        option: &O,
+        exercise_style: &ExerciseStyle,
+    ) -> f64 {
+        self.induct_to_step(option, exercise_style, 0)[0]
+    }
+}
+
+pub fn construct_trinomial_tree(
+    inputs: &LatticeInputs,
+    scheme: &TrinomialScheme,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    num_steps: i32,
+) -> PricerResult<TrinomialTree> {
+    let delta_t = date_utils::get_duration_in_years(start, end);
+    let parameters = scheme.parameters(inputs, delta_t, num_steps as usize)?;
+    let dividend_adjustments = DividendAdjustments::new(inputs, parameters.dt, num_steps as usize);
+    Ok(TrinomialTree {
+        num_steps: num_steps as usize,
+        parameters,
+        risk_free_rate: inputs.risk_free_rate,
+        dividend_adjustments,
+    })
+}
@@ -0,0 +1,166 @@
+use std::cell::RefCell;
+use std::ops::{Add, Div, Mul, Neg, Sub};
+
+// Sources of equations:
+//  - Giles & Glasserman (2006), Smoking adjoints: fast Monte Carlo greeks
+//  - Griewank & Walther (2008), Evaluating derivatives, chapter 3
+
+// Reverse-mode automatic differentiation. Every operation on a `Var` records its local partial
+// derivatives on the tape, a single backward sweep then gives the derivative of one output with
+// respect to every recorded variable
+#[derive(Default)]
+pub struct Tape {
+    nodes: RefCell<Vec<[(usize, f64); 2]>>,
+}
+
+#[derive(Clone, Copy)]
+pub struct Var<'t> {
+    tape: &'t Tape,
+    index: usize,
+    value: f64,
+}
+
+impl Tape {
+    pub fn new() -> Tape {
+        Tape::default()
+    }
+    fn push(&self, value: f64, parents: [(usize, f64); 2]) -> Var<'_> {
+        let mut nodes = self.nodes.borrow_mut();
+        nodes.push(parents);
+        Var {
+            tape: self,
+            index: nodes.len() - 1,
+            value,
+        }
+    }
+    // An independent input, whose derivative is read from the adjoints by its index
+    pub fn variable(&self, value: f64) -> Var<'_> {
+        let index = self.nodes.borrow().len();
+        self.push(value, [(index, 0.0), (index, 0.0)])
+    }
+}
+
+impl<'t> Var<'t> {
+    pub fn value(&self) -> f64 {
+        self.value
+    }
+    pub fn index(&self) -> usize {
+        self.index
+    }
+    fn unary(&self, value: f64, partial: f64) -> Var<'t> {
+        self.tape
+            .push(value, [(self.index, partial), (self.index, 0.0)])
+    }
+    fn binary(&self, other: &Var<'t>, value: f64, partials: (f64, f64)) -> Var<'t> {
+        self.tape
+            .push(value, [(self.index, partials.0), (other.index, partials.1)])
+    }
+    pub fn exp(&self) -> Var<'t> {
+        let value = self.value.exp();
+        self.unary(value, value)
+    }
+    pub fn ln(&self) -> Var<'t> {
+        self.unary(self.value.ln(), 1.0 / self.value)
+    }
+    pub fn sqrt(&self) -> Var<'t> {
+        let value = self.value.sqrt();
+        self.unary(value, 0.5 / value)
+    }
+    // The larger of the variable and a constant floor, flat below the floor
+    pub fn max(&self, floor: f64) -> Var<'t> {
+        if self.value > floor {
+            self.unary(self.value, 1.0)
+        } else {
+            self.unary(floor, 0.0)
+        }
+    }
+    // Derivatives of this variable with respect to every node on the tape, indexed like the tape
+    pub fn adjoints(&self) -> Vec<f64> {
+        let nodes = self.tape.nodes.borrow();
+        let mut adjoints = vec![0.0; self.index + 1];
+        adjoints[self.index] = 1.0;
+        for index in (0..=self.index).rev() {
+            let adjoint = adjoints[index];
+            if adjoint == 0.0 {
+                continue;
+            }
+            for (parent, partial) in nodes[index] {
+                if parent != index {
+                    adjoints[parent] += partial * adjoint;
+                }
+            }
+        }
+        adjoints
+    }
+}
+
+impl<'t> Add for Var<'t> {
+    type Output = Var<'t>;
+    fn add(self, rhs: Var<'t>) -> Var<'t> {
+        self.binary(&rhs, self.value + rhs.value, (1.0, 1.0))
+    }
+}
+
+impl<'t> Sub for Var<'t> {
+    type Output = Var<'t>;
+    fn sub(self, rhs: Var<'t>) -> Var<'t> {
+        self.binary(&rhs, self.value - rhs.value, (1.0, -1.0))
+    }
+}
+
+impl<'t> Mul for Var<'t> {
+    type Output = Var<'t>;
+    fn mul(self, rhs: Var<'t>) -> Var<'t> {
+        self.binary(&rhs, self.value * rhs.value, (rhs.value, self.value))
+    }
+}
+
+impl<'t> Div for Var<'t> {
+    type Output = Var<'t>;
+    fn div(self, rhs: Var<'t>) -> Var<'t> {
+        let value = self.value / rhs.value;
+        self.binary(&rhs, value, (1.0 / rhs.value, -value / rhs.value))
+    }
+}
+
+impl<'t> Neg for Var<'t> {
+    type Output = Var<'t>;
+    fn neg(self) -> Var<'t> {
+        self.unary(-self.value, -1.0)
+    }
+}
+
+impl<'t> Add<f64> for Var<'t> {
+    type Output = Var<'t>;
+    fn add(self, rhs: f64) -> Var<'t> {
+        self.unary(self.value + rhs, 1.0)
+    }
+}
+
+impl<'t> Sub<f64> for Var<'t> {
+    type Output = Var<'t>;
+    fn sub(self, rhs: f64) -> Var<'t> {
+        self.unary(self.value - rhs, 1.0)
+    }
+}
+
+impl<'t> Sub<Var<'t>> for f64 {
+    type Output = Var<'t>;
+    fn sub(self, rhs: Var<'t>) -> Var<'t> {
+        rhs.unary(self - rhs.value, -1.0)
+    }
+}
+
+impl<'t> Mul<f64> for Var<'t> {
+    type Output = Var<'t>;
+    fn mul(self, rhs: f64) -> Var<'t> {
+        self.unary(self.value * rhs, rhs)
+    }
+}
+
+impl<'t> Mul<Var<'t>> for f64 {
+    type Output = Var<'t>;
+    fn mul(self, rhs: Var<'t>) -> Var<'t> {
+        rhs * self
+    }
+}
@@ -1,13 +1,22 @@
 use chrono::prelude::Utc;
 use chrono::DateTime;
 
+const NUMBER_OF_SECONDS_IN_A_YEAR: f64 = 31536000.0;
+
+// Thetas are quoted per calendar day
+pub static DAYS_IN_YEAR: u32 = 365;
+
 pub fn get_duration_in_years(t1: DateTime<Utc>, t2: DateTime<Utc>) -> f64 {
     let diff: chrono::Duration = t2 - t1;
     let diff_in_secs: i64 = diff.num_seconds();
-    const NUMBER_OF_SECONDS_IN_A_YEAR: f64 = 31536000.0;
     diff_in_secs as f64 / NUMBER_OF_SECONDS_IN_A_YEAR
 }
 
+pub fn shift_by_years(t: DateTime<Utc>, years: f64) -> DateTime<Utc> {
+    let diff_in_secs = (years * NUMBER_OF_SECONDS_IN_A_YEAR).round() as i64;
+    t + chrono::Duration::seconds(diff_in_secs)
+}
+
 pub fn get_datetime_range(
     start: DateTime<Utc>,
     end: DateTime<Utc>,
@@ -0,0 +1,153 @@
+// Dense linear algebra on row-major `Vec<Vec<f64>>` matrices, sized for the handful of unknowns
+// in calibration and regression problems
+
+const SINGULARITY_TOLERANCE: f64 = 1e-14;
+
+// Solves `matrix * x = rhs` by Gaussian elimination with partial pivoting, None when singular
+pub fn solve(matrix: &[Vec<f64>], rhs: &[f64]) -> Option<Vec<f64>> {
+    let n = rhs.len();
+    let scale = matrix
+        .iter()
+        .flatten()
+        .fold(0.0f64, |scale, value| scale.max(value.abs()));
+    let mut augmented: Vec<Vec<f64>> = matrix
+        .iter()
+        .zip(rhs)
+        .map(|(row, value)| row.iter().copied().chain([*value]).collect())
+        .collect();
+    for column in 0..n {
+        let pivot = (column..n).max_by(|lhs, rhs| {
+            augmented[*lhs][column]
+                .abs()
+                .total_cmp(&augmented[*rhs][column].abs())
+        })?;
+        if augmented[pivot][column].abs() <= SINGULARITY_TOLERANCE * scale.max(1.0) {
+            return None;
+        }
+        augmented.swap(column, pivot);
+        let (pivot_rows, rows_below) = augmented.split_at_mut(column + 1);
+        let pivot_row = &pivot_rows[column];
+        for row in rows_below {
+            let factor = row[column] / pivot_row[column];
+            for (value, pivot_value) in row[column..].iter_mut().zip(&pivot_row[column..]) {
+                *value -= factor * pivot_value;
+            }
+        }
+    }
+    let mut solution = vec![0.0; n];
+    for row in (0..n).rev() {
+        let known: f64 = (row + 1..n).map(|k| augmented[row][k] * solution[k]).sum();
+        solution[row] = (augmented[row][n] - known) / augmented[row][row];
+    }
+    Some(solution)
+}
+
+pub fn invert(matrix: &[Vec<f64>]) -> Option<Vec<Vec<f64>>> {
+    let n = matrix.len();
+    let columns: Option<Vec<Vec<f64>>> = (0..n)
+        .map(|column| {
+            let unit: Vec<f64> = (0..n).map(|row| (row == column) as u8 as f64).collect();
+            solve(matrix, &unit)
+        })
+        .collect();
+    let columns = columns?;
+    Some(
+        (0..n)
+            .map(|row| columns.iter().map(|column| column[row]).collect())
+            .collect(),
+    )
+}
+
+// Transpose of `matrix` times itself
+pub fn gram(matrix: &[Vec<f64>]) -> Vec<Vec<f64>> {
+    let columns = matrix.first().map_or(0, |row| row.len());
+    (0..columns)
+        .map(|i| {
+            (0..columns)
+                .map(|j| matrix.iter().map(|row| row[i] * row[j]).sum())
+                .collect()
+        })
+        .collect()
+}
+
+// Transpose of `matrix` times `vector`
+pub fn transpose_times(matrix: &[Vec<f64>], vector: &[f64]) -> Vec<f64> {
+    let columns = matrix.first().map_or(0, |row| row.len());
+    (0..columns)
+        .map(|i| matrix.iter().zip(vector).map(|(row, v)| row[i] * v).sum())
+        .collect()
+}
+
+// Least squares solution of `matrix * x ≈ rhs` by Householder QR, which unlike the normal
+// equations does not square the condition number. A positive `ridge` adds the penalty
+// `ridge * |x|²` as extra rows. None when there are fewer rows than unknowns or the columns are
+// linearly dependent
+pub fn least_squares(matrix: &[Vec<f64>], rhs: &[f64], ridge: f64) -> Option<Vec<f64>> {
+    let columns = matrix.first().map_or(0, |row| row.len());
+    let mut rows: Vec<Vec<f64>> = matrix.to_vec();
+    let mut targets = rhs.to_vec();
+    if ridge > 0.0 {
+        for column in 0..columns {
+            let mut row = vec![0.0; columns];
+            row[column] = ridge.sqrt();
+            rows.push(row);
+            targets.push(0.0);
+        }
+    }
+    if rows.len() < columns {
+        return None;
+    }
+    let scale = rows
+        .iter()
+        .flatten()
+        .fold(0.0f64, |scale, value| scale.max(value.abs()));
+    for column in 0..columns {
+        let norm = rows[column..]
+            .iter()
+            .map(|row| row[column].powi(2))
+            .sum::<f64>()
+            .sqrt();
+        if norm <= SINGULARITY_TOLERANCE * scale.max(1.0) {
+            return None;
+        }
+        // Reflects the column below the diagonal onto the diagonal, choosing the sign that
+        // avoids cancellation
+        let diagonal = if rows[column][column] > 0.0 {
+            -norm
+        } else {
+            norm
+        };
+        let mut reflector: Vec<f64> = rows[column..].iter().map(|row| row[column]).collect();
+        reflector[0] -= diagonal;
+        let reflector_norm: f64 = reflector.iter().map(|value| value.powi(2)).sum();
+        if reflector_norm == 0.0 {
+            continue;
+        }
+        for other in column..columns {
+            let projection: f64 = rows[column..]
+                .iter()
+                .zip(&reflector)
+                .map(|(row, v)| row[other] * v)
+                .sum();
+            let factor = 2.0 * projection / reflector_norm;
+            for (row, v) in rows[column..].iter_mut().zip(&reflector) {
+                row[other] -= factor * v;
+            }
+        }
+        let projection: f64 = targets[column..]
+            .iter()
+            .zip(&reflector)
+            .map(|(target, v)| target * v)
+            .sum();
+        let factor = 2.0 * projection / reflector_norm;
+        for (target, v) in targets[column..].iter_mut().zip(&reflector) {
+            *target -= factor * v;
+        }
+    }
+    let mut solution = vec![0.0; columns];
+    for row in (0..columns).rev() {
+        let known: f64 = (row + 1..columns).map(|k| rows[row][k] * solution[k]).sum();
+        solution[row] = (targets[row] - known) / rows[row][row];
+    }
+    Some(solution)
+}
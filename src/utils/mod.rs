@@ -1,2 +1,5 @@
+pub mod aad;
 pub mod date;
+pub mod linear_algebra;
+pub mod optimise;
 pub mod test_utils;
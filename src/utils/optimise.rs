@@ -0,0 +1,229 @@
+// Sources of equations:
+//  - Nelder & Mead (1965), A simplex method for function minimization
+//  - Gao & Han (2012), Implementing the Nelder-Mead simplex algorithm with adaptive parameters
+//  - Marquardt (1963), An algorithm for least-squares estimation of nonlinear parameters
+
+use super::linear_algebra::{gram, solve, transpose_times};
+
+#[derive(Debug)]
+pub struct Minimum {
+    pub point: Vec<f64>,
+    pub value: f64,
+    pub iterations: usize,
+    pub converged: bool,
+}
+
+// Derivative free minimisation, an objective of infinity marks a point as infeasible. Converges
+// once the objective values across the simplex lie within `tolerance` of each other
+pub fn nelder_mead<F: Fn(&[f64]) -> f64>(
+    objective: F,
+    start: &[f64],
+    steps: &[f64],
+    tolerance: f64,
+    max_iterations: usize,
+) -> Minimum {
+    let dimension = start.len();
+    let n = dimension as f64;
+    let (reflection, expansion) = (1.0, 1.0 + 2.0 / n);
+    let (contraction, shrink) = (0.75 - 0.5 / n, 1.0 - 1.0 / n);
+
+    let mut simplex: Vec<(Vec<f64>, f64)> = (0..=dimension)
+        .map(|vertex| {
+            let mut point = start.to_vec();
+            if vertex > 0 {
+                point[vertex - 1] += steps[vertex - 1];
+            }
+            let value = objective(&point);
+            (point, value)
+        })
+        .collect();
+    let towards = |from: &[f64], to: &[f64], scale: f64| -> Vec<f64> {
+        from.iter()
+            .zip(to)
+            .map(|(from, to)| from + scale * (to - from))
+            .collect()
+    };
+
+    for iteration in 0..max_iterations {
+        simplex.sort_by(|lhs, rhs| lhs.1.total_cmp(&rhs.1));
+        let (best, worst) = (simplex[0].1, simplex[dimension].1);
+        if (worst - best).abs() <= tolerance * (1.0 + best.abs()) {
+            let (point, value) = simplex.swap_remove(0);
+            return Minimum {
+                point,
+                value,
+                iterations: iteration,
+                converged: true,
+            };
+        }
+        let centroid: Vec<f64> = (0..dimension)
+            .map(|i| {
+                simplex[..dimension]
+                    .iter()
+                    .map(|(point, _)| point[i])
+                    .sum::<f64>()
+                    / n
+            })
+            .collect();
+        let worst_point = simplex[dimension].0.clone();
+
+        let reflected = towards(&centroid, &worst_point, -reflection);
+        let reflected_value = objective(&reflected);
+        if reflected_value < best {
+            let expanded = towards(&centroid, &worst_point, -expansion);
+            let expanded_value = objective(&expanded);
+            simplex[dimension] = if expanded_value < reflected_value {
+                (expanded, expanded_value)
+            } else {
+                (reflected, reflected_value)
+            };
+            continue;
+        }
+        if reflected_value < simplex[dimension - 1].1 {
+            simplex[dimension] = (reflected, reflected_value);
+            continue;
+        }
+        let (contracted, contracted_value) = if reflected_value < worst {
+            let outside = towards(&centroid, &reflected, contraction);
+            let value = objective(&outside);
+            (outside, value)
+        } else {
+            let inside = towards(&centroid, &worst_point, contraction);
+            let value = objective(&inside);
+            (inside, value)
+        };
+        if contracted_value < reflected_value.min(worst) {
+            simplex[dimension] = (contracted, contracted_value);
+            continue;
+        }
+        let best_point = simplex[0].0.clone();
+        for vertex in simplex.iter_mut().skip(1) {
+            vertex.0 = towards(&best_point, &vertex.0, shrink);
+            vertex.1 = objective(&vertex.0);
+        }
+    }
+    simplex.sort_by(|lhs, rhs| lhs.1.total_cmp(&rhs.1));
+    let (point, value) = simplex.swap_remove(0);
+    Minimum {
+        point,
+        value,
+        iterations: max_iterations,
+        converged: false,
+    }
+}
+
+fn clamp_to(point: &[f64], bounds: &[(f64, f64)]) -> Vec<f64> {
+    point
+        .iter()
+        .zip(bounds)
+        .map(|(value, (lower, upper))| value.clamp(*lower, *upper))
+        .collect()
+}
+
+fn sum_of_squares(residuals: &[f64]) -> f64 {
+    residuals.iter().map(|residual| residual.powi(2)).sum()
+}
+
+// Forward difference Jacobian of the residuals, rows per residual and columns per parameter.
+// Steps are taken away from any bound the parameter sits on
+pub fn jacobian<F: Fn(&[f64]) -> Option<Vec<f64>>>(
+    residuals: &F,
+    point: &[f64],
+    bounds: &[(f64, f64)],
+) -> Option<Vec<Vec<f64>>> {
+    let base = residuals(point)?;
+    let columns: Option<Vec<Vec<f64>>> = (0..point.len())
+        .map(|i| {
+            let (_, upper) = bounds[i];
+            let mut step = 1e-6 * point[i].abs().max(1e-2);
+            if point[i] + step > upper {
+                step = -step;
+            }
+            let mut bumped = point.to_vec();
+            bumped[i] += step;
+            let bumped = residuals(&bumped)?;
+            Some(
+                bumped
+                    .iter()
+                    .zip(&base)
+                    .map(|(bumped, base)| (bumped - base) / step)
+                    .collect(),
+            )
+        })
+        .collect();
+    let columns = columns?;
+    Some(
+        (0..base.len())
+            .map(|row| columns.iter().map(|column| column[row]).collect())
+            .collect(),
+    )
+}
+
+// Minimises the sum of squared residuals within box bounds, a residual function returning None
+// marks a point as infeasible. Converges once a step improves the sum of squares by less than
+// `tolerance` relative to it, or no damping finds a better point
+pub fn levenberg_marquardt<F: Fn(&[f64]) -> Option<Vec<f64>>>(
+    residuals: F,
+    start: &[f64],
+    bounds: &[(f64, f64)],
+    tolerance: f64,
+    max_iterations: usize,
+) -> Minimum {
+    let mut point = clamp_to(start, bounds);
+    let mut value = residuals(&point).map_or(f64::INFINITY, |r| sum_of_squares(&r));
+    let mut damping = 1e-3;
+    let minimum = |point, value, iterations, converged| Minimum {
+        point,
+        value,
+        iterations,
+        converged,
+    };
+    if !value.is_finite() {
+        return minimum(point, value, 0, false);
+    }
+
+    for iteration in 0..max_iterations {
+        let (Some(jacobian), Some(current)) =
+            (jacobian(&residuals, &point, bounds), residuals(&point))
+        else {
+            return minimum(point, value, iteration, false);
+        };
+        let curvature = gram(&jacobian);
+        let gradient = transpose_times(&jacobian, &current);
+        loop {
+            let damped: Vec<Vec<f64>> = curvature
+                .iter()
+                .enumerate()
+                .map(|(i, row)| {
+                    let mut row = row.clone();
+                    row[i] += damping * row[i].max(1e-12);
+                    row
+                })
+                .collect();
+            let negative_gradient: Vec<f64> = gradient.iter().map(|g| -g).collect();
+            let candidate = solve(&damped, &negative_gradient).map(|step| {
+                let moved: Vec<f64> = point.iter().zip(&step).map(|(x, dx)| x + dx).collect();
+                clamp_to(&moved, bounds)
+            });
+            let candidate_value = candidate
+                .as_ref()
+                .and_then(|candidate| residuals(candidate))
+                .map_or(f64::INFINITY, |r| sum_of_squares(&r));
+            if candidate_value < value {
+                let improvement = value - candidate_value;
+                point = candidate.unwrap_or(point);
+                value = candidate_value;
+                damping = (damping / 10.0).max(1e-12);
+                if improvement <= tolerance * value.max(f64::MIN_POSITIVE) {
+                    return minimum(point, value, iteration + 1, true);
+                }
+                break;
+            }
+            damping *= 10.0;
+            if damping > 1e12 {
+                return minimum(point, value, iteration + 1, true);
+            }
+        }
+    }
+    minimum(point, value, max_iterations, false)
+}
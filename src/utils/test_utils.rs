@@ -1,6 +1,9 @@
 use crate::black_scholes::BlackScholes;
 use crate::option::{get_call, get_put, Call, Put};
 use crate::risk_factors::discount::rfr_discount;
+use crate::risk_factors::dividend::{
+    Dividend, DividendPayment, DividendSchedule, ScheduledDividend,
+};
 use crate::risk_factors::RiskFactors;
 use crate::symbol::Symbol;
 
@@ -81,6 +84,18 @@ pub fn get_test_ls_put() -> (Put, DateTime<Utc>, RiskFactors) {
     (put, begin_date, risk_factors)
 }
 
+// A single dividend going ex halfway through the test evaluation period
+pub fn get_test_dividend_schedule(payment: DividendPayment) -> Dividend {
+    let (begin_date, end_date) = get_test_evaluation_period();
+    Dividend::Schedule(DividendSchedule::new(
+        Symbol::from("AAPL"),
+        vec![ScheduledDividend {
+            ex_date: begin_date + (end_date - begin_date) / 2,
+            payment,
+        }],
+    ))
+}
+
 pub fn is_close(lhs: f64, rhs: f64, percentage_tolerance: f64) -> bool {
     let magnitude = (lhs.abs() + rhs.abs()) / 2.;
     let difference = (rhs - lhs).abs();